@@ -0,0 +1,51 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Dependencies whose resolved versions are reported by `--version`, and the env var each is
+// exposed to the crate as
+const REPORTED: &[(&str, &str)] = &[
+    ("ark-groth16", "ARK_GROTH16_VERSION"),
+    ("ark-bn254", "ARK_BN254_VERSION"),
+    ("ark-bls12-381", "ARK_BLS12_381_VERSION"),
+    ("ark-bw6-761", "ARK_BW6_761_VERSION"),
+    ("ark-circom", "ARK_CIRCOM_VERSION"),
+];
+
+// Version of `name` in the lockfile, with the commit for git dependencies
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let package = lock
+        .split("[[package]]")
+        .find(|package| package.contains(&format!("name = \"{}\"\n", name)))?;
+
+    let field = |key: &str| {
+        package.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix(" = \""))
+                .and_then(|rest| rest.strip_suffix('"'))
+        })
+    };
+
+    let version = field("version")?.to_string();
+    match field("source").and_then(|source| source.split_once('#')) {
+        Some((_, commit)) => Some(format!(
+            "{} (git {})",
+            version,
+            &commit[..7.min(commit.len())]
+        )),
+        None => Some(version),
+    }
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let lock_path = Path::new(&manifest_dir).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+
+    // Cargo.lock is not checked in, so a package built without one reports `unknown`
+    let lock = fs::read_to_string(&lock_path).unwrap_or_default();
+    for (name, var) in REPORTED {
+        let version = locked_version(&lock, name).unwrap_or_else(|| "unknown".to_string());
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
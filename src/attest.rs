@@ -0,0 +1,142 @@
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::types::{Address, Signature, U256};
+use ethers_core::utils::{hash_message, hex, secret_key_to_address};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const FORMAT: &str = "arkworks-bridge/verification-attestation/v1";
+
+/// What was verified and the outcome: the part of an attestation that is signed.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub format: String,
+    pub verified: bool,
+    /// Hex-encoded SHA-256 of the verifying key's untagged uncompressed serialization
+    pub verifying_key_sha256: String,
+    /// Hex-encoded SHA-256 of the proof's untagged uncompressed serialization
+    pub proof_sha256: String,
+    /// Hex-encoded `keccak256(abi.encodePacked(inputs))`, as `verify-proof --commitment` takes
+    pub inputs_keccak256: String,
+    /// Seconds since the Unix epoch when the proof was verified
+    pub verified_at: u64,
+}
+
+impl Claim {
+    pub fn new(
+        verified: bool,
+        verifying_key_sha256: String,
+        proof_sha256: String,
+        inputs_keccak256: String,
+    ) -> Self {
+        let verified_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Claim {
+            format: FORMAT.to_string(),
+            verified,
+            verifying_key_sha256,
+            proof_sha256,
+            inputs_keccak256,
+            verified_at,
+        }
+    }
+}
+
+/// A claim signed with a secp256k1 key. `claim` holds the claim's JSON exactly as signed, and
+/// `signature` is an EIP-191 `personal_sign` signature over those bytes, so the signer can be
+/// recovered from it, on chain with `ecrecover`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Attestation {
+    pub claim: String,
+    pub signer: String,
+    pub signature: String,
+}
+
+impl Attestation {
+    pub fn sign(claim: &Claim, key: &SigningKey) -> io::Result<Self> {
+        let claim = serde_json::to_string(claim)?;
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(hash_message(&claim).as_bytes())
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to sign attestation: {}", e),
+                )
+            })?;
+        let signature = Signature {
+            r: U256::from_big_endian(&signature.r().to_bytes()),
+            s: U256::from_big_endian(&signature.s().to_bytes()),
+            v: 27 + u64::from(recovery_id.to_byte()),
+        };
+        Ok(Attestation {
+            claim,
+            signer: format!("{:?}", secret_key_to_address(key)),
+            signature: format!("0x{}", hex::encode(signature.to_vec())),
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+
+    /// Check the signature against `signer` and return the signed claim.
+    pub fn verify(&self) -> io::Result<Claim> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let signature: Signature = self
+            .signature
+            .parse()
+            .map_err(|e| invalid(format!("Attestation signature is malformed: {}", e)))?;
+        let signer: Address = self
+            .signer
+            .parse()
+            .map_err(|e| invalid(format!("Attestation signer is malformed: {}", e)))?;
+        signature
+            .verify(self.claim.as_str(), signer)
+            .map_err(|e| invalid(format!("Attestation signature is invalid: {}", e)))?;
+
+        Ok(serde_json::from_str(&self.claim)?)
+    }
+}
+
+/// Parse a secp256k1 secret key from 32 hex-encoded bytes, with or without `0x`.
+pub fn parse_signing_key(key: &str) -> io::Result<SigningKey> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Signing key must be a hex-encoded 32-byte secp256k1 secret key",
+        )
+    };
+    let bytes = hex::decode(key.trim().trim_start_matches("0x")).map_err(|_| invalid())?;
+    SigningKey::from_slice(&bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = parse_signing_key(&format!("0x{:0>64}", "1")).unwrap();
+        let claim = Claim::new(true, "ab".repeat(32), "cd".repeat(32), "ef".repeat(32));
+        let attestation = Attestation::sign(&claim, &key).unwrap();
+
+        // The address of the secret key 1
+        assert_eq!(
+            attestation.signer,
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+        assert_eq!(attestation.verify().unwrap(), claim);
+
+        let forged = Attestation {
+            claim: attestation.claim.replace("true", "false"),
+            ..attestation
+        };
+        assert!(forged.verify().is_err());
+
+        assert!(parse_signing_key("0x1234").is_err());
+        assert!(parse_signing_key(&"0".repeat(64)).is_err());
+    }
+}
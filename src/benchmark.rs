@@ -0,0 +1,77 @@
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// The fastest of `runs` runs of `job`, so one-off costs like page faults don't count.
+pub fn best_of<T, F>(runs: usize, mut job: F) -> io::Result<Duration>
+where
+    F: FnMut() -> io::Result<T>,
+{
+    let mut best = None;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        job()?;
+        let elapsed = start.elapsed();
+        best = Some(best.map_or(elapsed, |b: Duration| b.min(elapsed)));
+    }
+    Ok(best.unwrap_or_default())
+}
+
+/// Time to get constraint matrices from the JSONL R1CS versus from a matrix cache.
+pub struct ParseTimings {
+    pub cold: Duration,
+    pub cached: Duration,
+}
+
+impl ParseTimings {
+    pub fn speedup(&self) -> f64 {
+        self.cold.as_secs_f64() / self.cached.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+impl fmt::Display for ParseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        writeln!(
+            f,
+            "{:<26} {:>12.3} ms",
+            "R1CS parse and synthesis",
+            ms(self.cold)
+        )?;
+        writeln!(
+            f,
+            "{:<26} {:>12.3} ms",
+            "matrix cache load",
+            ms(self.cached)
+        )?;
+        write!(f, "{:<26} {:>12.1}x", "speedup", self.speedup())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timings_report() {
+        let timings = ParseTimings {
+            cold: Duration::from_millis(30),
+            cached: Duration::from_millis(3),
+        };
+        assert!((timings.speedup() - 10.0).abs() < 1e-9);
+        assert_eq!(
+            timings.to_string(),
+            "R1CS parse and synthesis         30.000 ms\n\
+             matrix cache load                 3.000 ms\n\
+             speedup                            10.0x"
+        );
+
+        let mut calls = 0;
+        best_of(3, || -> io::Result<()> {
+            calls += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,139 @@
+use ethers_core::utils::hex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read, Seek, Write};
+use zip::result::ZipError;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+pub const MANIFEST: &str = "manifest.json";
+pub const PROVING_KEY: &str = "proving_key";
+pub const VERIFYING_KEY: &str = "verifying_key";
+pub const VERIFYING_KEY_ETH: &str = "verifying_key-eth.json";
+/// The R1CS file the setup was created from, as it was read
+pub const CIRCUIT: &str = "circuit";
+
+/// Describes what a setup bundle was created for and by.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    pub curve: String,
+    /// Hex-encoded SHA-256 of the R1CS file the setup was created from
+    pub circuit_sha256: String,
+    pub tool_version: String,
+    pub files: Vec<String>,
+}
+
+fn zip_error(description: &str, e: ZipError) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Other,
+        format!("Failed to {} setup bundle: {}", description, e),
+    )
+}
+
+/// Write the manifest followed by each named artifact into a zip archive.
+pub fn write_bundle<W: Write + Seek>(
+    writer: W,
+    manifest: &Manifest,
+    entries: &[(&str, Vec<u8>)],
+) -> io::Result<()> {
+    let mut zip = ZipWriter::new(writer);
+
+    zip.start_file(MANIFEST, FileOptions::default())
+        .map_err(|e| zip_error("write", e))?;
+    serde_json::to_writer_pretty(&mut zip, manifest)?;
+
+    for (name, bytes) in entries {
+        zip.start_file(*name, FileOptions::default())
+            .map_err(|e| zip_error("write", e))?;
+        zip.write_all(bytes)?;
+    }
+
+    zip.finish().map_err(|e| zip_error("write", e))?;
+    Ok(())
+}
+
+/// Read one artifact out of a setup bundle.
+pub fn read_entry<R: Read + Seek>(reader: R, name: &str) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(reader).map_err(|e| zip_error("read", e))?;
+    let mut file = archive.by_name(name).map_err(|e| match e {
+        ZipError::FileNotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Setup bundle does not contain {}", name),
+        ),
+        e => zip_error("read", e),
+    })?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn read_manifest<R: Read + Seek>(reader: R) -> io::Result<Manifest> {
+    Ok(serde_json::from_slice(&read_entry(reader, MANIFEST)?)?)
+}
+
+/// Hex-encoded SHA-256 of an R1CS file, as the manifest records it.
+pub fn circuit_sha256(r1cs: &[u8]) -> String {
+    hex::encode(Sha256::digest(r1cs))
+}
+
+/// Check the bundled circuit is the one the manifest records, so a bundle whose R1CS was
+/// swapped is not read as the setup of another circuit.
+pub fn check_circuit<R: Read + Seek>(reader: R, manifest: &Manifest) -> io::Result<()> {
+    let actual = circuit_sha256(&read_entry(reader, CIRCUIT)?);
+    if actual != manifest.circuit_sha256 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Setup bundle's circuit has SHA-256 {} but its manifest records {}",
+                actual, manifest.circuit_sha256
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_bundle_roundtrip() {
+        let manifest = Manifest {
+            curve: "bn254".to_string(),
+            circuit_sha256: "00".to_string(),
+            tool_version: "0.0.0".to_string(),
+            files: vec![PROVING_KEY.to_string()],
+        };
+
+        let mut bundle = Cursor::new(Vec::new());
+        write_bundle(&mut bundle, &manifest, &[(PROVING_KEY, vec![1, 2, 3])]).unwrap();
+
+        assert_eq!(read_manifest(&mut bundle).unwrap(), manifest);
+        assert_eq!(read_entry(&mut bundle, PROVING_KEY).unwrap(), vec![1, 2, 3]);
+
+        let err = read_entry(&mut bundle, VERIFYING_KEY).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_swapped_circuit() {
+        let manifest = Manifest {
+            curve: "bn254".to_string(),
+            circuit_sha256: circuit_sha256(b"r1cs"),
+            tool_version: "0.0.0".to_string(),
+            files: vec![CIRCUIT.to_string()],
+        };
+        let bundle = |r1cs: &[u8]| {
+            let mut bundle = Cursor::new(Vec::new());
+            write_bundle(&mut bundle, &manifest, &[(CIRCUIT, r1cs.to_vec())]).unwrap();
+            bundle
+        };
+
+        check_circuit(bundle(b"r1cs"), &manifest).unwrap();
+        let err = check_circuit(bundle(b"other r1cs"), &manifest).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("manifest records"));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::r1cs::{write_r1cs_file, R1CSFile, R1C};
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+const FINGERPRINT_DOMAIN: &[u8] = b"arkworks-bridge/r1cs-fingerprint/v1\n";
+
+type Terms<E> = Vec<(<E as Pairing>::ScalarField, usize)>;
+
+/// Sum the coefficients of repeated variables, drop zero terms and order by variable index.
+pub fn canonicalize_lc<E: Pairing>(terms: &[(E::ScalarField, usize)]) -> Terms<E> {
+    let mut summed: BTreeMap<usize, E::ScalarField> = BTreeMap::new();
+    for (coeff, var) in terms {
+        *summed.entry(*var).or_insert_with(E::ScalarField::zero) += coeff;
+    }
+
+    summed
+        .into_iter()
+        .filter(|(_, coeff)| !coeff.is_zero())
+        .map(|(var, coeff)| (coeff, var))
+        .collect()
+}
+
+pub fn canonicalize_constraint<E: Pairing>(constraint: &R1C<E>) -> R1C<E> {
+    R1C {
+        a: canonicalize_lc::<E>(&constraint.a),
+        b: canonicalize_lc::<E>(&constraint.b),
+        c: canonicalize_lc::<E>(&constraint.c),
+        // Equivalent constraints must compare equal wherever they came from
+        src: None,
+    }
+}
+
+// Constraints are ordered by their canonical terms, variable index first
+fn constraint_key<E: Pairing>(constraint: &R1C<E>) -> [Vec<(usize, E::ScalarField)>; 3] {
+    let key = |terms: &[(E::ScalarField, usize)]| {
+        terms
+            .iter()
+            .map(|(coeff, var)| (*var, *coeff))
+            .collect::<Vec<_>>()
+    };
+    [key(&constraint.a), key(&constraint.b), key(&constraint.c)]
+}
+
+/// Normalize an R1CS so that logically equivalent files serialize to identical bytes.
+///
+/// Every linear combination is canonicalized, constraints are sorted, the header's variable
+/// lists are sorted and deduplicated and `n_constraints` is recomputed.
+pub fn canonicalize<E: Pairing>(file: R1CSFile<E>) -> R1CSFile<E> {
+    let mut constraints: Vec<R1C<E>> = file
+        .constraints
+        .iter()
+        .map(canonicalize_constraint)
+        .collect();
+    constraints.sort_by_cached_key(constraint_key);
+
+    let mut header = file.header;
+    header.input_variables.sort_unstable();
+    header.input_variables.dedup();
+    header.output_variables.sort_unstable();
+    header.output_variables.dedup();
+    header.n_constraints = constraints.len();
+
+    R1CSFile {
+        header,
+        constraints,
+    }
+}
+
+/// Remove repeated constraints from a canonical R1CS, returning how many were dropped.
+///
+/// Canonical constraints are sorted, so duplicates are adjacent. Dropping a repeat leaves the
+/// set of satisfying witnesses unchanged.
+pub fn dedup<E: Pairing>(file: &mut R1CSFile<E>) -> usize {
+    let before = file.constraints.len();
+    file.constraints
+        .dedup_by(|a, b| constraint_key::<E>(a) == constraint_key::<E>(b));
+    file.header.n_constraints = file.constraints.len();
+    before - file.constraints.len()
+}
+
+/// SHA-256 of the canonical R1CS, the same for logically equivalent files however they are
+/// formatted.
+///
+/// Only the header's structural fields are hashed: a `curve` name is replaced by the field it
+/// stands for, and an absent `constant_index` by the 0 it defaults to.
+pub fn fingerprint<E: Pairing>(file: R1CSFile<E>) -> io::Result<[u8; 32]> {
+    let mut canonical = canonicalize(file);
+    canonical.header.constant_index = Some(canonical.header.constant_index());
+    canonical.header.curve = None;
+
+    let mut hasher = Sha256::new();
+    hasher.write_all(FINGERPRINT_DOMAIN)?;
+    write_r1cs_file(&canonical, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::{parse_r1cs_file, write_r1cs_file};
+    use ark_bn254::Bn254;
+
+    const HEADER: &str = r#"{"extension_degree":1,"field_characteristic":"21888242871839275222246405745257275088548364400416034343698204186575808495617","input_variables":[1],"n_constraints":2,"n_variables":3,"output_variables":[2]}"#;
+
+    fn canonical_bytes(r1cs: &str) -> Vec<u8> {
+        let file = parse_r1cs_file::<Bn254, _>(r1cs.as_bytes()).unwrap();
+        let mut out = Vec::new();
+        write_r1cs_file(&canonicalize(file), &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_equivalent_files_canonicalize_identically() {
+        let first = format!(
+            "{}\n{}\n{}\n",
+            HEADER,
+            r#"{"A":[["1",0]],"B":[["2",2],["3",1]],"C":[["0",0]]}"#,
+            r#"{"A":[["5",1]],"B":[["1",0]],"C":[["5",2]]}"#
+        );
+        let second = format!(
+            "{}\n{}\n{}\n",
+            HEADER,
+            r#"{"A":[["2",1],["3",1]],"B":[["1",0]],"C":[["5",2]]}"#,
+            r#"{"A":[["1",0]],"B":[["3",1],["1",2],["1",2]],"C":[]}"#
+        );
+
+        let canonical = canonical_bytes(&first);
+        assert_eq!(canonical, canonical_bytes(&second));
+
+        // Canonical output is a fixed point
+        assert_eq!(
+            canonical,
+            canonical_bytes(std::str::from_utf8(&canonical).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_formatting() {
+        let fingerprint =
+            |r1cs: &str| fingerprint(parse_r1cs_file::<Bn254, _>(r1cs.as_bytes()).unwrap());
+        let constraints = [
+            r#"{"A":[["1",0]],"B":[["2",2],["3",1]],"C":[["0",0]]}"#,
+            r#"{"A":[["5",1]],"B":[["1",0]],"C":[["5",2]]}"#,
+        ];
+        let first = format!("{}\n{}\n{}\n", HEADER, constraints[0], constraints[1]);
+        let expected = fingerprint(&first).unwrap();
+
+        // Reordered constraints and terms, split coefficients and a curve name in the header
+        let header = HEADER
+            .replace(
+                r#""field_characteristic":"21888242871839275222246405745257275088548364400416034343698204186575808495617""#,
+                r#""curve":"bn254""#,
+            )
+            .replace('}', r#","constant_index":0}"#);
+        let second = format!(
+            "{}\n{}\n{}\n",
+            header,
+            r#"{"A":[["2",1],["3",1]],"B":[["1",0]],"C":[["5",2]]}"#,
+            r#"{"A":[["1",0]],"B":[["3",1],["1",2],["1",2]],"C":[]}"#
+        );
+        assert_eq!(fingerprint(&second).unwrap(), expected);
+
+        let changed = first.replace(r#"["5",2]"#, r#"["6",2]"#);
+        assert_ne!(fingerprint(&changed).unwrap(), expected);
+        let more_inputs = first.replace(r#""input_variables":[1]"#, r#""input_variables":[1,2]"#);
+        assert_ne!(fingerprint(&more_inputs).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_dedup_removes_equivalent_constraints() {
+        let r1cs = format!(
+            "{}\n{}\n{}\n",
+            HEADER,
+            r#"{"A":[["5",1]],"B":[["1",0]],"C":[["5",2]]}"#,
+            r#"{"A":[["2",1],["3",1]],"B":[["1",0]],"C":[["5",2]]}"#
+        );
+        let mut file = canonicalize(parse_r1cs_file::<Bn254, _>(r1cs.as_bytes()).unwrap());
+
+        assert_eq!(dedup(&mut file), 1);
+        assert_eq!(file.constraints.len(), 1);
+        assert_eq!(file.header.n_constraints, 1);
+        assert_eq!(dedup(&mut file), 0);
+    }
+}
@@ -0,0 +1,213 @@
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::CanonicalSerialize;
+use ethers_core::utils::hex;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// What a results file records the verification of, written as its first line. A file is only
+/// resumed for the same batch, so results are never taken from another key, proofs or inputs.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Batch {
+    /// Hex-encoded SHA-256 of the compressed verifying key
+    pub verifying_key_sha256: String,
+    /// Hex-encoded SHA-256 of the compressed proofs, one after another
+    pub proofs_sha256: String,
+    /// Hex-encoded SHA-256 of the compressed public inputs, one after another
+    pub inputs_sha256: String,
+    /// Indices of the records that could not be read as proofs, which `proofs_sha256` skips
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unreadable: Vec<usize>,
+}
+
+// SHA-256 of the compressed serializations of `values`, one after another
+fn sha256<'a, T: CanonicalSerialize + 'a>(
+    values: impl IntoIterator<Item = &'a T>,
+) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    for value in values {
+        value
+            .serialize_compressed(&mut bytes)
+            .map_err(|e| invalid(e.to_string()))?;
+    }
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+impl Batch {
+    pub fn new<E: Pairing>(
+        verifying_key: &VerifyingKey<E>,
+        proofs: &[Proof<E>],
+        inputs: &[E::ScalarField],
+    ) -> io::Result<Self> {
+        Ok(Batch {
+            verifying_key_sha256: sha256([verifying_key])?,
+            proofs_sha256: sha256(proofs)?,
+            inputs_sha256: sha256(inputs)?,
+            unreadable: Vec::new(),
+        })
+    }
+}
+
+/// One line of a verification results file.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Record {
+    proof: usize,
+    verified: bool,
+}
+
+/// Verification results written one line per proof as each is verified, so that a run that is
+/// interrupted can resume from the proofs it already recorded.
+pub struct ResultsLog {
+    file: File,
+    /// Results recorded so far, by proof index, including those appended in this run
+    pub recorded: BTreeMap<usize, bool>,
+}
+
+impl ResultsLog {
+    /// Open or create a results file for `batch`, reading back what it already records. A file
+    /// written for another batch is refused. A last line cut off by a crash is dropped.
+    pub fn open(path: &Path, batch: &Batch) -> io::Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let complete = contents.rfind('\n').map_or(0, |end| end + 1);
+
+        let mut lines = contents[..complete].lines();
+        if let Some(line) = lines.next() {
+            let recorded: Batch = serde_json::from_str(line).map_err(|e| {
+                invalid(format!(
+                    "Results file does not start with the batch it records: {}",
+                    e
+                ))
+            })?;
+            if recorded != *batch {
+                return Err(invalid(format!(
+                    "Results file {} records another verifying key, proofs or inputs, remove it \
+                     to verify this batch",
+                    path.display()
+                )));
+            }
+        }
+
+        let mut recorded = BTreeMap::new();
+        for (i, line) in lines.enumerate() {
+            let record: Record = serde_json::from_str(line).map_err(|e| {
+                invalid(format!(
+                    "Results file line {} is not a result: {}",
+                    i + 2,
+                    e
+                ))
+            })?;
+            recorded.insert(record.proof, record.verified);
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if complete < contents.len() {
+            warn!(
+                "Dropping an incomplete last line of results file {:}",
+                path.display()
+            );
+            file.set_len(complete as u64)?;
+        }
+        if complete == 0 {
+            let mut line = serde_json::to_vec(batch)?;
+            line.push(b'\n');
+            file.write_all(&line)?;
+            file.flush()?;
+        }
+
+        Ok(ResultsLog { file, recorded })
+    }
+
+    /// Append the result of one proof, flushed before returning.
+    pub fn append(&mut self, proof: usize, verified: bool) -> io::Result<()> {
+        let mut line = serde_json::to_vec(&Record { proof, verified })?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        self.recorded.insert(proof, verified);
+        Ok(())
+    }
+
+    /// True if the batch has proofs and each of the `n_proofs` is recorded as verified.
+    pub fn all_verified(&self, n_proofs: usize) -> bool {
+        n_proofs > 0 && (0..n_proofs).all(|i| self.recorded.get(&i) == Some(&true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(proofs: &str) -> Batch {
+        Batch {
+            verifying_key_sha256: "ab".repeat(32),
+            proofs_sha256: proofs.repeat(32),
+            inputs_sha256: "ef".repeat(32),
+            unreadable: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_resume_from_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+        let header = serde_json::to_string(&batch("cd")).unwrap();
+        std::fs::write(
+            &path,
+            format!(
+                "{}\n{{\"proof\":0,\"verified\":true}}\n{{\"proof\":1,\"veri",
+                header
+            ),
+        )
+        .unwrap();
+
+        let mut log = ResultsLog::open(&path, &batch("cd")).unwrap();
+        assert_eq!(log.recorded, BTreeMap::from([(0, true)]));
+        assert!(log.all_verified(1));
+        assert!(!log.all_verified(2));
+        log.append(1, false).unwrap();
+        assert!(!log.all_verified(2));
+
+        let log = ResultsLog::open(&path, &batch("cd")).unwrap();
+        assert_eq!(log.recorded, BTreeMap::from([(0, true), (1, false)]));
+        assert!(!log.all_verified(0));
+
+        // Results of another batch are never reused
+        let err = ResultsLog::open(&path, &batch("00")).err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("another verifying key, proofs or inputs"));
+        // Nor those of a batch where other records could not be read
+        assert!(!header.contains("unreadable"));
+        let unreadable = Batch {
+            unreadable: vec![1],
+            ..batch("cd")
+        };
+        assert!(ResultsLog::open(&path, &unreadable).is_err());
+
+        std::fs::write(&path, format!("{}\nnot a result\n", header)).unwrap();
+        assert!(ResultsLog::open(&path, &batch("cd")).is_err());
+        std::fs::write(&path, "{\"proof\":0,\"verified\":true}\n").unwrap();
+        assert!(ResultsLog::open(&path, &batch("cd")).is_err());
+
+        // A new file starts with its batch
+        let path = dir.path().join("new.jsonl");
+        ResultsLog::open(&path, &batch("cd")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}\n", header)
+        );
+    }
+}
@@ -1,52 +1,82 @@
 use std::collections::HashMap;
 
-use crate::{r1cs::R1CS, witness::Witness};
+use crate::validate::{self, ConstraintViolation};
+use crate::{progress::Progress, r1cs::R1CS, witness::Witness};
 use ark_ec::pairing::Pairing;
 use ark_ff::fields::Field;
 use ark_relations::r1cs::{
     ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
 };
+use log::error;
+
+// `SynthesisError` can't carry the index, so log it before giving up
+fn lookup<F: Copy>(
+    values: &HashMap<usize, F>,
+    index: usize,
+    kind: &str,
+) -> Result<F, SynthesisError> {
+    values.get(&index).copied().ok_or_else(|| {
+        error!("Witness has no value for {} {}", kind, index);
+        SynthesisError::AssignmentMissing
+    })
+}
 
 #[derive(Clone, Debug)]
 pub struct Circuit<E: Pairing> {
     pub r1cs: R1CS<E>,
     pub witness: Option<Witness<E>>,
+    pub progress: Option<Progress>,
+}
+
+impl<E: Pairing> Circuit<E> {
+    /// [`validate::first_violation`] over this circuit's witness, or over no assignment at all if
+    /// it has none.
+    pub fn check_satisfied(&self) -> Result<(), ConstraintViolation<E>> {
+        match &self.witness {
+            Some(witness) => validate::first_violation(&self.r1cs, witness),
+            None => validate::first_violation(
+                &self.r1cs,
+                &Witness {
+                    constant_index: self.r1cs.constant_index,
+                    input_variables: HashMap::new(),
+                    witness_variables: HashMap::new(),
+                },
+            ),
+        }
+    }
 }
 
 impl<E: Pairing> ConstraintSynthesizer<E::ScalarField> for Circuit<E> {
     fn generate_constraints(
-        self: Self,
+        self,
         cs: ConstraintSystemRef<E::ScalarField>,
     ) -> Result<(), SynthesisError> {
         let mut input_mapping: HashMap<usize, Variable> = HashMap::new();
         let mut witness_mapping: HashMap<usize, Variable> = HashMap::new();
+        let constant_index = self.r1cs.constant_index;
 
         for v in self.r1cs.input_variables {
-            let var = cs.new_input_variable(|| {
-                Ok(match &self.witness {
-                    None => E::ScalarField::ONE,
-                    Some(witness) => witness.input_variables.get(&v).unwrap().clone(),
-                })
+            let var = cs.new_input_variable(|| match &self.witness {
+                None => Ok(E::ScalarField::ONE),
+                Some(witness) => lookup(&witness.input_variables, v, "public input"),
             })?;
             input_mapping.insert(v, var);
         }
 
         for v in self.r1cs.witness_variables {
-            let var = cs.new_witness_variable(|| {
-                Ok(match &self.witness {
-                    None => E::ScalarField::ONE,
-                    Some(witness) => witness.witness_variables.get(&v).unwrap().clone(),
-                })
+            let var = cs.new_witness_variable(|| match &self.witness {
+                None => Ok(E::ScalarField::ONE),
+                Some(witness) => lookup(&witness.witness_variables, v, "witness variable"),
             })?;
             witness_mapping.insert(v, var);
         }
 
         let make_index = |index| {
             if input_mapping.contains_key(&index) {
-                input_mapping.get(&index).unwrap().clone()
+                *input_mapping.get(&index).unwrap()
             } else if witness_mapping.contains_key(&index) {
-                witness_mapping.get(&index).unwrap().clone()
-            } else if index == 0 {
+                *witness_mapping.get(&index).unwrap()
+            } else if index == constant_index {
                 Variable::One
             } else {
                 // This isn't possible because we constructed the input and witness mappings
@@ -64,14 +94,97 @@ impl<E: Pairing> ConstraintSynthesizer<E::ScalarField> for Circuit<E> {
             )
         };
 
-        for constraint in &self.r1cs.constraints {
+        let n_constraints = self.r1cs.constraints.len();
+        for (i, constraint) in self.r1cs.constraints.iter().enumerate() {
             cs.enforce_constraint(
                 make_lc(&constraint.a),
                 make_lc(&constraint.b),
                 make_lc(&constraint.c),
             )?;
+            if let Some(progress) = &self.progress {
+                progress.synthesized(i + 1, n_constraints);
+            }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use crate::witness::parse_witness_file;
+    use ark_bn254::{Bn254, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn synthesize(witness: &str) -> Result<(), SynthesisError> {
+        let r1cs = parse_r1cs_file::<Bn254, _>(
+            include_str!("../test/resources/prog-r1cs.jsonl").as_bytes(),
+        )
+        .unwrap()
+        .into();
+        let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+        let circuit = Circuit {
+            r1cs,
+            witness: Some(witness),
+            progress: None,
+        };
+        circuit.generate_constraints(ConstraintSystem::<Fr>::new_ref())
+    }
+
+    #[test]
+    fn test_check_satisfied() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file::<Bn254, _>(
+            include_str!("../test/resources/prog-r1cs.jsonl").as_bytes(),
+        )
+        .unwrap()
+        .into();
+        let circuit = |witness: &str| Circuit {
+            r1cs: r1cs.clone(),
+            witness: Some(parse_witness_file(witness.as_bytes()).unwrap().into()),
+            progress: None,
+        };
+        let witness = include_str!("../test/resources/prog-witness.jsonl");
+        let header = witness.lines().next().unwrap();
+
+        assert_eq!(circuit(witness).check_satisfied(), Ok(()));
+        assert_eq!(
+            circuit(&format!("{}\n[2,\"66\"]", header)).check_satisfied(),
+            Err(ConstraintViolation::Unassigned {
+                index: 0,
+                src: None,
+                variable: 1
+            })
+        );
+
+        // 55 + 11·1 - 67 is -1, not 0
+        assert_eq!(
+            circuit(&format!("{}\n[1,\"1\"]\n[2,\"67\"]", header)).check_satisfied(),
+            Err(ConstraintViolation::Unsatisfied {
+                index: 0,
+                src: None,
+                lhs: -Fr::from(1),
+                rhs: Fr::from(0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_assignments_are_errors() {
+        let header = include_str!("../test/resources/prog-witness.jsonl")
+            .lines()
+            .next()
+            .unwrap();
+
+        assert!(synthesize(&format!("{}\n[1,\"1\"]\n[2,\"66\"]", header)).is_ok());
+        assert_eq!(
+            synthesize(&format!("{}\n[2,\"66\"]", header)),
+            Err(SynthesisError::AssignmentMissing)
+        );
+        assert_eq!(
+            synthesize(&format!("{}\n[1,\"1\"]", header)),
+            Err(SynthesisError::AssignmentMissing)
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use crate::field::JsonCoefficient;
+use crate::header::Header;
+use crate::r1cs::{check_constraint_count, R1CSFile, R1C};
+use crate::witness::WitnessFile;
+use ark_ec::pairing::Pairing;
+use serde::Deserialize;
+use std::io::{self, BufRead};
+
+// Every line after the header names what it holds in its `type` field
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", bound = "")]
+enum Line<E: Pairing> {
+    Constraint(R1C<E>),
+    Witness { var: usize, value: JsonCoefficient },
+}
+
+/// Read a combined file: the shared header, then `{"type":"constraint","A":..,"B":..,"C":..}`
+/// and `{"type":"witness","var":..,"value":".."}` lines in any order.
+pub fn parse_combined_file<E: Pairing, R: BufRead>(
+    reader: R,
+) -> io::Result<(R1CSFile<E>, WitnessFile<E>)> {
+    let mut lines = reader.lines();
+
+    let header_line = lines.next().ok_or(io::Error::new(
+        io::ErrorKind::NotFound,
+        "Combined file header line not found",
+    ))??;
+    let header: Header = serde_json::from_str(&header_line)?;
+
+    let mut constraints = Vec::new();
+    let mut witness = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        // Line numbers are 1-based and the header is line 1
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Line {} of the combined file: {}", i + 2, e),
+            )
+        };
+        match serde_json::from_str::<Line<E>>(&line).map_err(|e| invalid(e.to_string()))? {
+            Line::Constraint(constraint) => constraints.push(constraint),
+            Line::Witness { var, value } => witness.push((var, value.parse().map_err(invalid)?)),
+        }
+    }
+    check_constraint_count(&header, constraints.len())?;
+
+    Ok((
+        R1CSFile {
+            header: header.clone(),
+            constraints,
+        },
+        WitnessFile { header, witness },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::{parse_r1cs_file, R1CS};
+    use crate::witness::{parse_witness_file, Witness};
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn test_combined_matches_separate_files() {
+        let (r1cs_file, witness_file) =
+            parse_combined_file(include_str!("../test/resources/prog-combined.jsonl").as_bytes())
+                .unwrap();
+        let r1cs: R1CS<Bn254> = r1cs_file.into();
+        let witness: Witness<Bn254> = witness_file.into();
+
+        let expected_r1cs: R1CS<Bn254> =
+            parse_r1cs_file(include_str!("../test/resources/prog-r1cs.jsonl").as_bytes())
+                .unwrap()
+                .into();
+        let expected_witness: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        assert_eq!(r1cs.input_variables, expected_r1cs.input_variables);
+        assert_eq!(r1cs.witness_variables, expected_r1cs.witness_variables);
+        assert_eq!(r1cs.constraints.len(), 1);
+        assert_eq!(r1cs.constraints[0].b, expected_r1cs.constraints[0].b);
+        assert_eq!(witness.input_variables, expected_witness.input_variables);
+        assert_eq!(
+            witness.witness_variables,
+            expected_witness.witness_variables
+        );
+
+        let header = include_str!("../test/resources/prog-r1cs.jsonl")
+            .lines()
+            .next()
+            .unwrap();
+        let bad = format!("{}\n{{\"type\":\"output\",\"var\":2}}", header);
+        let err = parse_combined_file::<Bn254, _>(bad.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("Line 2 of the combined file"));
+    }
+
+    #[test]
+    fn test_truncated_combined_file() {
+        let combined = include_str!("../test/resources/prog-combined.jsonl");
+        // Cut off before the constraint line
+        let truncated = combined.lines().take(2).collect::<Vec<_>>().join("\n");
+        let err = parse_combined_file::<Bn254, _>(truncated.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("declares 1 constraints"));
+    }
+}
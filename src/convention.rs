@@ -0,0 +1,110 @@
+use ark_ec::pairing::Pairing;
+use arkworks_bridge::r1cs::R1CS;
+use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How the linear combinations of a constraint in the R1CS file relate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Convention {
+    /// `A·B = C`, what arkworks enforces
+    AbcEq,
+    /// `A·B + C = 0`
+    AbcZero,
+}
+
+impl Convention {
+    pub const VARIANTS: &'static [&'static str] = &["abc-eq", "abc-zero"];
+
+    /// Rewrite the constraints of `r1cs`, read in this convention, as `A·B = C`.
+    pub fn normalize<E: Pairing>(self, r1cs: &mut R1CS<E>) {
+        if self == Convention::AbcZero {
+            for constraint in &mut r1cs.constraints {
+                for (coeff, _) in &mut constraint.c {
+                    *coeff = -*coeff;
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Convention {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abc-eq" => Ok(Convention::AbcEq),
+            "abc-zero" => Ok(Convention::AbcZero),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown constraint convention {}, expected abc-eq or abc-zero",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+// Set once from the global `--constraint-convention` option before any command runs
+static ABC_ZERO: AtomicBool = AtomicBool::new(false);
+
+pub fn set(convention: Convention) {
+    ABC_ZERO.store(convention == Convention::AbcZero, Ordering::Relaxed);
+}
+
+/// The convention R1CS files are read in.
+pub fn current() -> Convention {
+    if ABC_ZERO.load(Ordering::Relaxed) {
+        Convention::AbcZero
+    } else {
+        Convention::AbcEq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use arkworks_bridge::circuit::Circuit;
+    use arkworks_bridge::r1cs::parse_r1cs_file;
+    use arkworks_bridge::validate::check_satisfied;
+    use arkworks_bridge::witness::{parse_witness_file, Witness};
+
+    const HEADER: &str = r#"{"extension_degree":1,"field_characteristic":"21888242871839275222246405745257275088548364400416034343698204186575808495617","input_variables":[1],"n_constraints":1,"n_variables":3,"output_variables":[2]}"#;
+
+    // x * x = y, written in each convention
+    fn r1cs(c: &str) -> R1CS<Bn254> {
+        let file = format!(
+            "{}\n{{\"A\":[[\"1\",1]],\"B\":[[\"1\",1]],\"C\":[[\"{}\",2]]}}\n",
+            HEADER, c
+        );
+        parse_r1cs_file(file.as_bytes()).unwrap().into()
+    }
+
+    #[test]
+    fn test_each_convention_is_satisfied() {
+        let witness = format!("{}\n[1,\"3\"]\n[2,\"9\"]\n", HEADER);
+        let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+
+        let mut abc_zero = r1cs("-1");
+        Convention::AbcEq.normalize(&mut abc_zero);
+        assert!(check_satisfied(&abc_zero, &witness).is_err());
+
+        for (c, convention) in [("1", Convention::AbcEq), ("-1", Convention::AbcZero)] {
+            let mut r1cs = r1cs(c);
+            convention.normalize(&mut r1cs);
+            check_satisfied(&r1cs, &witness).unwrap();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let circuit = Circuit {
+                r1cs,
+                witness: Some(witness.clone()),
+                progress: None,
+            };
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap(), "{:?}", convention);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_bw6_761::BW6_761;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use std::any::Any;
+use std::io;
+use std::str::FromStr;
+
+/// The curve a command proves over, chosen with the global `--curve` option.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Bn254,
+    Bls12_381,
+    Bw6_761,
+}
+
+impl Curve {
+    pub const VARIANTS: &'static [&'static str] = &["bn254", "bls12-381", "bw6-761"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Curve::Bn254 => "bn254",
+            Curve::Bls12_381 => "bls12-381",
+            Curve::Bw6_761 => "bw6-761",
+        }
+    }
+
+    /// Refuse `flag` unless the curve is BN254. Ethereum has precompiles for no other curve, and
+    /// gnark's layout is only implemented for it.
+    pub fn require_bn254(self, flag: &str) -> io::Result<()> {
+        match self {
+            Curve::Bn254 => Ok(()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is only supported with --curve bn254, not {}",
+                    flag,
+                    self.name()
+                ),
+            )),
+        }
+    }
+
+    /// Refuse the Ethereum output `flag` unless Ethereum has precompiles for the curve: EIP-197
+    /// for BN254 and EIP-2537 for BLS12-381.
+    pub fn require_ethereum(self, flag: &str) -> io::Result<()> {
+        match self {
+            Curve::Bn254 | Curve::Bls12_381 => Ok(()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is only supported with --curve bn254 or bls12-381, not {}",
+                    flag,
+                    self.name()
+                ),
+            )),
+        }
+    }
+}
+
+impl FromStr for Curve {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bn254" => Ok(Curve::Bn254),
+            "bls12-381" => Ok(Curve::Bls12_381),
+            "bw6-761" => Ok(Curve::Bw6_761),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown curve {}, expected bn254, bls12-381 or bw6-761", s),
+            )),
+        }
+    }
+}
+
+/// A pairing engine the generic commands can be run over, one for each `Curve`.
+pub trait SupportedCurve: Pairing {
+    const CURVE: Curve;
+
+    /// The scalar field element whose Montgomery form arkworks holds in memory is `limbs`.
+    fn from_montgomery(limbs: <Self::ScalarField as PrimeField>::BigInt) -> Self::ScalarField;
+}
+
+impl SupportedCurve for Bn254 {
+    const CURVE: Curve = Curve::Bn254;
+
+    fn from_montgomery(limbs: <Self::ScalarField as PrimeField>::BigInt) -> Self::ScalarField {
+        ark_bn254::Fr::new_unchecked(limbs)
+    }
+}
+
+impl SupportedCurve for Bls12_381 {
+    const CURVE: Curve = Curve::Bls12_381;
+
+    fn from_montgomery(limbs: <Self::ScalarField as PrimeField>::BigInt) -> Self::ScalarField {
+        ark_bls12_381::Fr::new_unchecked(limbs)
+    }
+}
+
+impl SupportedCurve for BW6_761 {
+    const CURVE: Curve = Curve::Bw6_761;
+
+    fn from_montgomery(limbs: <Self::ScalarField as PrimeField>::BigInt) -> Self::ScalarField {
+        ark_bw6_761::Fr::new_unchecked(limbs)
+    }
+}
+
+/// `value` as the BN254 type `B` it is when the curve is BN254, for the outputs only BN254 has.
+/// `None` over any other curve.
+pub fn as_bn254<T: Any, B: Any>(value: &T) -> Option<&B> {
+    (value as &dyn Any).downcast_ref()
+}
+
+/// `value` as the BLS12-381 type `B` it is when the curve is BLS12-381, for its EIP-2537 outputs.
+/// `None` over any other curve.
+pub fn as_bls12_381<T: Any, B: Any>(value: &T) -> Option<&B> {
+    (value as &dyn Any).downcast_ref()
+}
+
+/// The inverse of `as_bn254`: a BN254 `value` as the type `T` of the curve, if it is BN254.
+pub fn from_bn254<B: Any, T: Any>(value: B) -> Option<T> {
+    let value: Box<dyn Any> = Box::new(value);
+    value.downcast().ok().map(|value| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::curve_modulus;
+    use num_bigint::BigUint;
+
+    fn check_modulus<E: SupportedCurve>() {
+        let modulus: BigUint = E::ScalarField::MODULUS.into();
+        assert_eq!(curve_modulus(E::CURVE.name()), Some(modulus));
+        assert_eq!(E::CURVE.name().parse::<Curve>().unwrap(), E::CURVE);
+    }
+
+    #[test]
+    fn test_headers_name_every_curve() {
+        check_modulus::<Bn254>();
+        check_modulus::<Bls12_381>();
+        check_modulus::<BW6_761>();
+        assert!("bls12-377".parse::<Curve>().is_err());
+    }
+
+    #[test]
+    fn test_bn254_only_values() {
+        let inputs = vec![ark_bn254::Fr::from(1u64)];
+        assert_eq!(as_bn254::<_, Vec<ark_bn254::Fr>>(&inputs), Some(&inputs));
+        assert!(from_bn254::<_, Vec<ark_bls12_381::Fr>>(inputs).is_none());
+
+        assert!(Curve::Bn254.require_bn254("--ethereum").is_ok());
+        let err = Curve::Bls12_381.require_bn254("--ethereum").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--ethereum is only supported with --curve bn254, not bls12-381"
+        );
+
+        assert!(Curve::Bls12_381.require_ethereum("--ethereum").is_ok());
+        assert!(Curve::Bw6_761.require_ethereum("--ethereum").is_err());
+        let proof = ark_groth16::Proof::<ark_bls12_381::Bls12_381>::default();
+        assert!(as_bls12_381::<_, ark_groth16::Proof<ark_bls12_381::Bls12_381>>(&proof).is_some());
+    }
+}
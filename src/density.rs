@@ -0,0 +1,100 @@
+use crate::r1cs::R1C;
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use std::fmt;
+
+/// Non-zero entry counts for one of the A, B, C matrices.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MatrixDensity {
+    pub non_zeros: usize,
+    pub max_per_constraint: usize,
+}
+
+impl MatrixDensity {
+    fn add_row<F: Zero>(&mut self, terms: &[(F, usize)]) {
+        let non_zeros = terms.iter().filter(|(coeff, _)| !coeff.is_zero()).count();
+        self.non_zeros += non_zeros;
+        self.max_per_constraint = self.max_per_constraint.max(non_zeros);
+    }
+}
+
+/// Density of the constraint matrices, a rough predictor of MSM sizes and proving cost.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Density {
+    pub n_constraints: usize,
+    pub a: MatrixDensity,
+    pub b: MatrixDensity,
+    pub c: MatrixDensity,
+}
+
+impl Density {
+    pub fn of<E: Pairing>(constraints: &[R1C<E>]) -> Self {
+        let mut density = Density {
+            n_constraints: constraints.len(),
+            ..Default::default()
+        };
+        for constraint in constraints {
+            density.a.add_row(&constraint.a);
+            density.b.add_row(&constraint.b);
+            density.c.add_row(&constraint.c);
+        }
+        density
+    }
+
+    pub fn total_non_zeros(&self) -> usize {
+        self.a.non_zeros + self.b.non_zeros + self.c.non_zeros
+    }
+}
+
+impl fmt::Display for Density {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "constraints: {}", self.n_constraints)?;
+        for (name, matrix) in [("A", &self.a), ("B", &self.b), ("C", &self.c)] {
+            let average = if self.n_constraints == 0 {
+                0.0
+            } else {
+                matrix.non_zeros as f64 / self.n_constraints as f64
+            };
+            writeln!(
+                f,
+                "{}: {} non-zeros, {:.2} average / {} max terms per constraint",
+                name, matrix.non_zeros, average, matrix.max_per_constraint
+            )?;
+        }
+        write!(f, "total non-zeros: {}", self.total_non_zeros())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn test_density_of_fixture() {
+        let file = parse_r1cs_file::<Bn254, _>(
+            include_str!("../test/resources/prog-r1cs.jsonl").as_bytes(),
+        )
+        .unwrap();
+        let density = Density::of(&file.constraints);
+
+        // `["0",0]` in C is an explicit zero and does not count
+        assert_eq!(density.n_constraints, 1);
+        assert_eq!(
+            density.a,
+            MatrixDensity {
+                non_zeros: 1,
+                max_per_constraint: 1
+            }
+        );
+        assert_eq!(
+            density.b,
+            MatrixDensity {
+                non_zeros: 3,
+                max_per_constraint: 3
+            }
+        );
+        assert_eq!(density.c, MatrixDensity::default());
+    }
+}
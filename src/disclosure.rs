@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const FORMAT: &str = "arkworks-bridge/setup-disclosure/v1";
+
+pub const WARNING: &str = "This trusted setup was run by a single party. Whoever ran it could have \
+    kept the setup randomness (the toxic waste) and can then forge proofs that this verifying key \
+    accepts. Only rely on it where that party is trusted, or replace it with a multi-party ceremony.";
+
+/// Where the setup randomness came from.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Randomness {
+    /// The system's PRNG, never written anywhere
+    System,
+    /// Derived from an entropy file: anyone holding the file can recreate the toxic waste
+    EntropyFile,
+}
+
+/// States how a setup was made and who could forge proofs for it, for documenting provenance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Disclosure {
+    pub format: String,
+    pub single_party: bool,
+    pub tool_version: String,
+    /// Seconds since the Unix epoch when the setup finished
+    pub created_at: u64,
+    pub curve: String,
+    /// Hex-encoded SHA-256 of the R1CS file the setup was created from
+    pub circuit_sha256: String,
+    /// Hex-encoded SHA-256 of the verifying key's untagged uncompressed serialization
+    pub verifying_key_sha256: String,
+    pub randomness: Randomness,
+    pub warning: String,
+}
+
+impl Disclosure {
+    pub fn new(
+        curve: &str,
+        circuit_sha256: String,
+        verifying_key_sha256: String,
+        randomness: Randomness,
+    ) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Disclosure {
+            format: FORMAT.to_string(),
+            single_party: true,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at,
+            curve: curve.to_string(),
+            circuit_sha256,
+            verifying_key_sha256,
+            randomness,
+            warning: WARNING.to_string(),
+        }
+    }
+
+    /// Whether `other` discloses the same setup, whenever it was made.
+    pub fn same_setup(&self, other: &Disclosure) -> bool {
+        *self
+            == Disclosure {
+                created_at: self.created_at,
+                ..other.clone()
+            }
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_disclosure_json() {
+        let disclosure = Disclosure::new(
+            "bls12-381",
+            "ab".repeat(32),
+            "cd".repeat(32),
+            Randomness::EntropyFile,
+        );
+        let mut bytes = Vec::new();
+        disclosure.write(&mut bytes).unwrap();
+
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["format"], FORMAT);
+        assert_eq!(json["single_party"], true);
+        assert_eq!(json["curve"], "bls12-381");
+        assert_eq!(json["randomness"], "entropy_file");
+        assert!(json["created_at"].as_u64().unwrap() > 0);
+        assert!(json["warning"].as_str().unwrap().contains("forge proofs"));
+        assert_eq!(
+            serde_json::from_slice::<Disclosure>(&bytes).unwrap(),
+            disclosure
+        );
+
+        let later = Disclosure {
+            created_at: disclosure.created_at + 60,
+            ..disclosure.clone()
+        };
+        assert!(later.same_setup(&disclosure));
+        let other = Disclosure {
+            randomness: Randomness::System,
+            ..later
+        };
+        assert!(!other.same_setup(&disclosure));
+    }
+}
@@ -0,0 +1,139 @@
+use ark_bls12_381::{Bls12_381, Fq, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use ethers_core::utils::hex;
+use serde::{Deserialize, Serialize};
+
+// EIP-2537 pads each 48-byte base field element to 64 bytes, big-endian
+const FP_SIZE: usize = 64;
+pub const G1_SIZE: usize = 2 * FP_SIZE;
+pub const G2_SIZE: usize = 4 * FP_SIZE;
+
+fn write_fp(out: &mut Vec<u8>, value: &Fq) {
+    let bytes = value.into_bigint().to_bytes_be();
+    out.extend(vec![0; FP_SIZE - bytes.len()]);
+    out.extend(bytes);
+}
+
+/// A G1 point as the EIP-2537 precompiles take it: `x`, then `y`. The point at infinity is all
+/// zeros.
+pub fn g1_bytes(point: &G1Affine) -> Vec<u8> {
+    let mut out = Vec::with_capacity(G1_SIZE);
+    if point.infinity {
+        out.resize(G1_SIZE, 0);
+        return out;
+    }
+    write_fp(&mut out, &point.x);
+    write_fp(&mut out, &point.y);
+    out
+}
+
+/// A G2 point as the EIP-2537 precompiles take it: `x.c0`, `x.c1`, `y.c0`, `y.c1`. Unlike
+/// EIP-197, each coordinate is written real part first. The point at infinity is all zeros.
+pub fn g2_bytes(point: &G2Affine) -> Vec<u8> {
+    let mut out = Vec::with_capacity(G2_SIZE);
+    if point.infinity {
+        out.resize(G2_SIZE, 0);
+        return out;
+    }
+    write_fp(&mut out, &point.x.c0);
+    write_fp(&mut out, &point.x.c1);
+    write_fp(&mut out, &point.y.c0);
+    write_fp(&mut out, &point.y.c1);
+    out
+}
+
+fn hex_string(bytes: Vec<u8>) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// A BLS12-381 verifying key with every point hex-encoded for the EIP-2537 precompiles, the
+/// BLS12-381 counterpart of the BN254 eth-compatible json.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct EthVerifyingKey {
+    pub alpha1: String,
+    pub beta2: String,
+    pub gamma2: String,
+    pub delta2: String,
+    pub ic: Vec<String>,
+}
+
+impl From<&VerifyingKey<Bls12_381>> for EthVerifyingKey {
+    fn from(vk: &VerifyingKey<Bls12_381>) -> Self {
+        EthVerifyingKey {
+            alpha1: hex_string(g1_bytes(&vk.alpha_g1)),
+            beta2: hex_string(g2_bytes(&vk.beta_g2)),
+            gamma2: hex_string(g2_bytes(&vk.gamma_g2)),
+            delta2: hex_string(g2_bytes(&vk.delta_g2)),
+            ic: vk
+                .gamma_abc_g1
+                .iter()
+                .map(|point| hex_string(g1_bytes(point)))
+                .collect(),
+        }
+    }
+}
+
+/// A BLS12-381 proof with every point hex-encoded for the EIP-2537 precompiles, as the
+/// generated verifier's `verifyProof` takes it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct EthProof {
+    pub a: String,
+    pub b: String,
+    pub c: String,
+}
+
+impl From<&Proof<Bls12_381>> for EthProof {
+    fn from(proof: &Proof<Bls12_381>) -> Self {
+        EthProof {
+            a: hex_string(g1_bytes(&proof.a)),
+            b: hex_string(g2_bytes(&proof.b)),
+            c: hex_string(g1_bytes(&proof.c)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+
+    #[test]
+    fn test_generators() {
+        // The generators as EIP-2537 lists them
+        let g1 = hex::encode(g1_bytes(&G1Affine::generator()));
+        let pad = "0".repeat(32);
+        assert_eq!(
+            g1,
+            format!(
+                "{}{}{}{}",
+                pad,
+                "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+                pad,
+                "08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1"
+            )
+        );
+
+        let g2 = hex::encode(g2_bytes(&G2Affine::generator()));
+        assert_eq!(g2.len(), 2 * G2_SIZE);
+        assert_eq!(
+            &g2[..2 * FP_SIZE],
+            format!(
+                "{}{}",
+                pad,
+                "024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+            )
+        );
+        assert_eq!(
+            &g2[2 * FP_SIZE..4 * FP_SIZE],
+            format!(
+                "{}{}",
+                pad,
+                "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e"
+            )
+        );
+
+        assert_eq!(g1_bytes(&G1Affine::identity()), vec![0; G1_SIZE]);
+        assert_eq!(g2_bytes(&G2Affine::identity()), vec![0; G2_SIZE]);
+    }
+}
@@ -0,0 +1,42 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+
+/// Fewer bytes than this are rejected, since the file is meant to carry more entropy than a seed.
+pub const MIN_ENTROPY_BYTES: usize = 32;
+
+/// Seed a CSPRNG from the SHA-256 digest of everything in `reader`.
+pub fn rng_from_entropy<R: Read>(mut reader: R) -> io::Result<ChaCha20Rng> {
+    let mut entropy = Vec::new();
+    reader.read_to_end(&mut entropy)?;
+
+    if entropy.len() < MIN_ENTROPY_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Entropy file is too short: expected at least {} bytes, found {}",
+                MIN_ENTROPY_BYTES,
+                entropy.len()
+            ),
+        ));
+    }
+
+    Ok(ChaCha20Rng::from_seed(Sha256::digest(&entropy).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn test_rng_from_entropy() {
+        let entropy = [7u8; MIN_ENTROPY_BYTES];
+        let mut first = rng_from_entropy(&entropy[..]).unwrap();
+        let mut second = rng_from_entropy(&entropy[..]).unwrap();
+        assert_eq!(first.next_u64(), second.next_u64());
+
+        assert!(rng_from_entropy(&entropy[1..]).is_err());
+    }
+}
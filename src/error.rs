@@ -0,0 +1,173 @@
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// What went wrong, as far as a script calling the CLI cares. Each category has a stable exit code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// A file or argument that could not be parsed or is inconsistent
+    Parse,
+    /// A witness that does not satisfy the constraints
+    Unsatisfied,
+    /// A proof that did not verify
+    VerificationFailed,
+    /// Reading or writing a file, or any other failure outside the proof system
+    Io,
+    /// Setup or proving itself failed, including running out of `--time-limit`
+    Prove,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Parse => "parse",
+            Category::Unsatisfied => "unsatisfied",
+            Category::VerificationFailed => "verification_failed",
+            Category::Io => "io",
+            Category::Prove => "prove",
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Category::Parse => 2,
+            Category::Unsatisfied => 3,
+            Category::VerificationFailed => 4,
+            Category::Io => 5,
+            Category::Prove => 6,
+        }
+    }
+}
+
+// Carried inside an `io::Error` for the categories its kind can't tell apart
+#[derive(Debug)]
+struct Categorized {
+    category: Category,
+    message: String,
+}
+
+impl fmt::Display for Categorized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Categorized {}
+
+fn categorized(kind: io::ErrorKind, category: Category, message: String) -> io::Error {
+    io::Error::new(kind, Categorized { category, message })
+}
+
+pub fn verification_failed(message: &str) -> io::Error {
+    categorized(
+        io::ErrorKind::InvalidData,
+        Category::VerificationFailed,
+        message.to_string(),
+    )
+}
+
+pub fn unsatisfied(message: String) -> io::Error {
+    categorized(io::ErrorKind::InvalidData, Category::Unsatisfied, message)
+}
+
+pub fn prove_failed(message: String) -> io::Error {
+    categorized(io::ErrorKind::Other, Category::Prove, message)
+}
+
+/// The category of an error: the one it was created with, otherwise the one its kind implies.
+pub fn classify(error: &io::Error) -> Category {
+    if let Some(inner) = error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<Categorized>())
+    {
+        return inner.category;
+    }
+
+    match error.kind() {
+        io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput | io::ErrorKind::UnexpectedEof => {
+            Category::Parse
+        }
+        io::ErrorKind::TimedOut => Category::Prove,
+        _ => Category::Io,
+    }
+}
+
+/// Prefix the message of `error` with `context`, keeping its kind and category.
+pub fn with_context(error: io::Error, context: &str) -> io::Error {
+    with_message(&error, format!("{}: {}", context, error))
+}
+
+/// An error of the same kind and category as `error`, with another message.
+pub fn with_message(error: &io::Error, message: String) -> io::Error {
+    match error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<Categorized>())
+    {
+        Some(inner) => categorized(error.kind(), inner.category, message),
+        None => io::Error::new(error.kind(), message),
+    }
+}
+
+/// The error as `--error-json` prints it.
+pub fn to_json(error: &io::Error, command: Option<&str>) -> Value {
+    json!({
+        "error": error.to_string(),
+        "kind": classify(error).name(),
+        "command": command,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let error = verification_failed("Proof verification failed");
+        assert_eq!(classify(&error), Category::VerificationFailed);
+        assert_eq!(
+            to_json(&error, Some("run-r1cs")),
+            json!({
+                "error": "Proof verification failed",
+                "kind": "verification_failed",
+                "command": "run-r1cs",
+            })
+        );
+
+        let error = io::Error::new(io::ErrorKind::InvalidData, "bad header");
+        assert_eq!(classify(&error), Category::Parse);
+        let error = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(classify(&error), Category::Io);
+        let error = io::Error::from(io::ErrorKind::TimedOut);
+        assert_eq!(classify(&error), Category::Prove);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let cases = [
+            (io::Error::from(io::ErrorKind::UnexpectedEof), 2),
+            (unsatisfied("constraint 0 is not satisfied".to_string()), 3),
+            (verification_failed("Proof verification failed"), 4),
+            (io::Error::from(io::ErrorKind::PermissionDenied), 5),
+            (prove_failed("Failed to create proof".to_string()), 6),
+        ];
+        for (error, code) in cases {
+            assert_eq!(classify(&error).exit_code(), code, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_context_keeps_category() {
+        let error = with_context(
+            unsatisfied("constraint 0 is not satisfied".to_string()),
+            "constraint check failed",
+        );
+        assert_eq!(classify(&error), Category::Unsatisfied);
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "constraint check failed: constraint 0 is not satisfied"
+        );
+    }
+}
@@ -0,0 +1,737 @@
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_circom::ethereum as circom_eth;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::VerifyingKey;
+use ethers_core::abi::{self, Token};
+use ethers_core::types::transaction::eip712::{EIP712Domain, Eip712DomainType, TypedData};
+use ethers_core::types::U256;
+use ethers_core::utils::{hex, keccak256};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io;
+use std::str::FromStr;
+
+pub const EIP712_PROOF_TYPE: &str = "Groth16Proof";
+
+/// The uint256 word Solidity sees for a scalar field element
+pub fn fr_to_u256(value: &Fr) -> U256 {
+    U256::from_big_endian(&value.into_bigint().to_bytes_be())
+}
+
+/// Keccak-256 of the public inputs as 32-byte big-endian words, the same as
+/// `keccak256(abi.encodePacked(input))` over the `uint256[]` a verifier contract receives.
+pub fn inputs_commitment(inputs: &[Fr]) -> [u8; 32] {
+    let mut words = Vec::with_capacity(32 * inputs.len());
+    for input in inputs {
+        let mut word = [0u8; 32];
+        fr_to_u256(input).to_big_endian(&mut word);
+        words.extend_from_slice(&word);
+    }
+    keccak256(words)
+}
+
+/// Keccak-256 of the public inputs ABI-encoded as a dynamic `uint256[]`, the same as
+/// `keccak256(abi.encode(inputs))` in a contract that holds them in a `uint256[]`. For the
+/// generated verifier's fixed-size `uint[N]` the encoding has no offset or length and
+/// `inputs_commitment` applies instead.
+pub fn inputs_abi_commitment(inputs: &[Fr]) -> [u8; 32] {
+    let words = inputs.iter().map(|v| Token::Uint(fr_to_u256(v))).collect();
+    keccak256(abi::encode(&[Token::Array(words)]))
+}
+
+/// Domain separator at the start of every `transcript_hash` preimage.
+pub const TRANSCRIPT_DOMAIN: &[u8] = b"arkworks-bridge/groth16-transcript/v1";
+
+fn push_word(out: &mut Vec<u8>, value: U256) {
+    let mut word = [0u8; 32];
+    value.to_big_endian(&mut word);
+    out.extend_from_slice(&word);
+}
+
+fn push_g1(out: &mut Vec<u8>, point: &circom_eth::G1) {
+    push_word(out, point.x);
+    push_word(out, point.y);
+}
+
+fn push_g2(out: &mut Vec<u8>, point: &circom_eth::G2) {
+    let point = G2Order::Eth.apply(point);
+    for value in point.x.into_iter().chain(point.y) {
+        push_word(out, value);
+    }
+}
+
+/// Keccak-256 binding a proof to its verifying key and public inputs, for use in a larger
+/// Fiat-Shamir transcript. The preimage is `TRANSCRIPT_DOMAIN` followed by 32-byte big-endian
+/// words, with G1 points as `x, y` and G2 points in EIP-197 order `x.c1, x.c0, y.c1, y.c0`:
+/// `alpha1, beta2, gamma2, delta2`, the number of IC points then each of them, the proof's
+/// `A, B, C`, then the number of inputs and each input.
+pub fn transcript_hash(
+    vk: &circom_eth::VerifyingKey,
+    proof: &circom_eth::Proof,
+    inputs: &[Fr],
+) -> [u8; 32] {
+    let mut preimage = TRANSCRIPT_DOMAIN.to_vec();
+
+    push_g1(&mut preimage, &vk.alpha1);
+    push_g2(&mut preimage, &vk.beta2);
+    push_g2(&mut preimage, &vk.gamma2);
+    push_g2(&mut preimage, &vk.delta2);
+    push_word(&mut preimage, U256::from(vk.ic.len()));
+    for point in &vk.ic {
+        push_g1(&mut preimage, point);
+    }
+
+    push_g1(&mut preimage, &proof.a);
+    push_g2(&mut preimage, &proof.b);
+    push_g1(&mut preimage, &proof.c);
+
+    push_word(&mut preimage, U256::from(inputs.len()));
+    for input in inputs {
+        push_word(&mut preimage, fr_to_u256(input));
+    }
+
+    keccak256(preimage)
+}
+
+/// Calldata calling the generated Solidity verifier's
+/// `verifyProof(uint[2] a, uint[2][2] b, uint[2] c, uint[N] input)`. Its arguments are all
+/// fixed-size arrays, which the ABI encodes in place, so after the selector every value is one
+/// word, with `b` in EIP-197 order.
+pub fn verify_proof_calldata(proof: &circom_eth::Proof, inputs: &[Fr]) -> Vec<u8> {
+    let signature = format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+        inputs.len()
+    );
+    let mut calldata = keccak256(signature)[..4].to_vec();
+
+    push_g1(&mut calldata, &proof.a);
+    push_g2(&mut calldata, &proof.b);
+    push_g1(&mut calldata, &proof.c);
+    for input in inputs {
+        push_word(&mut calldata, fr_to_u256(input));
+    }
+    calldata
+}
+
+/// Decode the `bool` a `verifyProof` call returned.
+pub fn decode_bool(output: &[u8]) -> io::Result<bool> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Verifier returned 0x{}, which is not an ABI-encoded bool",
+                hex::encode(output)
+            ),
+        )
+    };
+    match output {
+        [zeros @ .., last] if output.len() == 32 && zeros.iter().all(|b| *b == 0) => match last {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+/// Split a hex blob of concatenated 32-byte big-endian words, as a verifier contract receives its
+/// `uint256` inputs, into field elements in order. Words of at least the modulus are reduced,
+/// and their positions returned alongside the inputs.
+pub fn inputs_from_words(words: &str) -> io::Result<(Vec<Fr>, Vec<usize>)> {
+    let bytes = hex::decode(words.trim().trim_start_matches("0x")).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Input words are not valid hex: {}", e),
+        )
+    })?;
+    if bytes.len() % 32 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Input words are {} bytes, which is not a multiple of 32",
+                bytes.len()
+            ),
+        ));
+    }
+
+    let mut inputs = Vec::new();
+    let mut reduced = Vec::new();
+    for (i, word) in bytes.chunks(32).enumerate() {
+        let input = Fr::from_be_bytes_mod_order(word);
+        if input.into_bigint().to_bytes_be() != word {
+            reduced.push(i);
+        }
+        inputs.push(input);
+    }
+    Ok((inputs, reduced))
+}
+
+/// Each input as a `0x`-prefixed 32-byte big-endian word, the inverse of `inputs_from_words`.
+pub fn inputs_to_words(inputs: &[Fr]) -> Vec<String> {
+    inputs
+        .iter()
+        .map(|v| format!("0x{}", hex::encode(v.into_bigint().to_bytes_be())))
+        .collect()
+}
+
+/// How the two coefficients of each G2 coordinate are ordered in exported data.
+///
+/// arkworks and circom write `c0 + c1 * u` as `[c0, c1]`, while Ethereum's EIP-197 pairing
+/// precompile expects `[c1, c0]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum G2Order {
+    Eth,
+    Arkworks,
+}
+
+impl G2Order {
+    pub const VARIANTS: &'static [&'static str] = &["eth", "arkworks"];
+
+    /// Convert a point between arkworks order and this order. The swap is its own inverse, so
+    /// this also converts back.
+    pub fn apply(self, g2: &circom_eth::G2) -> circom_eth::G2 {
+        match self {
+            G2Order::Eth => circom_eth::G2 {
+                x: [g2.x[1], g2.x[0]],
+                y: [g2.y[1], g2.y[0]],
+            },
+            G2Order::Arkworks => circom_eth::G2 { x: g2.x, y: g2.y },
+        }
+    }
+
+    pub fn apply_to_proof(self, proof: &circom_eth::Proof) -> circom_eth::Proof {
+        circom_eth::Proof {
+            a: proof.a,
+            b: self.apply(&proof.b),
+            c: proof.c,
+        }
+    }
+
+    pub fn apply_to_vk(self, vk: &circom_eth::VerifyingKey) -> circom_eth::VerifyingKey {
+        circom_eth::VerifyingKey {
+            alpha1: vk.alpha1,
+            beta2: self.apply(&vk.beta2),
+            gamma2: self.apply(&vk.gamma2),
+            delta2: self.apply(&vk.delta2),
+            ic: vk.ic.clone(),
+        }
+    }
+
+    /// Convert an eth-compatible verifying key in this order to an arkworks one. The `From`
+    /// conversion panics on a point that is not on the curve, as the G2 points of a key written in
+    /// the other order are, so every point is checked first.
+    pub fn vk_from_eth(self, vk: &circom_eth::VerifyingKey) -> io::Result<VerifyingKey<Bn254>> {
+        let vk = self.apply_to_vk(vk);
+        check_g1("alpha1", &vk.alpha1)?;
+        for (name, point) in [
+            ("beta2", &vk.beta2),
+            ("gamma2", &vk.gamma2),
+            ("delta2", &vk.delta2),
+        ] {
+            check_g2(name, point).map_err(|e| {
+                let other = match self {
+                    G2Order::Eth => "arkworks",
+                    G2Order::Arkworks => "eth",
+                };
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "{}. If the key was written in {} G2 order, read it with --g2-order {}",
+                        e, other, other
+                    ),
+                )
+            })?;
+        }
+        for (i, point) in vk.ic.iter().enumerate() {
+            check_g1(&format!("ic[{}]", i), point)?;
+        }
+        Ok(vk.into())
+    }
+}
+
+// The base field element a coordinate word encodes, unless it is not below the modulus
+fn base_field_element(word: U256) -> Option<Fq> {
+    let mut bytes = [0u8; 32];
+    word.to_little_endian(&mut bytes);
+    let element = Fq::from_le_bytes_mod_order(&bytes);
+    (element.into_bigint().to_bytes_le() == bytes).then_some(element)
+}
+
+fn not_a_point(name: &str, group: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "Verifying key point {} is not on the curve or not in the {} subgroup",
+            name, group
+        ),
+    )
+}
+
+fn check_g1(name: &str, point: &circom_eth::G1) -> io::Result<()> {
+    let (x, y) = match (base_field_element(point.x), base_field_element(point.y)) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return Err(not_a_point(name, "G1")),
+    };
+    // (0, 0) is how the identity is written
+    let point = G1Affine::new_unchecked(x, y);
+    let identity = point.x.is_zero() && point.y.is_zero();
+    if identity || point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        Ok(())
+    } else {
+        Err(not_a_point(name, "G1"))
+    }
+}
+
+fn check_g2(name: &str, point: &circom_eth::G2) -> io::Result<()> {
+    let coordinate = |c: &[U256; 2]| -> Option<Fq2> {
+        Some(Fq2::new(
+            base_field_element(c[0])?,
+            base_field_element(c[1])?,
+        ))
+    };
+    let (x, y) = match (coordinate(&point.x), coordinate(&point.y)) {
+        (Some(x), Some(y)) => (x, y),
+        _ => return Err(not_a_point(name, "G2")),
+    };
+    let point = G2Affine::new_unchecked(x, y);
+    let identity = point.x.is_zero() && point.y.is_zero();
+    if identity || point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        Ok(())
+    } else {
+        Err(not_a_point(name, "G2"))
+    }
+}
+
+impl FromStr for G2Order {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "eth" => Ok(G2Order::Eth),
+            "arkworks" => Ok(G2Order::Arkworks),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown G2 order {}, expected eth or arkworks", s),
+            )),
+        }
+    }
+}
+
+/// Field names and layout of eth-compatible proof and verifying key json.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonNaming {
+    /// The serde layout of the ark-circom types: `{"a": {"x": .., "y": ..}, ..}`
+    Arkworks,
+    /// snarkjs `proof.json` and `verification_key.json`: projective coordinates as decimal
+    /// strings under `pi_a`, `vk_alpha_1`, `IC` and so on
+    Snarkjs,
+    /// The arkworks layout with top-level fields renamed, written `a=pi_a,b=pi_b`
+    Custom(BTreeMap<String, String>),
+}
+
+impl JsonNaming {
+    pub fn proof_json(&self, proof: &circom_eth::Proof) -> Value {
+        match self {
+            JsonNaming::Snarkjs => json!({
+                "pi_a": snarkjs_g1(&proof.a),
+                "pi_b": snarkjs_g2(&proof.b),
+                "pi_c": snarkjs_g1(&proof.c),
+                "protocol": "groth16",
+                "curve": "bn128",
+            }),
+            _ => self.rename(json!(proof)),
+        }
+    }
+
+    pub fn vk_json(&self, vk: &circom_eth::VerifyingKey) -> Value {
+        match self {
+            JsonNaming::Snarkjs => json!({
+                "protocol": "groth16",
+                "curve": "bn128",
+                "nPublic": vk.ic.len().saturating_sub(1),
+                "vk_alpha_1": snarkjs_g1(&vk.alpha1),
+                "vk_beta_2": snarkjs_g2(&vk.beta2),
+                "vk_gamma_2": snarkjs_g2(&vk.gamma2),
+                "vk_delta_2": snarkjs_g2(&vk.delta2),
+                "IC": vk.ic.iter().map(snarkjs_g1).collect::<Vec<_>>(),
+            }),
+            _ => self.rename(json!(vk)),
+        }
+    }
+
+    /// snarkjs json always has G2 coefficients in arkworks order, so it can't be written in eth order.
+    pub fn check_g2_order(&self, g2_order: G2Order) -> io::Result<()> {
+        match (self, g2_order) {
+            (JsonNaming::Snarkjs, G2Order::Eth) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "snarkjs json naming needs --g2-order arkworks, as snarkjs reads G2 coefficients in arkworks order",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn rename(&self, value: Value) -> Value {
+        match (self, value) {
+            (JsonNaming::Custom(names), Value::Object(fields)) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (names.get(&key).cloned().unwrap_or(key), value))
+                    .collect(),
+            ),
+            (_, value) => value,
+        }
+    }
+}
+
+impl FromStr for JsonNaming {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "arkworks" => Ok(JsonNaming::Arkworks),
+            "snarkjs" => Ok(JsonNaming::Snarkjs),
+            _ => s
+                .split(',')
+                .map(|rename| match rename.split_once('=') {
+                    Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+                        Ok((from.to_string(), to.to_string()))
+                    }
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Unknown json naming {}, expected arkworks, snarkjs or renames like a=pi_a,b=pi_b",
+                            s
+                        ),
+                    )),
+                })
+                .collect::<io::Result<_>>()
+                .map(JsonNaming::Custom),
+        }
+    }
+}
+
+// snarkjs writes affine points in projective form with z = 1
+fn snarkjs_g1(point: &circom_eth::G1) -> Value {
+    json!([point.x.to_string(), point.y.to_string(), "1"])
+}
+
+fn snarkjs_g2(point: &circom_eth::G2) -> Value {
+    let coordinate = |c: &[U256; 2]| [c[0].to_string(), c[1].to_string()];
+    json!([coordinate(&point.x), coordinate(&point.y), ["1", "0"]])
+}
+
+/// A known-answer test for checking other verifier implementations against this one.
+#[derive(Serialize, Debug)]
+pub struct TestVector {
+    pub verifying_key: circom_eth::VerifyingKey,
+    pub proof: circom_eth::Proof,
+    pub inputs: Vec<U256>,
+    pub expected: bool,
+}
+
+fn domain_type(name: &str, r#type: &str) -> Eip712DomainType {
+    Eip712DomainType {
+        name: name.to_string(),
+        r#type: r#type.to_string(),
+    }
+}
+
+/// Bundle an eth-compatible proof and its public inputs as EIP-712 typed data.
+///
+/// The message mirrors the arguments of the generated verifier's `verifyProof`. `proof` must
+/// already be in the G2 order the contract was generated for.
+pub fn eip712_typed_data(
+    proof: &circom_eth::Proof,
+    inputs: &[Fr],
+    domain: EIP712Domain,
+) -> TypedData {
+    let mut domain_fields = Vec::new();
+    if domain.name.is_some() {
+        domain_fields.push(domain_type("name", "string"));
+    }
+    if domain.version.is_some() {
+        domain_fields.push(domain_type("version", "string"));
+    }
+    if domain.chain_id.is_some() {
+        domain_fields.push(domain_type("chainId", "uint256"));
+    }
+    if domain.verifying_contract.is_some() {
+        domain_fields.push(domain_type("verifyingContract", "address"));
+    }
+
+    let mut types = BTreeMap::new();
+    types.insert("EIP712Domain".to_string(), domain_fields);
+    types.insert(
+        EIP712_PROOF_TYPE.to_string(),
+        vec![
+            domain_type("a", "uint256[2]"),
+            domain_type("b", "uint256[2][2]"),
+            domain_type("c", "uint256[2]"),
+            domain_type("input", "uint256[]"),
+        ],
+    );
+
+    let (a, b, c) = (&proof.a, &proof.b, &proof.c);
+    let words = |values: &[U256]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+    let mut message = BTreeMap::new();
+    message.insert("a".to_string(), json!(words(&[a.x, a.y])));
+    message.insert("b".to_string(), json!([words(&b.x), words(&b.y)]));
+    message.insert("c".to_string(), json!(words(&[c.x, c.y])));
+    message.insert(
+        "input".to_string(),
+        json!(words(&inputs.iter().map(fr_to_u256).collect::<Vec<_>>())),
+    );
+
+    TypedData {
+        domain,
+        types,
+        primary_type: EIP712_PROOF_TYPE.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+    use ethers_core::types::transaction::eip712::Eip712;
+
+    #[test]
+    fn test_inputs_from_words() {
+        let modulus = hex::encode(Fr::MODULUS.to_bytes_be());
+        let words = format!("0x{:0>64}{:0>64}", "2a", modulus);
+
+        let (inputs, reduced) = inputs_from_words(&words).unwrap();
+        assert_eq!(inputs, vec![Fr::from(42u64), Fr::from(0u64)]);
+        assert_eq!(reduced, vec![1]);
+
+        assert!(inputs_from_words("0x2a").is_err());
+        assert!(inputs_from_words("zz").is_err());
+    }
+
+    #[test]
+    fn test_inputs_to_words() {
+        let inputs = vec![Fr::from(42u64), -Fr::from(1u64)];
+        let words = inputs_to_words(&inputs);
+        assert_eq!(words[0], format!("0x{:0>64}", "2a"));
+        assert!(words.iter().all(|w| w.len() == 66));
+
+        let (roundtrip, reduced) = inputs_from_words(&words.concat().replace("0x", "")).unwrap();
+        assert_eq!(roundtrip, inputs);
+        assert!(reduced.is_empty());
+    }
+
+    #[test]
+    fn test_eip712_typed_data_encodes() {
+        let proof = circom_eth::Proof::default();
+        let domain = EIP712Domain {
+            name: Some("Groth16Verifier".to_string()),
+            version: Some("1".to_string()),
+            chain_id: Some(U256::from(1)),
+            ..Default::default()
+        };
+
+        let typed_data = eip712_typed_data(&proof, &[Fr::from(1u64), Fr::from(66u64)], domain);
+        assert_eq!(typed_data.types["EIP712Domain"].len(), 3);
+        assert_eq!(typed_data.message["input"], json!(["1", "66"]));
+
+        // The bundle must be hashable exactly as a signer would hash it
+        assert!(typed_data.encode_eip712().is_ok());
+    }
+
+    #[test]
+    fn test_transcript_hash_layout() {
+        let g1 = |x: u64, y: u64| circom_eth::G1 {
+            x: x.into(),
+            y: y.into(),
+        };
+        let g2 = |base: u64| circom_eth::G2 {
+            x: [base.into(), (base + 1).into()],
+            y: [(base + 2).into(), (base + 3).into()],
+        };
+        let vk = circom_eth::VerifyingKey {
+            alpha1: g1(1, 2),
+            beta2: g2(10),
+            gamma2: g2(20),
+            delta2: g2(30),
+            ic: vec![g1(3, 4), g1(5, 6)],
+        };
+        let proof = circom_eth::Proof {
+            a: g1(7, 8),
+            b: g2(40),
+            c: g1(9, 10),
+        };
+
+        // Written out word by word, with G2 coefficients swapped into EIP-197 order
+        let words: [u64; 29] = [
+            1, 2, 11, 10, 13, 12, 21, 20, 23, 22, 31, 30, 33, 32, 2, 3, 4, 5, 6, 7, 8, 41, 40, 43,
+            42, 9, 10, 1, 66,
+        ];
+        let mut preimage = TRANSCRIPT_DOMAIN.to_vec();
+        for word in words {
+            push_word(&mut preimage, U256::from(word));
+        }
+        assert_eq!(
+            transcript_hash(&vk, &proof, &[Fr::from(66u64)]),
+            keccak256(preimage)
+        );
+        assert_ne!(
+            transcript_hash(&vk, &proof, &[Fr::from(66u64)]),
+            transcript_hash(&vk, &proof, &[Fr::from(67u64)])
+        );
+    }
+
+    #[test]
+    fn test_verify_proof_calldata() {
+        let g2 = circom_eth::G2 {
+            x: [2.into(), 3.into()],
+            y: [4.into(), 5.into()],
+        };
+        let proof = circom_eth::Proof {
+            a: circom_eth::G1 {
+                x: 0.into(),
+                y: 1.into(),
+            },
+            b: g2,
+            c: circom_eth::G1 {
+                x: 6.into(),
+                y: 7.into(),
+            },
+        };
+        let calldata = verify_proof_calldata(&proof, &[Fr::from(8u64), Fr::from(9u64)]);
+
+        assert_eq!(
+            calldata[..4],
+            keccak256("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[2])")[..4]
+        );
+        let words: Vec<U256> = calldata[4..]
+            .chunks(32)
+            .map(U256::from_big_endian)
+            .collect();
+        let expected: Vec<U256> = [0u64, 1, 3, 2, 5, 4, 6, 7, 8, 9]
+            .into_iter()
+            .map(U256::from)
+            .collect();
+        assert_eq!(words, expected);
+
+        let mut word = [0u8; 32];
+        assert!(!decode_bool(&word).unwrap());
+        word[31] = 1;
+        assert!(decode_bool(&word).unwrap());
+        word[0] = 1;
+        assert!(decode_bool(&word).is_err());
+        assert!(decode_bool(&[]).is_err());
+    }
+
+    #[test]
+    fn test_inputs_commitment() {
+        let mut packed = [0u8; 64];
+        packed[31] = 1;
+        packed[63] = 66;
+        assert_eq!(
+            inputs_commitment(&[Fr::from(1u64), Fr::from(66u64)]),
+            keccak256(packed)
+        );
+        assert_ne!(
+            inputs_commitment(&[Fr::from(1u64)]),
+            inputs_commitment(&[Fr::from(2u64)])
+        );
+    }
+
+    #[test]
+    fn test_inputs_abi_commitment() {
+        // Offset of the array, its length, then the elements
+        let mut encoded = [0u8; 128];
+        encoded[31] = 0x20;
+        encoded[63] = 2;
+        encoded[95] = 1;
+        encoded[127] = 66;
+        assert_eq!(
+            inputs_abi_commitment(&[Fr::from(1u64), Fr::from(66u64)]),
+            keccak256(encoded)
+        );
+    }
+
+    #[test]
+    fn test_json_naming() {
+        let proof = circom_eth::Proof {
+            a: circom_eth::G1 {
+                x: U256::from(1),
+                y: U256::from(2),
+            },
+            ..Default::default()
+        };
+
+        let arkworks = JsonNaming::Arkworks.proof_json(&proof);
+        assert_eq!(arkworks, serde_json::to_value(proof).unwrap());
+
+        let snarkjs = JsonNaming::Snarkjs.proof_json(&proof);
+        assert_eq!(snarkjs["pi_a"], json!(["1", "2", "1"]));
+        assert_eq!(snarkjs["pi_b"][2], json!(["1", "0"]));
+
+        let custom: JsonNaming = "a=pi_a,c=pi_c".parse().unwrap();
+        let renamed = custom.proof_json(&proof);
+        assert_eq!(renamed["pi_a"], arkworks["a"]);
+        assert_eq!(renamed["b"], arkworks["b"]);
+        assert!(renamed.get("a").is_none());
+
+        assert!(JsonNaming::Snarkjs.check_g2_order(G2Order::Eth).is_err());
+        assert!(JsonNaming::Snarkjs
+            .check_g2_order(G2Order::Arkworks)
+            .is_ok());
+        assert!(custom.check_g2_order(G2Order::Eth).is_ok());
+
+        assert!("a=".parse::<JsonNaming>().is_err());
+        assert!("circom".parse::<JsonNaming>().is_err());
+    }
+
+    #[test]
+    fn test_g2_order() {
+        let g2 = circom_eth::G2 {
+            x: [U256::from(1), U256::from(2)],
+            y: [U256::from(3), U256::from(4)],
+        };
+
+        // Eth order is what the EIP-197 tuple encoding uses
+        let eth = G2Order::Eth.apply(&g2);
+        let (x, y) = g2.as_tuple();
+        assert_eq!((eth.x, eth.y), (x, y));
+        assert_eq!(G2Order::Eth.apply(&eth), g2);
+
+        assert_eq!(G2Order::Arkworks.apply(&g2), g2);
+
+        let vk = circom_eth::VerifyingKey {
+            beta2: g2,
+            gamma2: g2,
+            delta2: g2,
+            ..Default::default()
+        };
+        let vk = G2Order::Eth.apply_to_vk(&vk);
+        assert_eq!([vk.beta2, vk.gamma2, vk.delta2], [eth, eth, eth]);
+    }
+
+    #[test]
+    fn test_vk_from_eth() {
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: G1Affine::generator(),
+            beta_g2: G2Affine::generator(),
+            gamma_g2: G2Affine::generator(),
+            delta_g2: G2Affine::generator(),
+            gamma_abc_g1: vec![G1Affine::generator(), G1Affine::zero()],
+        };
+        let eth = G2Order::Eth.apply_to_vk(&vk.clone().into());
+        assert_eq!(G2Order::Eth.vk_from_eth(&eth).unwrap(), vk);
+
+        // Read in the wrong order the G2 points are off the curve, which is an error, not a panic
+        let err = G2Order::Arkworks.vk_from_eth(&eth).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("--g2-order eth"), "{}", err);
+
+        let mut unreduced = eth;
+        unreduced.ic[0].x = U256::MAX;
+        let err = G2Order::Eth.vk_from_eth(&unreduced).unwrap_err();
+        assert!(err.to_string().contains("ic[0]"), "{}", err);
+    }
+}
@@ -0,0 +1,123 @@
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use serde::Deserialize;
+use serde_json::Number;
+
+/// Decimal representation of a field element, suitable for the JSONL formats.
+///
+/// `Display` for arkworks fields trims every leading zero, which renders zero as "".
+pub fn to_decimal_string<F: PrimeField>(value: &F) -> String {
+    let value: BigUint = (*value).into();
+    value.to_string()
+}
+
+fn parse_integer<F: PrimeField>(s: &str) -> Result<F, String> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    let magnitude: BigUint = digits
+        .parse()
+        .map_err(|_| format!("Invalid field element {:?}", s))?;
+    let value = F::from(magnitude);
+    Ok(if negative { -value } else { value })
+}
+
+/// A coefficient as written in json: a string, or for small values a plain number.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum JsonCoefficient {
+    Text(String),
+    Number(Number),
+}
+
+impl JsonCoefficient {
+    /// The coefficient in the string form `parse_coefficient` reads. Only integer numbers that fit
+    /// in 64 bits are accepted, since json parsers may have rounded anything else.
+    pub fn into_string(self) -> Result<String, String> {
+        match self {
+            JsonCoefficient::Text(s) => Ok(s),
+            JsonCoefficient::Number(n) if n.is_u64() || n.is_i64() => Ok(n.to_string()),
+            JsonCoefficient::Number(n) => Err(format!(
+                "Coefficient {} is not a 64-bit integer, which json numbers can't hold exactly. Write it as a string instead, e.g. \"123456789012345678901234567890\" or \"1/3\"",
+                n
+            )),
+        }
+    }
+
+    pub fn parse<F: PrimeField>(self) -> Result<F, String> {
+        parse_coefficient(&self.into_string()?)
+    }
+}
+
+/// Whether `s` is already a field element: a plain decimal integer below the modulus. Parsing
+/// reduces anything else, such as negative values, fractions or values of at least the modulus.
+pub fn is_reduced<F: PrimeField>(s: &str) -> bool {
+    let modulus: BigUint = F::MODULUS.into();
+    s.bytes().all(|b| b.is_ascii_digit()) && s.parse::<BigUint>().is_ok_and(|value| value < modulus)
+}
+
+/// Parse a coefficient written as a decimal integer or as a fraction `"n/d"`, either of which may
+/// be negative. A fraction becomes `n * d^-1` in the field.
+pub fn parse_coefficient<F: PrimeField>(s: &str) -> Result<F, String> {
+    match s.split_once('/') {
+        None => parse_integer(s),
+        Some((numerator, denominator)) => {
+            let numerator: F = parse_integer(numerator)?;
+            let denominator: F = parse_integer(denominator)?;
+            let inverse = denominator
+                .inverse()
+                .ok_or_else(|| format!("Denominator of {:?} is zero in the field", s))?;
+            Ok(numerator * inverse)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_parse_rational_coefficients() {
+        let third: Fr = parse_coefficient("1/3").unwrap();
+        assert_eq!(third * Fr::from(3u64), Fr::from(1u64));
+
+        let value: Fr = parse_coefficient("-2/5").unwrap();
+        assert_eq!(value * Fr::from(5u64), -Fr::from(2u64));
+
+        assert_eq!(parse_coefficient::<Fr>("42").unwrap(), Fr::from(42u64));
+        assert_eq!(parse_coefficient::<Fr>("-1").unwrap(), -Fr::from(1u64));
+
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let err = parse_coefficient::<Fr>(&format!("1/{}", modulus)).unwrap_err();
+        assert!(err.contains("zero in the field"));
+        assert!(parse_coefficient::<Fr>("1/x").is_err());
+
+        assert!(is_reduced::<Fr>("0"));
+        assert!(is_reduced::<Fr>("66"));
+        assert!(!is_reduced::<Fr>(modulus));
+        assert!(!is_reduced::<Fr>("-1"));
+        assert!(!is_reduced::<Fr>("1/3"));
+    }
+
+    #[test]
+    fn test_numeric_and_string_coefficients() {
+        let parse = |json: &str| {
+            serde_json::from_str::<JsonCoefficient>(json)
+                .unwrap()
+                .parse::<Fr>()
+        };
+
+        assert_eq!(parse("2").unwrap(), Fr::from(2u64));
+        assert_eq!(parse("\"2\"").unwrap(), Fr::from(2u64));
+        assert_eq!(parse("-3").unwrap(), -Fr::from(3u64));
+        assert_eq!(parse("\"1/3\"").unwrap() * Fr::from(3u64), Fr::from(1u64));
+
+        assert!(parse("2.5").unwrap_err().contains("Write it as a string"));
+        assert!(parse("1e3").is_err());
+        // Beyond 64 bits json numbers become floats
+        assert!(parse("36893488147419103232").is_err());
+    }
+}
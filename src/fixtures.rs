@@ -0,0 +1,130 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const R1CS_SUFFIX: &str = "-r1cs.jsonl";
+
+/// An R1CS with the witness and public inputs that go with it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Fixture {
+    pub name: String,
+    pub r1cs: PathBuf,
+    pub witness: PathBuf,
+    pub inputs: PathBuf,
+}
+
+/// Every `NAME-r1cs.jsonl` in `dir` with a `NAME-witness.jsonl` and `NAME-inputs.jsonl` next to
+/// it, ordered by name.
+pub fn discover(dir: &Path) -> io::Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let file_name = entry?.file_name();
+        let name = match file_name.to_str().and_then(|f| f.strip_suffix(R1CS_SUFFIX)) {
+            Some(name) => name,
+            None => continue,
+        };
+        let witness = dir.join(format!("{}-witness.jsonl", name));
+        let inputs = dir.join(format!("{}-inputs.jsonl", name));
+        if witness.is_file() && inputs.is_file() {
+            fixtures.push(Fixture {
+                name: name.to_string(),
+                r1cs: dir.join(&file_name),
+                witness,
+                inputs,
+            });
+        }
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// The steps of a fixture check, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Validate,
+    Prove,
+    Verify,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 3] = [Stage::Validate, Stage::Prove, Stage::Verify];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Validate => "validate",
+            Stage::Prove => "prove",
+            Stage::Verify => "verify",
+        }
+    }
+}
+
+/// How far the check of one fixture got.
+#[derive(Debug)]
+pub struct Outcome {
+    pub name: String,
+    pub failure: Option<(Stage, io::Error)>,
+}
+
+/// One row per fixture, one column per stage. Stages after a failure did not run.
+pub struct Matrix<'a>(pub &'a [Outcome]);
+
+impl fmt::Display for Matrix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .0
+            .iter()
+            .map(|outcome| outcome.name.len())
+            .chain(Some("fixture".len()))
+            .max()
+            .unwrap_or_default();
+
+        let row = |name: &str, cells: [&str; 3]| {
+            let mut row = format!("{:<width$}", name, width = width);
+            for cell in cells {
+                row.push_str(&format!("  {:<8}", cell));
+            }
+            row.trim_end().to_string()
+        };
+
+        write!(f, "{}", row("fixture", Stage::ALL.map(Stage::name)))?;
+        for outcome in self.0 {
+            let cells = Stage::ALL.map(|stage| match &outcome.failure {
+                Some((failed, _)) if stage == *failed => "FAIL",
+                Some((failed, _)) if stage > *failed => "-",
+                _ => "pass",
+            });
+            write!(f, "\n{}", row(&outcome.name, cells))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_and_matrix() {
+        let fixtures = discover(Path::new("test/resources")).unwrap();
+        let prog = fixtures.iter().find(|f| f.name == "prog").unwrap();
+        assert_eq!(prog.witness, Path::new("test/resources/prog-witness.jsonl"));
+
+        let outcomes = [
+            Outcome {
+                name: "prog".to_string(),
+                failure: None,
+            },
+            Outcome {
+                name: "broken".to_string(),
+                failure: Some((Stage::Prove, io::Error::from(io::ErrorKind::Other))),
+            },
+        ];
+        assert_eq!(
+            Matrix(&outcomes).to_string(),
+            "fixture  validate  prove     verify\n\
+             prog     pass      pass      pass\n\
+             broken   pass      FAIL      -"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_groth16::VerifyingKey;
+use std::collections::BTreeMap;
+use std::io;
+
+/// Fold public inputs whose values are fixed at setup into the verifying key.
+///
+/// Verification adds each input times its IC point to `IC[0]`, so for a fixed input that term is
+/// added once here and the point dropped. The key then takes the remaining inputs, in the same
+/// order, and only accepts proofs made with the fixed values.
+pub fn fold_fixed_inputs<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    input_variables: &[usize],
+    fixed: &[(usize, E::ScalarField)],
+) -> io::Result<VerifyingKey<E>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+    // IC points follow the public inputs in variable index order
+    let mut positions = input_variables.to_vec();
+    positions.sort_unstable();
+    if vk.gamma_abc_g1.len() != positions.len() + 1 {
+        return Err(invalid(format!(
+            "Verifying key has {} IC points, but the circuit's {} public inputs need {}",
+            vk.gamma_abc_g1.len(),
+            positions.len(),
+            positions.len() + 1
+        )));
+    }
+
+    let mut values = BTreeMap::new();
+    for (index, value) in fixed {
+        let position = positions.binary_search(index).map_err(|_| {
+            invalid(format!(
+                "Fixed input variable {} is not a public input of the circuit",
+                index
+            ))
+        })?;
+        if values.insert(position, *value).is_some() {
+            return Err(invalid(format!(
+                "Fixed input variable {} is given more than once",
+                index
+            )));
+        }
+    }
+
+    let mut constant = vk.gamma_abc_g1[0].into_group();
+    for (position, value) in &values {
+        constant += vk.gamma_abc_g1[position + 1] * value;
+    }
+    let gamma_abc_g1 = std::iter::once(constant.into_affine())
+        .chain(
+            vk.gamma_abc_g1[1..]
+                .iter()
+                .enumerate()
+                .filter(|(position, _)| !values.contains_key(position))
+                .map(|(_, point)| *point),
+        )
+        .collect();
+
+    Ok(VerifyingKey {
+        gamma_abc_g1,
+        ..vk.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::{deserialize_from, Options};
+    use ark_bn254::{Bn254, Fr};
+    use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+
+    #[test]
+    fn test_folded_key_verifies_without_fixed_inputs() {
+        let vk: VerifyingKey<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/vk").as_slice(),
+            "verifying key",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+        let proof: Proof<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/proof").as_slice(),
+            "proof",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+        // The proof is for the input 1 at variable 1
+        let verifies = |value: u64| {
+            let folded = fold_fixed_inputs(&vk, &[1], &[(1, Fr::from(value))]).unwrap();
+            assert_eq!(folded.gamma_abc_g1.len(), 1);
+            Groth16::<Bn254>::verify_proof(&prepare_verifying_key(&folded), &proof, &[]).unwrap()
+        };
+        assert!(verifies(1));
+        assert!(!verifies(2));
+
+        // Folding nothing leaves the key as it was
+        assert_eq!(fold_fixed_inputs(&vk, &[1], &[]).unwrap(), vk);
+
+        assert!(fold_fixed_inputs(&vk, &[1], &[(2, Fr::from(1u64))]).is_err());
+        let twice = [(1, Fr::from(1u64)), (1, Fr::from(1u64))];
+        assert!(fold_fixed_inputs(&vk, &[1], &twice).is_err());
+        assert!(fold_fixed_inputs(&vk, &[1, 2], &[]).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use ark_bn254::{Bn254, Fq, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+
+// gnark-crypto keeps point metadata in the top two bits of the first byte. Uncompressed points
+// leave them clear, except for the point at infinity.
+const UNCOMPRESSED_INFINITY: u8 = 0b01 << 6;
+
+const FQ_SIZE: usize = 32;
+
+fn write_fq(out: &mut Vec<u8>, value: &Fq) {
+    out.extend(value.into_bigint().to_bytes_be());
+}
+
+fn write_infinity(out: &mut Vec<u8>, size: usize) {
+    out.push(UNCOMPRESSED_INFINITY);
+    out.extend(vec![0; size - 1]);
+}
+
+fn write_g1(out: &mut Vec<u8>, point: &G1Affine) {
+    if point.infinity {
+        return write_infinity(out, 2 * FQ_SIZE);
+    }
+    write_fq(out, &point.x);
+    write_fq(out, &point.y);
+}
+
+// Coefficients are written imaginary part first, as in EIP-197
+fn write_g2(out: &mut Vec<u8>, point: &G2Affine) {
+    if point.infinity {
+        return write_infinity(out, 4 * FQ_SIZE);
+    }
+    write_fq(out, &point.x.c1);
+    write_fq(out, &point.x.c0);
+    write_fq(out, &point.y.c1);
+    write_fq(out, &point.y.c0);
+}
+
+// gnark prefixes slices with their length as a big-endian u32
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend((len as u32).to_be_bytes());
+}
+
+/// A proof in gnark's raw BN254 encoding, as read by `groth16.Proof.ReadFrom`: `Ar`, `Bs`, `Krs`,
+/// then no commitments and an empty commitment proof of knowledge.
+pub fn proof_bytes(proof: &Proof<Bn254>) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_g1(&mut out, &proof.a);
+    write_g2(&mut out, &proof.b);
+    write_g1(&mut out, &proof.c);
+    write_len(&mut out, 0);
+    write_g1(&mut out, &G1Affine::identity());
+    out
+}
+
+/// A verifying key in gnark's raw BN254 encoding, as read by `groth16.VerifyingKey.ReadFrom`.
+/// gnark also stores `[β]1` and `[δ]1`, which only the proving key has, so both are needed.
+pub fn verifying_key_bytes(pk: &ProvingKey<Bn254>, vk: &VerifyingKey<Bn254>) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_g1(&mut out, &vk.alpha_g1);
+    write_g1(&mut out, &pk.beta_g1);
+    write_g2(&mut out, &vk.beta_g2);
+    write_g2(&mut out, &vk.gamma_g2);
+    write_g1(&mut out, &pk.delta_g1);
+    write_g2(&mut out, &vk.delta_g2);
+    write_len(&mut out, vk.gamma_abc_g1.len());
+    for point in &vk.gamma_abc_g1 {
+        write_g1(&mut out, point);
+    }
+    // No public inputs committed to, and no commitment keys
+    write_len(&mut out, 0);
+    write_len(&mut out, 0);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::{parse_r1cs_file, R1CS};
+    use crate::witness::{parse_witness_file, Witness};
+    use crate::{prove_with, setup_with};
+    use ark_ec::AffineRepr;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_gnark_layout() {
+        let r1cs: R1CS<Bn254> =
+            parse_r1cs_file(include_str!("../test/resources/prog-r1cs.jsonl").as_bytes())
+                .unwrap()
+                .into();
+        let witness: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+        let mut rng = StdRng::seed_from_u64(0);
+        let (pk, _) = setup_with(r1cs.clone(), &mut rng).unwrap();
+        let proof = prove_with(&pk, r1cs, witness, true, &mut rng).unwrap();
+
+        let bytes = proof_bytes(&proof);
+        assert_eq!(bytes.len(), 64 + 128 + 64 + 4 + 64);
+        assert_eq!(bytes[..32], proof.a.x.into_bigint().to_bytes_be());
+        assert_eq!(bytes[64..96], proof.b.x.c1.into_bigint().to_bytes_be());
+        assert_eq!(bytes[bytes.len() - 64], UNCOMPRESSED_INFINITY);
+
+        // One public input plus the constant one
+        let bytes = verifying_key_bytes(&pk, &pk.vk);
+        assert_eq!(bytes.len(), 3 * 64 + 3 * 128 + 4 + 2 * 64 + 4 + 4);
+        let k = 3 * 64 + 3 * 128;
+        assert_eq!(bytes[k..k + 4], [0, 0, 0, 2]);
+        assert_eq!(
+            bytes[64..96],
+            pk.beta_g1.x().unwrap().into_bigint().to_bytes_be()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::r1cs::R1CS;
+use ark_ec::pairing::Pairing;
+use std::io::{self, Write};
+
+const ONE_COLOR: &str = "lightgray";
+const INPUT_COLOR: &str = "lightblue";
+const WITNESS_COLOR: &str = "lightyellow";
+
+/// Write the circuit as a bipartite graphviz graph, with an edge from each constraint to every
+/// variable it touches labelled by the matrix (A, B or C) the term is in.
+pub fn write_dot<E: Pairing, W: Write>(r1cs: &R1CS<E>, mut writer: W) -> io::Result<()> {
+    writeln!(writer, "graph r1cs {{")?;
+    writeln!(writer, "  node [style=filled];")?;
+
+    writeln!(
+        writer,
+        "  v{} [label=\"1\", shape=ellipse, fillcolor={}];",
+        r1cs.constant_index, ONE_COLOR
+    )?;
+    for v in &r1cs.input_variables {
+        writeln!(
+            writer,
+            "  v{} [label=\"v{}\", shape=ellipse, fillcolor={}];",
+            v, v, INPUT_COLOR
+        )?;
+    }
+    for v in &r1cs.witness_variables {
+        writeln!(
+            writer,
+            "  v{} [label=\"v{}\", shape=ellipse, fillcolor={}];",
+            v, v, WITNESS_COLOR
+        )?;
+    }
+
+    for (i, constraint) in r1cs.constraints.iter().enumerate() {
+        writeln!(
+            writer,
+            "  c{} [label=\"c{}\", shape=box, fillcolor=white];",
+            i, i
+        )?;
+        for (name, terms) in [
+            ("A", &constraint.a),
+            ("B", &constraint.b),
+            ("C", &constraint.c),
+        ] {
+            for (_, var) in terms {
+                writeln!(writer, "  c{} -- v{} [label=\"{}\"];", i, var, name)?;
+            }
+        }
+    }
+
+    writeln!(writer, "}}")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn test_write_dot() {
+        let r1cs: R1CS<Bn254> =
+            parse_r1cs_file(include_str!("../test/resources/prog-r1cs.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        let mut out = Vec::new();
+        write_dot(&r1cs, &mut out).unwrap();
+        let dot = String::from_utf8(out).unwrap();
+
+        assert!(dot.starts_with("graph r1cs {"));
+        assert!(dot.contains("v1 [label=\"v1\", shape=ellipse, fillcolor=lightblue];"));
+        assert!(dot.contains("v2 [label=\"v2\", shape=ellipse, fillcolor=lightyellow];"));
+        assert!(dot.contains("c0 -- v2 [label=\"B\"];"));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+}
@@ -1,5 +1,5 @@
 use num_bigint::BigUint;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::Debug;
 use std::str::FromStr; // Import IntoDeserializer trait
 
@@ -12,13 +12,173 @@ where
     BigUint::from_str(&s).map_err(serde::de::Error::custom)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// Serialize BigUint as a decimal string, mirroring `deserialize_biguint`
+fn serialize_biguint<S>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+fn deserialize_optional_biguint<'de, D>(deserializer: D) -> Result<Option<BigUint>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_biguint(deserializer).map(Some)
+}
+
+const BN254_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// Scalar field moduli of the curves a header can name with `curve` instead of giving
+/// `field_characteristic`. `bn128` is circom's name for BN254.
+pub const KNOWN_CURVES: &[(&str, &str)] = &[
+    ("bn254", BN254_MODULUS),
+    ("bn128", BN254_MODULUS),
+    (
+        "bls12-381",
+        "52435875175126190479447740508185965837690552500527637822603658699938581184513",
+    ),
+    (
+        "bls12-377",
+        "8444461749428370424248824938781546531375899335154063827935233455917409239041",
+    ),
+    (
+        "bw6-761",
+        "258664426012969094010652733694893533536393512754914660539884262666720468348340822774968888139573360124440321458177",
+    ),
+];
+
+/// The scalar field modulus of a curve in `KNOWN_CURVES`.
+pub fn curve_modulus(name: &str) -> Option<BigUint> {
+    KNOWN_CURVES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, modulus)| BigUint::from_str(modulus).expect("known curve moduli are decimal"))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "RawHeader")]
 pub struct Header {
     pub extension_degree: usize,
-    #[serde(deserialize_with = "deserialize_biguint")]
+    #[serde(serialize_with = "serialize_biguint")]
     pub field_characteristic: BigUint,
+    /// A curve from `KNOWN_CURVES` whose scalar field the circuit is over, which may stand in for
+    /// `field_characteristic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
     pub input_variables: Vec<usize>,
     pub n_constraints: usize,
     pub n_variables: usize,
     pub output_variables: Vec<usize>,
+    /// Variable fixed to the constant one. Most generators use 0, which is assumed when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub constant_index: Option<usize>,
+}
+
+// A header as written, before `curve` is resolved to a field characteristic
+#[derive(Deserialize)]
+struct RawHeader {
+    extension_degree: usize,
+    #[serde(default, deserialize_with = "deserialize_optional_biguint")]
+    field_characteristic: Option<BigUint>,
+    #[serde(default)]
+    curve: Option<String>,
+    input_variables: Vec<usize>,
+    n_constraints: usize,
+    n_variables: usize,
+    output_variables: Vec<usize>,
+    #[serde(default)]
+    constant_index: Option<usize>,
+}
+
+impl TryFrom<RawHeader> for Header {
+    type Error = String;
+
+    fn try_from(raw: RawHeader) -> Result<Self, Self::Error> {
+        let field_characteristic = match (&raw.curve, raw.field_characteristic) {
+            (Some(curve), field_characteristic) => {
+                let modulus = curve_modulus(curve).ok_or_else(|| {
+                    let known: Vec<_> = KNOWN_CURVES.iter().map(|(name, _)| *name).collect();
+                    format!(
+                        "unknown curve {}, expected one of {}",
+                        curve,
+                        known.join(", ")
+                    )
+                })?;
+                match field_characteristic {
+                    Some(given) if given != modulus => {
+                        return Err(format!(
+                            "field_characteristic {} is not the scalar field modulus of curve {}",
+                            given, curve
+                        ))
+                    }
+                    _ => modulus,
+                }
+            }
+            (None, Some(field_characteristic)) => field_characteristic,
+            (None, None) => {
+                return Err("header needs a field_characteristic or a curve".to_string())
+            }
+        };
+
+        Ok(Header {
+            extension_degree: raw.extension_degree,
+            field_characteristic,
+            curve: raw.curve,
+            input_variables: raw.input_variables,
+            n_constraints: raw.n_constraints,
+            n_variables: raw.n_variables,
+            output_variables: raw.output_variables,
+            constant_index: raw.constant_index,
+        })
+    }
+}
+
+impl Header {
+    pub fn constant_index(&self) -> usize {
+        self.constant_index.unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTS: &str =
+        r#""input_variables":[1],"n_constraints":1,"n_variables":3,"output_variables":[2]"#;
+
+    fn parse(fields: &str) -> serde_json::Result<Header> {
+        serde_json::from_str(&format!(
+            r#"{{"extension_degree":1,{},{}}}"#,
+            fields, COUNTS
+        ))
+    }
+
+    #[test]
+    fn test_named_curve() {
+        let named = parse(r#""curve":"bn254""#).unwrap();
+        assert_eq!(named.field_characteristic.to_string(), BN254_MODULUS);
+        assert_eq!(named.curve.as_deref(), Some("bn254"));
+
+        let both = parse(&format!(
+            r#""curve":"BN128","field_characteristic":"{}""#,
+            BN254_MODULUS
+        ))
+        .unwrap();
+        assert_eq!(both.field_characteristic, named.field_characteristic);
+
+        // The curve is kept when the header is written back out
+        let written = serde_json::to_string(&named).unwrap();
+        assert!(written.contains(r#""curve":"bn254""#));
+        assert!(written.contains(BN254_MODULUS));
+
+        let mismatch = parse(r#""curve":"bls12-381","field_characteristic":"7""#).unwrap_err();
+        assert!(mismatch
+            .to_string()
+            .contains("not the scalar field modulus"));
+        let unknown = parse(r#""curve":"pallas""#).unwrap_err();
+        assert!(unknown.to_string().contains("unknown curve pallas"));
+        assert!(parse(r#""constant_index":0"#).is_err());
+    }
 }
@@ -1,31 +1,240 @@
-use crate::witness::deserialize_coeff_var_tuple;
-use ark_bn254::Bn254;
+use crate::field::{to_decimal_string, JsonCoefficient};
+use crate::header::Header;
+use crate::witness::{deserialize_coeff_var_tuple, Witness};
 use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
 use serde::de::IntoDeserializer;
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, Read, Write};
 
 #[derive(Debug)]
 pub struct Inputs<E: Pairing> {
     pub inputs: Vec<(usize, E::ScalarField)>,
 }
 
-pub fn parse_inputs_file(reader: BufReader<File>) -> io::Result<Inputs<Bn254>> {
+impl<E: Pairing> Inputs<E> {
+    /// Check that every input agrees with the witness assignment of the same public variable,
+    /// and that the witness has no public variables the inputs leave out.
+    pub fn check_against(&self, witness: &Witness<E>) -> io::Result<()> {
+        for (index, value) in &self.inputs {
+            match witness.input_variables.get(index) {
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Input variable {} is not a public input of the witness",
+                            index
+                        ),
+                    ))
+                }
+                Some(expected) if expected != value => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Input variable {} is {} in the inputs file but {} in the witness",
+                            index, value, expected
+                        ),
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if self.inputs.len() != witness.input_variables.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Inputs file has {} values but the witness has {} public inputs",
+                    self.inputs.len(),
+                    witness.input_variables.len()
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Combine several inputs files into one sorted by index. An index may appear more than once only
+/// with the same value.
+pub fn merge_inputs<E: Pairing>(files: Vec<Inputs<E>>) -> io::Result<Inputs<E>> {
+    let mut merged = BTreeMap::new();
+    for (index, value) in files.into_iter().flat_map(|file| file.inputs) {
+        match merged.insert(index, value) {
+            Some(previous) if previous != value => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Input variable {} is assigned both {} and {}",
+                        index, previous, value
+                    ),
+                ))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(Inputs {
+        inputs: merged.into_iter().collect(),
+    })
+}
+
+/// Write inputs as `[index, "value"]` lines, the layout `parse_inputs_file` reads.
+pub fn write_inputs_file<E: Pairing, W: Write>(
+    inputs: &Inputs<E>,
+    mut writer: W,
+) -> io::Result<()> {
+    for (index, value) in &inputs.inputs {
+        serde_json::to_writer(&mut writer, &(index, to_decimal_string(value)))?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+// The header must declare exactly the variables the inputs assign
+fn check_header(header: &Header, inputs: &[(usize, impl Debug)]) -> io::Result<()> {
+    if let Some((index, _)) = inputs
+        .iter()
+        .find(|(index, _)| !header.input_variables.contains(index))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Input variable {} is not declared public in the inputs file header",
+                index
+            ),
+        ));
+    }
+
+    if inputs.len() != header.input_variables.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Inputs file header declares {} public inputs but the file has {} values",
+                header.input_variables.len(),
+                inputs.len()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn parse_inputs_file<E: Pairing, R: BufRead>(reader: R) -> io::Result<Inputs<E>> {
     let lines = reader.lines();
 
+    let mut header = None;
     let mut inputs_data = Vec::new();
-    for line in lines {
+    for (i, line) in lines.enumerate() {
         let line = line.expect("Error reading line from inputs file");
         let json = serde_json::from_str::<Value>(&line).expect("Error parsing JSON to Value");
+
+        // A header is an object rather than an [index, value] tuple, and may only come first
+        if i == 0 && json.is_object() {
+            header = Some(serde_json::from_value::<Header>(json)?);
+            continue;
+        }
+
         let deserializer = json.into_deserializer();
-        let parsed_data = deserialize_coeff_var_tuple::<_, Bn254>(deserializer)
+        let parsed_data = deserialize_coeff_var_tuple::<_, E>(deserializer)
             .expect("Error in custom deserialization");
         inputs_data.push(parsed_data);
     }
 
+    if let Some(header) = &header {
+        check_header(header, &inputs_data)?;
+    }
+
     Ok(Inputs {
         inputs: inputs_data,
     })
 }
+
+/// Read public inputs in order from a snarkjs `public.json`: a json array of decimal strings.
+pub fn parse_public_json<F: PrimeField, R: Read>(reader: R) -> io::Result<Vec<F>> {
+    let values: Vec<JsonCoefficient> = serde_json::from_reader(reader)?;
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            value.parse().map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Public input {}: {}", i, e),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::witness::parse_witness_file;
+    use ark_bn254::{Bn254, Fr};
+
+    const WITNESS_JSONL: &str = include_str!("../test/resources/prog-witness.jsonl");
+
+    #[test]
+    fn test_check_against_witness() {
+        let witness: Witness<Bn254> = parse_witness_file(WITNESS_JSONL.as_bytes()).unwrap().into();
+
+        let matching = parse_inputs_file::<Bn254, _>("[1,\"1\"]".as_bytes()).unwrap();
+        assert!(matching.check_against(&witness).is_ok());
+
+        let mismatched = parse_inputs_file::<Bn254, _>("[1,\"2\"]".as_bytes()).unwrap();
+        let err = mismatched.check_against(&witness).unwrap_err();
+        assert!(err.to_string().contains("Input variable 1 is 2"));
+
+        let not_public = parse_inputs_file::<Bn254, _>("[2,\"66\"]".as_bytes()).unwrap();
+        assert!(not_public.check_against(&witness).is_err());
+    }
+
+    #[test]
+    fn test_merge_inputs() {
+        let first = parse_inputs_file::<Bn254, _>("[3,\"7\"]\n[1,\"1\"]".as_bytes()).unwrap();
+        let second = parse_inputs_file::<Bn254, _>("[2,\"5\"]\n[1,\"1\"]".as_bytes()).unwrap();
+        let merged = merge_inputs(vec![first, second]).unwrap();
+
+        let mut out = Vec::new();
+        write_inputs_file(&merged, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[1,\"1\"]\n[2,\"5\"]\n[3,\"7\"]\n"
+        );
+
+        let conflicting = parse_inputs_file::<Bn254, _>("[1,\"2\"]".as_bytes()).unwrap();
+        let err = merge_inputs(vec![merged, conflicting]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Input variable 1 is assigned both"));
+    }
+
+    #[test]
+    fn test_parse_public_json() {
+        let inputs: Vec<Fr> = parse_public_json(r#"["1", 66]"#.as_bytes()).unwrap();
+        assert_eq!(inputs, vec![Fr::from(1u64), Fr::from(66u64)]);
+
+        let err = parse_public_json::<Fr, _>(r#"["1", "x"]"#.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("Public input 1"));
+        assert!(parse_public_json::<Fr, _>(r#"{"1": "1"}"#.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_optional_header() {
+        let without_header = parse_inputs_file::<Bn254, _>("[1,\"1\"]".as_bytes()).unwrap();
+
+        // The witness file is a valid inputs file once its private values are dropped
+        let header = WITNESS_JSONL.lines().next().unwrap();
+        let with_header =
+            parse_inputs_file::<Bn254, _>(format!("{}\n[1,\"1\"]", header).as_bytes()).unwrap();
+        assert_eq!(with_header.inputs, without_header.inputs);
+
+        let err = parse_inputs_file::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Input variable 2 is not declared public"));
+    }
+}
@@ -0,0 +1,237 @@
+use ark_bn254::Bn254;
+use ark_circom::ethereum::{VerifyingKey, G1, G2};
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_groth16::{Proof, VerifyingKey as ArkVerifyingKey};
+use ethers_core::abi::AbiEncode;
+use ethers_core::types::U256;
+use std::fmt;
+use std::io::{self, Write};
+
+// One coordinate as the contract writes it, with its decimal value for comparing against tools
+// that print numbers
+fn write_coordinate<W: Write>(out: &mut W, name: &str, value: U256) -> io::Result<()> {
+    writeln!(out, "  {}: {} ({})", name, value.encode_hex(), value)
+}
+
+fn write_g1<W: Write>(out: &mut W, name: &str, point: &G1) -> io::Result<()> {
+    writeln!(out, "{}:", name)?;
+    write_coordinate(out, "x", point.x)?;
+    write_coordinate(out, "y", point.y)
+}
+
+fn write_g2<W: Write>(out: &mut W, name: &str, point: &G2) -> io::Result<()> {
+    writeln!(out, "{}:", name)?;
+    write_coordinate(out, "x[0]", point.x[0])?;
+    write_coordinate(out, "x[1]", point.x[1])?;
+    write_coordinate(out, "y[0]", point.y[0])?;
+    write_coordinate(out, "y[1]", point.y[1])
+}
+
+/// Print every point of an eth-compatible verifying key in the hex encoding of the generated
+/// contract. G2 coefficients are printed in the order they are stored in `vk`.
+pub fn write_vk<W: Write>(vk: &VerifyingKey, mut out: W) -> io::Result<()> {
+    write_g1(&mut out, "alpha1", &vk.alpha1)?;
+    write_g2(&mut out, "beta2", &vk.beta2)?;
+    write_g2(&mut out, "gamma2", &vk.gamma2)?;
+    write_g2(&mut out, "delta2", &vk.delta2)?;
+    for (i, point) in vk.ic.iter().enumerate() {
+        write_g1(&mut out, &format!("ic[{}]", i), point)?;
+    }
+    Ok(())
+}
+
+/// The first point at which two verifying keys differ, in the order `write_vk` prints them.
+#[derive(Debug, PartialEq, Eq)]
+pub enum VkDifference {
+    Alpha,
+    Beta,
+    Gamma,
+    Delta,
+    IcLength(usize, usize),
+    Ic(usize),
+}
+
+impl fmt::Display for VkDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VkDifference::Alpha => write!(f, "alpha1"),
+            VkDifference::Beta => write!(f, "beta2"),
+            VkDifference::Gamma => write!(f, "gamma2"),
+            VkDifference::Delta => write!(f, "delta2"),
+            VkDifference::IcLength(a, b) => write!(f, "ic length ({} vs {})", a, b),
+            VkDifference::Ic(i) => write!(f, "ic[{}]", i),
+        }
+    }
+}
+
+pub fn first_difference(
+    a: &ArkVerifyingKey<Bn254>,
+    b: &ArkVerifyingKey<Bn254>,
+) -> Option<VkDifference> {
+    if a.alpha_g1 != b.alpha_g1 {
+        return Some(VkDifference::Alpha);
+    }
+    if a.beta_g2 != b.beta_g2 {
+        return Some(VkDifference::Beta);
+    }
+    if a.gamma_g2 != b.gamma_g2 {
+        return Some(VkDifference::Gamma);
+    }
+    if a.delta_g2 != b.delta_g2 {
+        return Some(VkDifference::Delta);
+    }
+    if a.gamma_abc_g1.len() != b.gamma_abc_g1.len() {
+        return Some(VkDifference::IcLength(
+            a.gamma_abc_g1.len(),
+            b.gamma_abc_g1.len(),
+        ));
+    }
+    a.gamma_abc_g1
+        .iter()
+        .zip(&b.gamma_abc_g1)
+        .position(|(a, b)| a != b)
+        .map(VkDifference::Ic)
+}
+
+/// Print the point of `vk` that `difference` names, as `write_vk` does.
+pub fn write_component<W: Write>(
+    vk: &VerifyingKey,
+    difference: &VkDifference,
+    mut out: W,
+) -> io::Result<()> {
+    match difference {
+        VkDifference::Alpha => write_g1(&mut out, "alpha1", &vk.alpha1),
+        VkDifference::Beta => write_g2(&mut out, "beta2", &vk.beta2),
+        VkDifference::Gamma => write_g2(&mut out, "gamma2", &vk.gamma2),
+        VkDifference::Delta => write_g2(&mut out, "delta2", &vk.delta2),
+        VkDifference::IcLength(..) => writeln!(out, "ic: {} elements", vk.ic.len()),
+        VkDifference::Ic(i) => write_g1(&mut out, &format!("ic[{}]", i), &vk.ic[*i]),
+    }
+}
+
+/// The checks arkworks runs on one point of a proof when deserializing it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PointCheck {
+    pub name: &'static str,
+    pub on_curve: bool,
+    /// Only checked for a point on the curve
+    pub in_subgroup: bool,
+}
+
+impl PointCheck {
+    fn of<P: SWCurveConfig>(name: &'static str, point: &Affine<P>) -> Self {
+        let on_curve = point.is_on_curve();
+        PointCheck {
+            name,
+            on_curve,
+            in_subgroup: on_curve && point.is_in_correct_subgroup_assuming_on_curve(),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.on_curve && self.in_subgroup
+    }
+}
+
+impl fmt::Display for PointCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.on_curve, self.in_subgroup) {
+            (false, _) => write!(f, "{}: NOT on the curve", self.name),
+            (true, false) => write!(
+                f,
+                "{}: on the curve, NOT in the prime-order subgroup",
+                self.name
+            ),
+            (true, true) => write!(
+                f,
+                "{}: on the curve, in the prime-order subgroup",
+                self.name
+            ),
+        }
+    }
+}
+
+/// Check each point of a proof deserialized without validation.
+pub fn check_proof(proof: &Proof<Bn254>) -> [PointCheck; 3] {
+    [
+        PointCheck::of("A", &proof.a),
+        PointCheck::of("B", &proof.b),
+        PointCheck::of("C", &proof.c),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_vk() {
+        let vk = VerifyingKey {
+            alpha1: G1 {
+                x: U256::from(1),
+                y: U256::from(2),
+            },
+            ic: vec![G1::default(); 2],
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        write_vk(&vk, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "alpha1:");
+        assert_eq!(lines[1], format!("  x: 0x{:0>64} (1)", "1"));
+        assert_eq!(lines.len(), 3 + 3 * 5 + 2 * 3);
+        assert_eq!(lines[18], "ic[0]:");
+    }
+
+    #[test]
+    fn test_first_difference() {
+        use ark_ec::AffineRepr;
+
+        let a = ArkVerifyingKey::<Bn254> {
+            gamma_abc_g1: vec![Default::default(); 2],
+            ..Default::default()
+        };
+        assert_eq!(first_difference(&a, &a), None);
+
+        let mut b = a.clone();
+        b.gamma_abc_g1[1] = AffineRepr::generator();
+        assert_eq!(first_difference(&a, &b), Some(VkDifference::Ic(1)));
+
+        b.delta_g2 = AffineRepr::generator();
+        assert_eq!(first_difference(&a, &b), Some(VkDifference::Delta));
+
+        b.gamma_abc_g1.pop();
+        b.delta_g2 = a.delta_g2;
+        let difference = first_difference(&a, &b).unwrap();
+        assert_eq!(difference.to_string(), "ic length (2 vs 1)");
+    }
+
+    #[test]
+    fn test_check_proof() {
+        use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+        use ark_ec::AffineRepr;
+        use ark_ff::One;
+
+        let mut proof = Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: G1Affine::generator(),
+        };
+        assert!(check_proof(&proof).iter().all(PointCheck::is_valid));
+
+        proof.a = G1Affine::new_unchecked(Fq::one(), Fq::one());
+        // A point of the twist outside the subgroup, since its cofactor is not cleared
+        proof.b = (1u64..)
+            .find_map(|x| G2Affine::get_point_from_x_unchecked(Fq2::from(x), true))
+            .unwrap();
+        let [a, b, c] = check_proof(&proof);
+        assert_eq!(a.to_string(), "A: NOT on the curve");
+        assert_eq!(
+            b.to_string(),
+            "B: on the curve, NOT in the prime-order subgroup"
+        );
+        assert!(c.is_valid());
+    }
+}
@@ -0,0 +1,241 @@
+//! Groth16 setup, proving and verification for circuits given as R1CS and witness files.
+//!
+//! The `arkworks-bridge` binary is a command line front end to this library. Parse a circuit with
+//! `parse_r1cs_file` and `parse_witness_file`, convert the results into `R1CS` and `Witness`, and
+//! pass them to `setup`, `prove` and `verify`, which work on those in-memory values rather than
+//! files.
+
+pub mod attest;
+pub mod bundle;
+pub mod canonical;
+pub mod circuit;
+pub mod combined;
+pub mod curve;
+pub mod density;
+pub mod disclosure;
+pub mod eip2537;
+pub mod entropy;
+pub mod error;
+pub mod eth;
+pub mod field;
+pub mod fold;
+pub mod gnark;
+pub mod graph;
+pub mod header;
+pub mod inputs;
+pub mod inspect;
+pub mod matrices;
+pub mod matrix_cache;
+pub mod progress;
+pub mod r1cs;
+pub mod rank;
+pub mod serialization;
+pub mod streaming;
+pub mod strict;
+pub mod templates;
+pub mod validate;
+pub mod witness;
+
+pub use crate::circuit::Circuit;
+pub use crate::combined::parse_combined_file;
+pub use crate::curve::{Curve, SupportedCurve};
+pub use crate::inputs::{parse_inputs_file, Inputs};
+pub use crate::r1cs::{parse_r1cs, parse_r1cs_binary, parse_r1cs_file, R1CSFile, R1CS};
+pub use crate::validate::ConstraintViolation;
+pub use crate::witness::{
+    parse_assignment, parse_binary_witness, parse_montgomery_witness, parse_witness_file, Witness,
+    WitnessFile,
+};
+
+use crate::matrix_cache::MatrixCache;
+use crate::progress::Progress;
+use ark_crypto_primitives::snark::*;
+use ark_ec::pairing::Pairing;
+use ark_ff::{UniformRand, Zero};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use log::debug;
+use rand::{thread_rng, CryptoRng, RngCore};
+use std::io;
+
+/// Create the Groth16 keys for `r1cs`, with randomness from the thread-local generator.
+pub fn setup<E: Pairing>(r1cs: R1CS<E>) -> io::Result<(ProvingKey<E>, VerifyingKey<E>)> {
+    setup_with(r1cs, &mut thread_rng())
+}
+
+/// Like `setup`, drawing the setup randomness from `rng`.
+pub fn setup_with<E: Pairing, R: RngCore + CryptoRng>(
+    r1cs: R1CS<E>,
+    rng: &mut R,
+) -> io::Result<(ProvingKey<E>, VerifyingKey<E>)> {
+    setup_with_progress(r1cs, rng, None)
+}
+
+/// Like `setup_with`, reporting progress to `progress` as it goes.
+pub fn setup_with_progress<E: Pairing, R: RngCore + CryptoRng>(
+    r1cs: R1CS<E>,
+    rng: &mut R,
+    progress: Option<&Progress>,
+) -> io::Result<(ProvingKey<E>, VerifyingKey<E>)> {
+    let circuit = Circuit {
+        r1cs,
+        witness: None,
+        progress: progress.cloned(),
+    };
+
+    debug!("Creating trusted setup");
+
+    let keys = Groth16::<E>::circuit_specific_setup(circuit, rng)
+        .map_err(|err| error::prove_failed(format!("Failed to create trusted setup: {}", err)))?;
+    if let Some(progress) = progress {
+        progress.finished();
+    }
+    Ok(keys)
+}
+
+/// Prove that `witness` satisfies `r1cs`, with zero-knowledge.
+pub fn prove<E: Pairing>(
+    proving_key: &ProvingKey<E>,
+    r1cs: R1CS<E>,
+    witness: Witness<E>,
+) -> io::Result<Proof<E>> {
+    prove_with(proving_key, r1cs, witness, true, &mut thread_rng())
+}
+
+/// Like `prove`, drawing the randomizers from `rng`. With `zk` unset they are zero and `rng` is
+/// unused, so the proof is deterministic.
+pub fn prove_with<E: Pairing, R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<E>,
+    r1cs: R1CS<E>,
+    witness: Witness<E>,
+    zk: bool,
+    rng: &mut R,
+) -> io::Result<Proof<E>> {
+    prove_with_progress(proving_key, r1cs, witness, zk, rng, None)
+}
+
+/// Like `prove_with`, reporting progress to `progress` as it goes.
+pub fn prove_with_progress<E: Pairing, R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<E>,
+    r1cs: R1CS<E>,
+    witness: Witness<E>,
+    zk: bool,
+    rng: &mut R,
+    progress: Option<&Progress>,
+) -> io::Result<Proof<E>> {
+    let circuit = Circuit {
+        r1cs,
+        witness: Some(witness),
+        progress: progress.cloned(),
+    };
+
+    debug!("Creating proof for witness");
+
+    let proof = if zk {
+        Groth16::<E>::prove(proving_key, circuit, rng)
+    } else {
+        Groth16::<E>::create_proof_with_reduction_no_zk(circuit, proving_key)
+    };
+
+    let proof =
+        proof.map_err(|err| error::prove_failed(format!("Failed to create proof: {}", err)))?;
+    if let Some(progress) = progress {
+        progress.finished();
+    }
+    Ok(proof)
+}
+
+/// Like `prove_with_progress`, from matrices cached by cache-matrices. There is no synthesis to
+/// report, so progress starts where it would have ended.
+pub fn prove_cached_with<E: Pairing, R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<E>,
+    cache: &MatrixCache<E>,
+    witness: &Witness<E>,
+    zk: bool,
+    rng: &mut R,
+    progress: Option<&Progress>,
+) -> io::Result<Proof<E>> {
+    debug!("Creating proof for witness from cached matrices");
+
+    let (r, s) = if zk {
+        (E::ScalarField::rand(rng), E::ScalarField::rand(rng))
+    } else {
+        (E::ScalarField::zero(), E::ScalarField::zero())
+    };
+    if let Some(progress) = progress {
+        progress.report(progress::SYNTHESIS_SHARE);
+    }
+    let proof = cache.prove(proving_key, witness, r, s)?;
+    if let Some(progress) = progress {
+        progress.finished();
+    }
+    Ok(proof)
+}
+
+/// Check `proof` against the verifying key and public inputs.
+///
+/// Not constant time, and it need not be: every argument is public. See "Timing" in the README.
+pub fn verify<E: Pairing>(
+    verifying_key: &VerifyingKey<E>,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+) -> io::Result<bool> {
+    debug!("Processing verifying key");
+
+    let pvk = Groth16::<E>::process_vk(verifying_key).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to process verifying key: {}", e),
+        )
+    })?;
+
+    Groth16::<E>::verify_with_processed_vk(&pvk, inputs, proof).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to verify proof: {}", e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn circuit() -> (R1CS<Bn254>, Witness<Bn254>, Vec<Fr>) {
+        let r1cs = include_str!("../test/resources/prog-r1cs.jsonl");
+        let witness = include_str!("../test/resources/prog-witness.jsonl");
+        let inputs = include_str!("../test/resources/prog-inputs.jsonl");
+        let inputs: Inputs<Bn254> = parse_inputs_file(inputs.as_bytes()).unwrap();
+        (
+            parse_r1cs_file(r1cs.as_bytes()).unwrap().into(),
+            parse_witness_file(witness.as_bytes()).unwrap().into(),
+            inputs.inputs.into_iter().map(|(_, v)| v).collect(),
+        )
+    }
+
+    #[test]
+    fn test_prove_and_verify_in_memory() {
+        let (r1cs, witness, inputs) = circuit();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+
+        let proof = prove(&proving_key, r1cs, witness).unwrap();
+        assert!(verify(&verifying_key, &proof, &inputs).unwrap());
+        assert!(!verify(&verifying_key, &proof, &[inputs[0] + Fr::from(1u64)]).unwrap());
+    }
+
+    #[test]
+    fn test_prove_without_zk_is_deterministic() {
+        let (r1cs, witness, inputs) = circuit();
+        let mut rng = StdRng::seed_from_u64(0);
+        let (proving_key, verifying_key) = setup_with(r1cs.clone(), &mut rng).unwrap();
+
+        let no_zk = |rng: &mut StdRng| {
+            prove_with(&proving_key, r1cs.clone(), witness.clone(), false, rng).unwrap()
+        };
+        let proof = no_zk(&mut rng);
+        assert_eq!(no_zk(&mut rng), proof);
+        assert!(verify(&verifying_key, &proof, &inputs).unwrap());
+    }
+}
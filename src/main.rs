@@ -1,511 +1,3464 @@
-mod circuit;
-mod header;
-mod inputs;
-mod r1cs;
-mod templates;
-mod witness;
-
-use crate::circuit::Circuit;
-use crate::inputs::{parse_inputs_file, Inputs};
-use crate::witness::Witness; // Import IntoDeserializer trait
-use ark_bn254::Bn254;
+mod benchmark;
+mod checkpoint;
+mod convention;
+mod fixtures;
+mod memory;
+mod post_process;
+mod profile;
+mod remote;
+mod rpc;
+mod summary;
+mod time_limit;
+
+use crate::convention::Convention;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fr};
+use ark_bw6_761::BW6_761;
 use ark_circom::ethereum as circom_eth;
-use ark_crypto_primitives::snark::*;
-use ark_groth16::Groth16;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
+use ark_ff::UniformRand;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalSerialize, Write};
+use arkworks_bridge::curve::{Curve, SupportedCurve};
+use arkworks_bridge::eth::{G2Order, JsonNaming};
+use arkworks_bridge::header::Header;
+use arkworks_bridge::inputs::{merge_inputs, parse_inputs_file, write_inputs_file, Inputs};
+use arkworks_bridge::matrix_cache::MatrixCache;
+use arkworks_bridge::progress::Progress;
+use arkworks_bridge::r1cs::{parse_r1cs, write_r1cs_file, R1CS};
+use arkworks_bridge::serialization::{
+    append_record, deserialize_from, deserialize_unchecked_from, min_proving_key_size,
+    min_proving_key_size_for, proof_size, read_each_record, serialize_to, verifying_key_size,
+};
+use arkworks_bridge::templates::verifier_groth16::Language;
+use arkworks_bridge::witness::parse_witness_file;
+use arkworks_bridge::witness::{Witness, WitnessFormat}; // Import IntoDeserializer trait
+use arkworks_bridge::{
+    attest, bundle, canonical, combined, curve, density, disclosure, eip2537, entropy, error, eth,
+    field, fold, gnark, graph, inputs, inspect, matrices, matrix_cache, progress, prove,
+    prove_cached_with, prove_with, prove_with_progress, rank, serialization, setup, setup_with,
+    setup_with_progress, streaming, strict, templates, validate, verify, witness,
+};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use env_logger::Builder;
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::types::transaction::eip712::EIP712Domain;
+use ethers_core::types::{Address, U256};
+use ethers_core::utils::hex;
 use log::LevelFilter;
-use log::{debug, info};
-use r1cs::{parse_r1cs_file, R1CS};
-use rand::thread_rng;
-use serde_json;
-use std::fs::File;
-use std::io::{self, BufReader};
-use std::path::PathBuf;
-use structopt::clap::AppSettings;
+use log::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use structopt::clap::{self, AppSettings};
 use structopt::StructOpt;
-use witness::parse_witness_file;
+
+// The dependency versions are resolved from Cargo.lock by build.rs
+const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\nark-groth16 ",
+    env!("ARK_GROTH16_VERSION"),
+    "\nark-bn254 ",
+    env!("ARK_BN254_VERSION"),
+    "\nark-bls12-381 ",
+    env!("ARK_BLS12_381_VERSION"),
+    "\nark-bw6-761 ",
+    env!("ARK_BW6_761_VERSION"),
+    "\nark-circom ",
+    env!("ARK_CIRCOM_VERSION"),
+    "\ncurves: bn254, bls12-381, bw6-761"
+);
 
 #[derive(StructOpt, Debug)]
-#[structopt(name = "arkworks-bridge", global_settings = &[AppSettings::TrailingVarArg])]
+#[structopt(name = "arkworks-bridge", long_version = LONG_VERSION, global_settings = &[AppSettings::TrailingVarArg])]
 struct Cli {
     #[structopt(subcommand)]
-    command: Command,
+    command: Option<Command>,
+
+    /// Setup, prove and verify every R1CS, witness and inputs triple in this directory, named like `NAME-r1cs.jsonl`, and print a pass/fail matrix. For checking fixtures during development.
+    #[structopt(long, hidden = true, parse(from_os_str))]
+    self_check_resources: Option<PathBuf>,
 
     #[structopt(long, default_value = "info", global = true, possible_values = &["error", "warn", "info", "debug"])]
     log_level: LevelFilter,
+
+    /// Colorize log levels. `auto` only uses color when logging to a terminal.
+    #[structopt(long, default_value = "auto", global = true, possible_values = &["auto", "always", "never"])]
+    color: String,
+
+    /// Log the peak memory (resident set size) after setup, proving and verify-proof. Linux only.
+    #[structopt(long, global = true)]
+    report_memory: bool,
+
+    /// Fail instead of warning on every validation warning, e.g. an R1CS without public inputs. See the README for the full list.
+    #[structopt(long, global = true)]
+    strict: bool,
+
+    /// Show a progress bar on stderr during setup, proving and verify-proof --records. In setup and proving only constraint synthesis is tracked in detail, the rest of the work completes in one step.
+    #[structopt(long, global = true)]
+    progress: bool,
+
+    /// How the R1CS constraints relate A, B and C: `abc-eq` for A·B = C, or `abc-zero` for A·B + C = 0, which is read by negating C. Matrix caches hold the constraints after this is applied.
+    #[structopt(long, default_value = "abc-eq", global = true, possible_values = Convention::VARIANTS)]
+    constraint_convention: Convention,
+
+    /// On failure, write the error to stderr as one JSON object with `error`, `kind` and `command` fields.
+    #[structopt(long, global = true)]
+    error_json: bool,
+
+    /// Size of the thread pool shared by setup, proving and every other parallel step. Defaults to one thread per core.
+    #[structopt(long, global = true)]
+    threads: Option<usize>,
+
+    /// Curve to prove over. The R1CS must be over its scalar field. Only `create-trusted-setup`, `create-proof`, `verify-proof` and `run-r1cs` support curves other than `bn254`, and the Ethereum and gnark outputs need `bn254`.
+    #[structopt(long, default_value = "bn254", global = true, possible_values = Curve::VARIANTS)]
+    curve: Curve,
+
+    /// Prefix every key and proof written with a tag recording the format and ark-serialize versions, so that reading it with an incompatible build warns, or fails under `--strict`. Plain arkworks deserialization can't read tagged files.
+    #[structopt(long, global = true)]
+    tag_artifacts: bool,
 }
 
 #[derive(StructOpt, Debug)]
 enum Command {
-    /// Create a trusted setup given an R1CS file. Currently there is no way to input randomness for the setup, so it is generated using the system's PRNG.
+    /// Create a trusted setup given an R1CS file. Randomness comes from the system's PRNG unless an entropy file is given. Either way this is a single-party setup, not suitable for production.
     CreateTrustedSetup {
         /// Path to the R1CS file
         #[structopt(short, long, parse(from_os_str))]
         r1cs: PathBuf,
 
+        /// Read the constraints from this cache written by cache-matrices instead of parsing the R1CS file, which must still be given so the cache can be checked against its header
+        #[structopt(long, parse(from_os_str))]
+        matrix_cache: Option<PathBuf>,
+
         /// Write the serialized proving key to this file
-        #[structopt(short, long, parse(from_os_str))]
-        proving_key: PathBuf,
+        #[structopt(short, long, parse(from_os_str), required_unless = "bundle")]
+        proving_key: Option<PathBuf>,
 
         /// Write the serialized verifying key to this file
-        #[structopt(short, long, parse(from_os_str))]
-        verifying_key: PathBuf,
+        #[structopt(short, long, parse(from_os_str), required_unless = "bundle")]
+        verifying_key: Option<PathBuf>,
 
-        /// Export the verifying key as json (useful for external ethereum contracts)
+        /// Export the verifying key as json (useful for external ethereum contracts). With --curve bls12-381 the points are in their EIP-2537 encoding.
         #[structopt(short, long)]
         ethereum: bool,
-    },
-    /// Generate a solidity verifier contract given a verifying key
-    GenerateContract {
-        /// Path to the serialized verifying key
-        #[structopt(short, long, parse(from_os_str))]
-        verifying_key: PathBuf,
 
-        /// Write the solidity verifier contract to this file
-        #[structopt(short, long, parse(from_os_str))]
-        contract: PathBuf,
+        /// Coefficient order of G2 coordinates in eth-compatible json: `arkworks`, as earlier releases wrote it, or `eth` (EIP-197)
+        #[structopt(long, default_value = "arkworks", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
 
-        /// Path to the inputs file
-        #[structopt(short, long, parse(from_os_str))]
-        inputs: PathBuf,
-    },
-    /// Create a proof given a proving key, witness, and R1CS file
-    CreateProof {
-        /// Path to the serialized proving key
-        #[structopt(short, long, parse(from_os_str))]
-        proving_key: PathBuf,
+        /// Field names of the eth-compatible json: `arkworks`, `snarkjs` (which can't be combined with `--g2-order eth`), or renames of the top-level fields like `a=pi_a,b=pi_b`. Only `arkworks` json can be read back by verify-proof.
+        #[structopt(long, default_value = "arkworks")]
+        json_naming: JsonNaming,
 
-        // Path to the witness file
-        #[structopt(short, long, parse(from_os_str))]
-        witness: PathBuf,
+        /// Also write the verifying key in gnark's raw binary encoding, next to the verifying key with a `-gnark.bin` suffix
+        #[structopt(long, requires = "verifying-key")]
+        gnark: bool,
 
-        /// Path to the R1CS file
-        #[structopt(short, long, parse(from_os_str))]
-        r1cs: PathBuf,
+        /// Seed the setup randomness from the hash of this file (at least 32 bytes)
+        #[structopt(long, parse(from_os_str))]
+        entropy: Option<PathBuf>,
 
-        /// Write the serialized proof to this file
-        #[structopt(short, long, parse(from_os_str))]
-        proof: PathBuf,
+        /// Run the seeded setup twice and fail unless both produce byte-identical keys
+        #[structopt(long, requires = "entropy")]
+        verify_determinism: bool,
 
-        /// Generate an eth-compatible proof and serialize as json
-        #[structopt(short, long)]
-        ethereum: bool,
-    },
-    /// Verify a proof given a verifying key, proof, and inputs
-    VerifyProof {
-        /// Path to the serialized verifying key
-        #[structopt(short, long, parse(from_os_str))]
-        verifying_key: PathBuf,
+        /// Leave output files that already hold exactly what would be written, by SHA-256, untouched, so their modification times stay the same
+        #[structopt(long, requires = "entropy")]
+        skip_unchanged: bool,
 
-        /// Path to the serialized proof
-        #[structopt(short, long, parse(from_os_str))]
-        proof: PathBuf,
+        /// Also write every setup output and the R1CS, with a manifest, into this zip archive
+        #[structopt(long, parse(from_os_str))]
+        bundle: Option<PathBuf>,
 
-        /// Path to the inputs file
-        #[structopt(short, long, parse(from_os_str))]
-        inputs: PathBuf,
+        /// Also write a JSON disclosure to this file, stating the setup was single-party and that whoever ran it could forge proofs, with the tool version, a timestamp and hashes of the circuit and verifying key
+        #[structopt(long, parse(from_os_str))]
+        disclose: Option<PathBuf>,
 
-        /// load an eth-compatible proof from json
-        #[structopt(short, long)]
-        ethereum: bool,
+        /// Print key element counts and the size of every file written
+        #[structopt(long)]
+        summary: bool,
+
+        /// Fold these public inputs, an inputs file of variable indices and values, into every verifying key written, so verifiers no longer take them. The proving key keeps the full verifying key. See the README before using it.
+        #[structopt(long, parse(from_os_str))]
+        fixed_inputs: Option<PathBuf>,
     },
-    /// Generate a trusted setup, proof, and run proof verification without serializing any intermediate files. This is mostly useful for testing.
-    RunR1CS {
-        /// Path to the R1CS file
+    /// Generate a verifier contract given a verifying key, in solidity or as a Cairo or Noir verifier to complete with a BN254 implementation. With --curve bls12-381, a solidity verifier calling the EIP-2537 precompiles.
+    GenerateContract {
+        /// Path to the serialized verifying key
         #[structopt(short, long, parse(from_os_str))]
-        r1cs: PathBuf,
+        verifying_key: PathBuf,
 
-        // Path to the witness file
+        /// Write the verifier contract to this file
         #[structopt(short, long, parse(from_os_str))]
-        witness: PathBuf,
+        contract: PathBuf,
 
-        /// Path to the inputs file
+        /// Language of the verifier: `solidity`, `cairo` (Starknet) or `noir`. The Cairo and Noir verifiers are generic over a `Bn254` trait for the curve operations, which the integrator implements.
+        #[structopt(long, default_value = "solidity", possible_values = Language::VARIANTS)]
+        language: Language,
+
+        /// Path to an inputs file, checked to have as many inputs as the verifying key. The contract takes its input count from the verifying key either way.
         #[structopt(short, long, parse(from_os_str))]
-        inputs: PathBuf,
-    },
-}
+        inputs: Option<PathBuf>,
 
-fn create_trusted_setup(
-    r1cs_path: PathBuf,
-    pk_output: PathBuf,
-    mut vk_output: PathBuf,
-    ethereum: bool,
-) -> io::Result<()> {
-    let file = File::open(r1cs_path.clone())?;
-    let reader = BufReader::new(file);
+        /// Fail unless the SHA-256 of the rendered contract is this hex digest
+        #[structopt(long)]
+        expected_hash: Option<String>,
 
-    debug!("Loading R1CS file from {:}", r1cs_path.display());
+        /// Coefficient order of G2 coordinates in the contract: `eth` (EIP-197) or `arkworks`. Not used with --curve bls12-381, whose contract takes points in their EIP-2537 encoding.
+        #[structopt(long, default_value = "eth", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
 
-    let r1cs: R1CS<Bn254> = parse_r1cs_file(reader)?.into();
+        /// Pipe the rendered contract through this shell command and write its output instead, e.g. `forge fmt -r -`. --expected-hash still applies to the rendered contract.
+        #[structopt(long)]
+        post_process: Option<String>,
+    },
+    /// Generate one solidity contract verifying proofs for several circuits, each selected by the position of its verifying key as the circuit id
+    GenerateMultiContract {
+        /// Path to a serialized verifying key. Repeat for each circuit, in circuit id order.
+        #[structopt(short, long, parse(from_os_str), number_of_values = 1, required = true)]
+        verifying_key: Vec<PathBuf>,
 
-    let circuit = Circuit {
-        r1cs,
-        witness: None,
-    };
+        /// Write the solidity verifier contract to this file
+        #[structopt(short, long, parse(from_os_str))]
+        contract: PathBuf,
 
-    debug!("Creating trusted setup");
+        /// Coefficient order of G2 coordinates in the contract: `eth` (EIP-197) or `arkworks`
+        #[structopt(long, default_value = "eth", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
+    },
+    /// Create a proof given a proving key, witness, and R1CS file
+    CreateProof {
+        /// Path to the serialized proving key, or `@NAME` to read it base64 encoded from an environment variable
+        #[structopt(short, long, parse(from_os_str), required_unless = "bundle")]
+        proving_key: Option<PathBuf>,
 
-    let setup =
-        Groth16::<Bn254>::circuit_specific_setup(circuit, &mut thread_rng()).map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to create trusted setup: {}", err),
-            )
-        })?;
+        /// Read the proving key from this setup bundle instead
+        #[structopt(long, parse(from_os_str), conflicts_with = "proving-key")]
+        bundle: Option<PathBuf>,
 
-    info!("Serializing proving key to file {:}", pk_output.display());
+        /// Path to the serialized verifying key for --self-verify, or `@NAME` to read it base64 encoded from an environment variable
+        #[structopt(
+            long,
+            parse(from_os_str),
+            requires = "self-verify",
+            conflicts_with = "bundle"
+        )]
+        verifying_key: Option<PathBuf>,
 
-    // Serialize the proving key to the output file
-    let mut file = File::create(pk_output)?;
-    setup.0.serialize_uncompressed(&mut file).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to serialize proving key: {}", e),
-        )
-    })?;
+        /// Verify the proof against the verifying key from --verifying-key or the bundle, and only write it if it passes
+        #[structopt(long)]
+        self_verify: bool,
 
-    info!(
-        "Serializing verification key to file {:}",
-        vk_output.display()
-    );
+        /// Path to the witness file. Repeat it to prove from json witness shards that each assign a disjoint set of variables.
+        #[structopt(
+            short,
+            long,
+            parse(from_os_str),
+            number_of_values = 1,
+            required_unless_one = &["combined", "assignment"]
+        )]
+        witness: Vec<PathBuf>,
 
-    // Serialize the verifying key to the output file
-    let mut file = File::create(vk_output.clone())?;
-    setup.1.serialize_uncompressed(&mut file).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to serialize verifying key: {}", e),
-        )
-    })?;
+        /// Format of the witness file: `json`, `bin` for packed 32-byte little-endian field elements, or `montgomery` for raw arkworks BN254 Montgomery form. Inferred from a `.bin` extension when omitted, never as `montgomery`.
+        #[structopt(long, possible_values = WitnessFormat::VARIANTS)]
+        witness_format: Option<WitnessFormat>,
 
-    if ethereum {
-        let mut file_stem = vk_output.file_stem().unwrap().to_os_string();
-        file_stem.push("-eth");
-        vk_output.set_file_name(file_stem);
-        vk_output.set_extension("json");
-        let mut file = File::create(vk_output.clone())?;
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str), required_unless = "combined")]
+        r1cs: Option<PathBuf>,
 
-        let eth_vk: circom_eth::VerifyingKey = setup.1.into();
+        /// Read the R1CS and witness from one file: the header, then constraint and witness lines tagged with a `type` field
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["r1cs", "witness", "witness-format"])]
+        combined: Option<PathBuf>,
 
-        info!(
-            "Serializing eth-compatible verifying key to file {:}",
-            vk_output.display()
-        );
-        file.write_all(serde_json::to_string(&eth_vk).unwrap().as_bytes())?;
-    };
+        /// Read the witness from one JSON object `{"inputs": {...}, "witness": {...}}`, each keyed by variable index. The inputs must be exactly the public variables of the witness.
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["witness", "witness-format", "combined"])]
+        assignment: Option<PathBuf>,
 
-    Ok(())
-}
+        /// Prove from the constraint matrices in this cache written by cache-matrices instead of synthesizing the circuit. Only the header line of the R1CS or combined file is read, to check the cache was built from it.
+        #[structopt(long, parse(from_os_str))]
+        matrix_cache: Option<PathBuf>,
 
-fn create_proof(
-    proving_key: PathBuf,
-    witness: PathBuf,
-    r1cs: PathBuf,
-    mut output: PathBuf,
-    ethereum: bool,
-) -> io::Result<()> {
-    let file = File::open(proving_key.clone())?;
-    let mut reader = BufReader::new(file);
+        /// Check the witness satisfies every constraint before proving, and fail at the first that does not with its A·B and C values
+        #[structopt(long, conflicts_with = "matrix-cache")]
+        check_witness: bool,
 
-    debug!("Loading proving key from file {:}", proving_key.display());
+        /// Write the serialized proof to this file
+        #[structopt(long, parse(from_os_str))]
+        proof: PathBuf,
 
-    let proving_key =
-        <Groth16<Bn254> as ark_crypto_primitives::snark::SNARK<ark_bn254::Fr>>::ProvingKey::deserialize_uncompressed(&mut reader).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to deserialize proving key: {}", e),
-            )
-        })?;
+        /// Generate an eth-compatible proof and serialize as json. With --curve bls12-381 the points are in their EIP-2537 encoding.
+        #[structopt(short, long)]
+        ethereum: bool,
 
-    let file = File::open(witness.clone())?;
-    let reader = BufReader::new(file);
+        /// Coefficient order of G2 coordinates in eth-compatible json: `arkworks`, as earlier releases wrote it, or `eth` (EIP-197)
+        #[structopt(long, default_value = "arkworks", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
 
-    debug!("Loading witness file from {:}", witness.display());
+        /// Field names of the eth-compatible json: `arkworks`, `snarkjs` (which can't be combined with `--g2-order eth`), or renames of the top-level fields like `a=pi_a,b=pi_b`. Only `arkworks` json can be read back by verify-proof.
+        #[structopt(long, default_value = "arkworks")]
+        json_naming: JsonNaming,
 
-    let witness: Witness<Bn254> = parse_witness_file(reader)?.into();
+        /// Append the proof as a length-prefixed record instead of overwriting the proof file
+        #[structopt(long)]
+        append: bool,
 
-    let file = File::open(r1cs.clone())?;
-    let reader = BufReader::new(file);
+        /// Also write the proof in gnark's raw binary encoding, next to the proof with a `-gnark.bin` suffix
+        #[structopt(long)]
+        gnark: bool,
 
-    debug!("Loading R1CS file from {:}", r1cs.display());
+        /// Also write keccak256 commitments to the public inputs next to the proof: `commitment` over the packed words, matching `keccak256(abi.encode(input))` for the verifier's `uint[N]`, and `abi_commitment` matching `keccak256(abi.encode(inputs))` for a `uint256[]`
+        #[structopt(long)]
+        commit_inputs: bool,
 
-    let r1cs: R1CS<Bn254> = parse_r1cs_file(reader)?.into();
+        /// Also write the eth-compatible proof and its public inputs as EIP-712 typed data
+        #[structopt(long)]
+        eip712: bool,
 
-    let circuit = Circuit {
-        r1cs,
-        witness: Some(witness),
-    };
+        /// Name of the EIP-712 signing domain
+        #[structopt(long, default_value = "Groth16Verifier")]
+        eip712_name: String,
 
-    debug!("Creating proof for witness");
+        /// Version of the EIP-712 signing domain
+        #[structopt(long, default_value = "1")]
+        eip712_version: String,
 
-    let proof =
-        Groth16::<Bn254>::prove(&proving_key, circuit, &mut thread_rng()).map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to create proof: {}", err),
-            )
-        })?;
+        /// Chain id of the EIP-712 signing domain
+        #[structopt(long)]
+        chain_id: Option<u64>,
 
-    info!("Serializing proof to file {:}", output.display());
+        /// Address of the contract that verifies the EIP-712 signature
+        #[structopt(long)]
+        verifying_contract: Option<Address>,
 
-    let mut file = File::create(output.clone())?;
-    proof.serialize_uncompressed(&mut file).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to serialize proof: {}", e),
-        )
-    })?;
+        /// Seed the prover's randomness so the proof is reproducible
+        #[structopt(long)]
+        seed: Option<u64>,
 
-    if ethereum {
-        let mut file_stem = output.file_stem().unwrap().to_os_string();
-        file_stem.push("-eth");
-        output.set_file_name(file_stem);
-        output.set_extension("json");
-        let mut file = File::create(output.clone())?;
+        /// Skip the zero-knowledge randomizers, making the proof deterministic. The proof leaks information about the witness: for test fixtures only, NEVER use this in production.
+        #[structopt(long)]
+        no_zk: bool,
 
-        let eth_proof: circom_eth::Proof = proof.into();
+        /// Fail if proving takes longer than this many seconds. Proving can't be interrupted, so the process exits with an error at the limit.
+        #[structopt(long)]
+        time_limit: Option<u64>,
+    },
+    /// Verify a proof given a verifying key, proof, and inputs
+    VerifyProof {
+        /// Path or, with the `http` feature, URL of the serialized verifying key. `@NAME` reads it base64 encoded from an environment variable.
+        #[structopt(short, long, parse(from_os_str), required_unless = "bundle")]
+        verifying_key: Option<PathBuf>,
 
-        info!(
-            "Serializing eth-compatible proof to file {:}",
-            output.display()
-        );
-        file.write_all(serde_json::to_string(&eth_proof).unwrap().as_bytes())?;
-    };
+        /// Read the verifying key from this setup bundle instead
+        #[structopt(long, parse(from_os_str), conflicts_with = "verifying-key")]
+        bundle: Option<PathBuf>,
 
-    Ok(())
-}
+        /// Path or, with the `http` feature, URL of the serialized proof
+        #[structopt(short, long, parse(from_os_str))]
+        proof: PathBuf,
 
-fn verify_proof(
-    verifying_key: PathBuf,
-    proof: PathBuf,
-    inputs: PathBuf,
-    ethereum: bool,
-) -> io::Result<bool> {
-    let file = File::open(verifying_key.clone())?;
-    let mut reader = BufReader::new(file);
+        /// Path to the inputs file. Given with other inputs sources, they must all give identical inputs.
+        #[structopt(short, long, parse(from_os_str), required_unless_one = &["inputs-words", "inputs-snarkjs"])]
+        inputs: Option<PathBuf>,
 
-    debug!(
-        "Loading verifying key from file {:}",
-        verifying_key.display()
-    );
+        /// Read the public inputs from a hex blob of concatenated 32-byte big-endian words, as an on-chain verifier receives them
+        #[structopt(long)]
+        inputs_words: Option<String>,
 
-    let verifying_key = if ethereum {
-        let file = File::open(verifying_key.clone())?;
-        let reader = BufReader::new(file);
+        /// Read the public inputs from a snarkjs `public.json`, a json array of decimal strings
+        #[structopt(long, parse(from_os_str))]
+        inputs_snarkjs: Option<PathBuf>,
 
-        debug!(
-            "Loading eth-compatible verifying key from {:}",
-            verifying_key.display()
-        );
+        /// load an eth-compatible proof from json
+        #[structopt(short, long)]
+        ethereum: bool,
 
-        let eth_vk: circom_eth::VerifyingKey = serde_json::from_reader(reader).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to deserialize verifying key: {}", e),
-            )
-        })?;
+        /// The proof file holds length-prefixed records written by `create-proof --append`. It must hold at least one proof, and every proof must verify.
+        #[structopt(long)]
+        records: bool,
 
-        Ok(<Groth16<Bn254> as ark_crypto_primitives::snark::SNARK<
-            ark_bn254::Fr,
-        >>::VerifyingKey::from(eth_vk))
-    } else {
-        <Groth16<Bn254> as ark_crypto_primitives::snark::SNARK<ark_bn254::Fr>>::VerifyingKey::deserialize_uncompressed(&mut reader).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to deserialize verifying key: {}", e),
-            )
-        })
-    }?;
+        /// With --records, write each proof's result to this file as soon as it is verified, and skip proofs it already records, to resume an interrupted run
+        #[structopt(long, parse(from_os_str), requires = "records")]
+        results: Option<PathBuf>,
 
-    let file = File::open(proof.clone())?;
-    let mut reader = BufReader::new(file);
+        /// With --records, go on to the next proof when one can't be read or verified, then fail with a summary of every proof that failed and why
+        #[structopt(long, requires = "records")]
+        continue_on_error: bool,
 
-    debug!("Loading proof from file {:}", proof.display());
+        /// Also fail unless the inputs hash to this keccak256 commitment, as written by `create-proof --commit-inputs`
+        #[structopt(long)]
+        commitment: Option<String>,
 
-    let proof =
-        <Groth16<Bn254> as ark_crypto_primitives::snark::SNARK<ark_bn254::Fr>>::Proof::deserialize_uncompressed(&mut reader).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to deserialize proof: {}", e),
-            )
-        })?;
+        /// Coefficient order of G2 coordinates in eth-compatible json: `arkworks`, as earlier releases wrote it, or `eth` (EIP-197)
+        #[structopt(long, default_value = "arkworks", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
 
-    let file = File::open(inputs.clone())?;
-    let reader = BufReader::new(file);
+        /// Verify while reading the verifying key, without holding all of it in memory. For verifiers with many public inputs and little memory.
+        #[structopt(long, conflicts_with_all = &["ethereum", "records"])]
+        stream_vk: bool,
+    },
+    /// Verify a proof and write a JSON attestation of the result, the verifying key, proof and inputs hashes and the time, signed with a secp256k1 key. The attestation is written even if verification fails, and the command then fails.
+    VerifyAttest {
+        /// Path or, with the `http` feature, URL of the serialized verifying key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: PathBuf,
 
-    debug!("Loading witness file from {:}", inputs.display());
+        /// Path or, with the `http` feature, URL of the serialized proof
+        #[structopt(short, long, parse(from_os_str))]
+        proof: PathBuf,
 
-    let inputs: Inputs<Bn254> = parse_inputs_file(reader)?.into();
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: PathBuf,
 
-    let inputs: Vec<ark_bn254::Fr> = inputs.inputs.into_iter().map(|(_, v)| v).collect();
+        /// Path to a file holding the hex-encoded secp256k1 secret key to sign with. `@NAME` reads it from an environment variable.
+        #[structopt(short, long, parse(from_os_str))]
+        signing_key: PathBuf,
 
-    debug!("Processing verifying key");
+        /// Write the attestation here
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Ask a deployed verifier contract whether it accepts a proof, by an `eth_call` of `verifyProof` through a JSON-RPC endpoint. Needs the `rpc` feature.
+    VerifyOnChain {
+        /// URL of the JSON-RPC endpoint
+        #[structopt(long)]
+        rpc_url: String,
 
-    let pvk = Groth16::<Bn254>::process_vk(&verifying_key).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            format!("Failed to process verifying key: {}", e),
-        )
-    })?;
+        /// Address of the verifier contract, generated by `generate-contract`
+        #[structopt(short, long)]
+        address: String,
 
-    let result = Groth16::<Bn254>::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap();
+        /// Path or, with the `http` feature, URL of the serialized proof
+        #[structopt(short, long, parse(from_os_str))]
+        proof: PathBuf,
 
-    info!("Proof verification result: {}", result);
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: PathBuf,
 
-    Ok(result)
-}
+        /// Also verify with this verifying key off-chain, and fail if the contract disagrees, e.g. because it was generated from another key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: Option<PathBuf>,
+    },
+    /// Generate a trusted setup, proof, and run proof verification without serializing any intermediate files. This is mostly useful for testing.
+    RunR1CS {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str), required_unless = "combined")]
+        r1cs: Option<PathBuf>,
 
-fn run_r1cs(r1cs: PathBuf, witness: PathBuf, inputs: PathBuf) -> io::Result<()> {
-    let file = File::open(r1cs.clone())?;
-    let reader = BufReader::new(file);
+        // Path to the witness file
+        #[structopt(short, long, parse(from_os_str), required_unless_one = &["combined", "assignment"])]
+        witness: Option<PathBuf>,
 
-    debug!("Loading R1CS file from {:}", r1cs.display());
+        /// Format of the witness file: `json`, `bin` for packed 32-byte little-endian field elements, or `montgomery` for raw arkworks BN254 Montgomery form. Inferred from a `.bin` extension when omitted, never as `montgomery`.
+        #[structopt(long, possible_values = WitnessFormat::VARIANTS)]
+        witness_format: Option<WitnessFormat>,
 
-    let r1cs: R1CS<Bn254> = parse_r1cs_file(reader)?.into();
+        /// Read the R1CS and witness from one file: the header, then constraint and witness lines tagged with a `type` field
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["r1cs", "witness", "witness-format"])]
+        combined: Option<PathBuf>,
 
-    let file = File::open(witness.clone())?;
-    let reader = BufReader::new(file);
+        /// Read the inputs and witness from one JSON object `{"inputs": {...}, "witness": {...}}`, each keyed by variable index. The inputs must be exactly the public variables of the witness.
+        #[structopt(long, parse(from_os_str), conflicts_with_all = &["witness", "witness-format", "combined", "inputs"])]
+        assignment: Option<PathBuf>,
 
-    debug!("Loading witness file from {:}", witness.display());
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str), required_unless = "assignment")]
+        inputs: Option<PathBuf>,
 
-    let witness: Witness<Bn254> = parse_witness_file(reader)?.into();
+        /// Check the witness satisfies every constraint before proving, and fail at the first that does not with its A·B and C values
+        #[structopt(long)]
+        check_witness: bool,
 
-    let file = File::open(inputs.clone())?;
-    let reader = BufReader::new(file);
+        /// Fail if proving takes longer than this many seconds. Proving can't be interrupted, so the process exits with an error at the limit.
+        #[structopt(long)]
+        time_limit: Option<u64>,
+    },
+    /// Validate the R1CS and witness, check the witness satisfies every constraint, then create a proof. Fails at the first stage that does not pass.
+    ProveChecked {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
 
-    debug!("Loading inputs file from {:}", inputs.display());
+        /// Path to the witness file
+        #[structopt(short, long, parse(from_os_str))]
+        witness: PathBuf,
 
-    let inputs: Inputs<Bn254> = parse_inputs_file(reader)?.into();
+        /// Path to the serialized proving key
+        #[structopt(short, long, parse(from_os_str))]
+        proving_key: PathBuf,
 
-    let inputs: Vec<ark_bn254::Fr> = inputs.inputs.into_iter().map(|(_, v)| v).collect();
+        /// Write the serialized proof to this file
+        #[structopt(long, parse(from_os_str))]
+        proof: PathBuf,
+    },
+    /// Report witness and inputs values that are not already reduced into the field: negative, fractional, or at least the modulus. Parsing reduces these silently, which can hide a generator bug.
+    CheckReduced {
+        /// Path to the witness file
+        #[structopt(short, long, parse(from_os_str), required_unless = "inputs")]
+        witness: Option<PathBuf>,
 
-    let circuit = Circuit {
-        r1cs,
-        witness: Some(witness),
-    };
+        /// Format of the witness file: `json`, `bin` for packed 32-byte little-endian field elements, or `montgomery` for raw arkworks BN254 Montgomery form. Inferred from a `.bin` extension when omitted, never as `montgomery`.
+        #[structopt(long, possible_values = WitnessFormat::VARIANTS)]
+        witness_format: Option<WitnessFormat>,
 
-    let (proving_key, verifying_key) =
-        Groth16::<Bn254>::circuit_specific_setup(circuit.clone(), &mut thread_rng()).map_err(
-            |err| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("Failed to create trusted setup: {}", err),
-                )
-            },
-        )?;
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: Option<PathBuf>,
+    },
+    /// Check a random sample of constraints against a witness. Much faster than a full check on large circuits, but passing does not prove the witness satisfies every constraint.
+    SpotCheck {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
 
-    debug!("Creating proof for witness");
+        /// Path to the witness file
+        #[structopt(short, long, parse(from_os_str))]
+        witness: PathBuf,
 
-    let proof =
-        Groth16::<Bn254>::prove(&proving_key, circuit, &mut thread_rng()).map_err(|err| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to create proof: {}", err),
-            )
-        })?;
+        /// Format of the witness file: `json`, `bin` for packed 32-byte little-endian field elements, or `montgomery` for raw arkworks BN254 Montgomery form. Inferred from a `.bin` extension when omitted, never as `montgomery`.
+        #[structopt(long, possible_values = WitnessFormat::VARIANTS)]
+        witness_format: Option<WitnessFormat>,
 
-    let valid = Groth16::<Bn254>::verify(&verifying_key, &inputs, &proof).unwrap();
+        /// Number of constraints to check, all of them if the circuit has fewer
+        #[structopt(long, default_value = "100")]
+        samples: usize,
 
-    if valid {
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Proof verification failed",
-        ))
-    }
-}
+        /// Seed the sampling so the same constraints are checked on every run
+        #[structopt(long)]
+        seed: Option<u64>,
+    },
+    /// Write the verifying key embedded in a proving key, e.g. to recover a lost one. It is byte-identical to the verifying key the setup wrote.
+    DeriveVk {
+        /// Path to the serialized proving key
+        #[structopt(short, long, parse(from_os_str))]
+        proving_key: PathBuf,
 
-fn generate_contract(verifying_key: PathBuf, contract: PathBuf, inputs: PathBuf) -> io::Result<()> {
-    let file = File::open(verifying_key.clone())?;
-    let mut reader = BufReader::new(file);
+        /// Write the serialized verifying key to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Write as many uniformly random field elements as a verifying key has public inputs, as an inputs file. Paired with a valid proof they should never verify, for fuzzing a verifier's rejection of wrong inputs.
+    GenRandomInputs {
+        /// Path or, with the `http` feature, URL of the serialized verifying key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: PathBuf,
 
-    debug!(
-        "Loading verifying key from file {:}",
-        verifying_key.display()
-    );
+        /// Write the inputs file here
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
 
-    let verifying_key =
-        <Groth16<Bn254> as ark_crypto_primitives::snark::SNARK<ark_bn254::Fr>>::VerifyingKey::deserialize_uncompressed(&mut reader).map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to deserialize verifying key: {}", e),
-            )
-        })?;
+        /// Seed the randomness so the same inputs are written on every run
+        #[structopt(long)]
+        seed: Option<u64>,
+    },
+    /// Check a proving key and verifying key came from the same setup by comparing the verifying key embedded in the proving key
+    CheckSetup {
+        /// Path to the serialized proving key
+        #[structopt(short, long, parse(from_os_str))]
+        proving_key: PathBuf,
 
-    let eth_vk: circom_eth::VerifyingKey = circom_eth::VerifyingKey::from(verifying_key);
+        /// Path to the serialized verifying key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: PathBuf,
 
-    let file = File::open(inputs.clone())?;
-    let reader = BufReader::new(file);
+        /// Also prove the witness with the proving key and verify the proof with the verifying key
+        #[structopt(long, requires_all = &["r1cs", "witness"])]
+        roundtrip: bool,
 
-    debug!("Loading inputs file from {:}", inputs.display());
+        /// Path to the R1CS file, for --roundtrip
+        #[structopt(short, long, parse(from_os_str), requires = "roundtrip")]
+        r1cs: Option<PathBuf>,
 
-    let inputs: Inputs<Bn254> = parse_inputs_file(reader)?.into();
+        /// Path to a satisfying witness, for --roundtrip
+        #[structopt(short, long, parse(from_os_str), requires = "roundtrip")]
+        witness: Option<PathBuf>,
+    },
+    /// Print the points of a verifying key with the hex coordinates the verifier contract uses, and their decimal values
+    ShowVk {
+        /// Path to the serialized verifying key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: PathBuf,
 
-    let template =
-        templates::verifier_groth16::render_contract(&eth_vk, inputs.inputs.len()).unwrap();
+        /// Coefficient order of G2 coordinates: `eth` (EIP-197), as in the contract, or `arkworks`
+        #[structopt(long, default_value = "eth", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
+    },
+    /// Check each point of a proof is on the curve and in the prime-order subgroup, without a verifying key or inputs. Fails if any point is not.
+    InspectProof {
+        /// Path to the serialized proof
+        #[structopt(short, long, parse(from_os_str))]
+        proof: PathBuf,
+    },
+    /// Print public inputs as `0x`-prefixed 32-byte words sorted by variable index, one per line, the exact `uint256` calldata a verifier contract receives
+    InputsToWords {
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: PathBuf,
+
+        /// Write the words here instead of stdout
+        #[structopt(short, long, parse(from_os_str))]
+        out: Option<PathBuf>,
+    },
+    /// Compare two verifying keys, reporting the first point that differs. Fails unless they serialize identically.
+    DiffVk {
+        /// Path to the first serialized verifying key
+        #[structopt(short, parse(from_os_str))]
+        a: PathBuf,
+
+        /// Path to the second serialized verifying key
+        #[structopt(short, parse(from_os_str))]
+        b: PathBuf,
+
+        /// Coefficient order of G2 coordinates when printing a differing point: `eth` (EIP-197), as in the contract, or `arkworks`
+        #[structopt(long, default_value = "eth", possible_values = G2Order::VARIANTS)]
+        g2_order: G2Order,
+    },
+    /// Print the Keccak-256 transcript hash binding a proof to its verifying key and public inputs. See the README for the exact preimage.
+    TranscriptHash {
+        /// Path to the serialized verifying key
+        #[structopt(short, long, parse(from_os_str))]
+        verifying_key: PathBuf,
+
+        /// Path to the serialized proof
+        #[structopt(short, long, parse(from_os_str))]
+        proof: PathBuf,
+
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: PathBuf,
+    },
+    /// Merge several inputs files into one sorted by index, failing if two files assign different values to the same index
+    MergeInputs {
+        /// Paths to the inputs files
+        #[structopt(short, long, parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+
+        /// Write the merged inputs file here
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Split a JSON witness file into shards of contiguous variable index ranges, each with the original header. Pass the shards back with repeated `--witness` to prove from them.
+    SplitWitness {
+        /// Path to the witness file
+        #[structopt(short, long, parse(from_os_str))]
+        witness: PathBuf,
+
+        /// Number of shards
+        #[structopt(short = "n", long)]
+        shards: usize,
+
+        /// Write the shards to this directory as `NAME-shard-I.jsonl`, for a witness file `NAME.jsonl`
+        #[structopt(short, long, parse(from_os_str))]
+        out_dir: PathBuf,
+    },
+    /// Run a fixed-seed setup and proof and write the eth-compatible verifying key, proof, inputs and expected verification result as json, for testing other verifiers
+    ExportTestVectors {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Path to the witness file
+        #[structopt(short, long, parse(from_os_str))]
+        witness: PathBuf,
+
+        /// Path to the inputs file
+        #[structopt(short, long, parse(from_os_str))]
+        inputs: PathBuf,
+
+        /// Write the test vectors to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// List the public inputs that no constraint uses. Each is a warning, or an error under --strict: a prover can prove for any value of such an input.
+    UnconstrainedInputs {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+    },
+    /// Print the number of non-zero entries in each of the A, B, C matrices and the terms per constraint
+    Density {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+    },
+    /// Compute the linear rank of the constraints as rows of the matrix [A | B | C], and count duplicate constraints. Only duplicates are implied by other constraints: a linearly dependent one may still be needed. The exact rank is expensive for large circuits: pass --samples for an estimate from a random sample.
+    Redundancy {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Estimate from this many randomly chosen constraints instead of computing the exact rank
+        #[structopt(long)]
+        samples: Option<usize>,
+
+        /// Seed the sampling so the same constraints are chosen on every run
+        #[structopt(long, requires = "samples")]
+        seed: Option<u64>,
+    },
+    /// Rank constraints by the number of terms in their linear combinations, the main driver of synthesis time and size
+    Profile {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Number of constraints to report
+        #[structopt(short, long, default_value = "10")]
+        top: usize,
+    },
+    /// Write the circuit as a graphviz DOT graph of constraints and the variables they touch. Only practical for small circuits.
+    Graph {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Write the DOT graph to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Synthesize the circuit with arkworks and write the resulting A, B, C matrices as json, for comparing against the R1CS file
+    ExportMatrices {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Write the matrices to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Rebuild an R1CS file from A, B, C matrices as `export-matrices` writes them. Output variables are not in the matrices, so the header lists none.
+    ImportMatrices {
+        /// Path to the matrices json
+        #[structopt(short, long, parse(from_os_str))]
+        matrices: PathBuf,
+
+        /// Write the R1CS file here
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Synthesize the circuit once and write its constraint matrices as a binary cache, for create-trusted-setup and create-proof --matrix-cache
+    CacheMatrices {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Write the matrix cache to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+    /// Time getting the constraint matrices from an R1CS file against loading them from a matrix cache, and report the speedup. Files are read into memory before timing.
+    BenchmarkParse {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Matrix cache written by cache-matrices from the same R1CS. Built in memory if not given.
+        #[structopt(long, parse(from_os_str))]
+        matrix_cache: Option<PathBuf>,
+
+        /// Report the fastest of this many runs of each
+        #[structopt(long, default_value = "3")]
+        runs: usize,
+    },
+    /// Rewrite an R1CS file in canonical form, so that logically equivalent files are byte-identical
+    Canonicalize {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+
+        /// Write the canonical R1CS to this file
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+
+        /// Also remove constraints that are identical once canonicalized
+        #[structopt(long)]
+        dedup: bool,
+    },
+    /// Print a SHA-256 fingerprint of the constraint system that is the same for logically equivalent R1CS files. See the README for what it covers.
+    Fingerprint {
+        /// Path to the R1CS file
+        #[structopt(short, long, parse(from_os_str))]
+        r1cs: PathBuf,
+    },
+}
+
+// e.g. `proof/proof` with suffix "-eth" becomes `proof/proof-eth.json`
+fn sibling_json_path(path: &Path, suffix: &str) -> PathBuf {
+    sibling_path(path, suffix, "json")
+}
+
+fn sibling_path(path: &Path, suffix: &str, extension: &str) -> PathBuf {
+    let mut file_stem = path.file_stem().unwrap().to_os_string();
+    file_stem.push(suffix);
+    let mut sibling = path.with_file_name(file_stem);
+    sibling.set_extension(extension);
+    sibling
+}
+
+// Read buffers grow with the file, roughly one read per 1/1024th of it, within these bounds
+const MIN_READ_BUFFER_SIZE: usize = 8 * 1024;
+const MAX_READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+fn read_buffer_size(file_len: u64) -> usize {
+    usize::try_from(file_len / 1024)
+        .unwrap_or(MAX_READ_BUFFER_SIZE)
+        .clamp(MIN_READ_BUFFER_SIZE, MAX_READ_BUFFER_SIZE)
+}
+
+fn open_file(path: &Path, description: &str) -> io::Result<BufReader<File>> {
+    debug!("Loading {} from file {:}", description, path.display());
+    let file = File::open(path)?;
+    let capacity = read_buffer_size(file.metadata()?.len());
+    Ok(BufReader::with_capacity(capacity, file))
+}
+
+// Like `open_file`, but an http(s) URL is downloaded instead
+fn open_file_or_url(path: &Path, description: &str) -> io::Result<Box<dyn BufRead>> {
+    match remote::as_url(path) {
+        Some(url) => {
+            debug!("Fetching {} from {}", description, url);
+            Ok(Box::new(Cursor::new(remote::fetch(url)?)))
+        }
+        None => Ok(Box::new(open_file(path, description)?)),
+    }
+}
+
+/// Where to read a setup artifact from
+enum Artifact {
+    File(PathBuf),
+    Bundle(PathBuf),
+    /// Base64 in an environment variable, given on the command line as `@NAME`
+    Env(String),
+}
+
+impl Artifact {
+    // clap guarantees exactly one of the two is given
+    fn from_args(path: Option<PathBuf>, bundle: Option<PathBuf>) -> Self {
+        match bundle {
+            Some(bundle) => Artifact::Bundle(bundle),
+            None => {
+                let path = path.expect("either a path or a bundle is required");
+                match path.to_str().and_then(|s| s.strip_prefix('@')) {
+                    Some(name) => Artifact::Env(name.to_string()),
+                    None => Artifact::File(path),
+                }
+            }
+        }
+    }
+
+    /// Open the artifact, which if it is in a bundle must be one for the curve of `E`.
+    fn open<E: SupportedCurve>(
+        &self,
+        entry: &str,
+        description: &str,
+    ) -> io::Result<Box<dyn BufRead>> {
+        match self {
+            Artifact::File(path) => open_file_or_url(path, description),
+            Artifact::Bundle(path) => {
+                debug!("Loading {} from bundle {:}", description, path.display());
+                let mut file = File::open(path)?;
+
+                let manifest = bundle::read_manifest(&mut file)?;
+                if manifest.curve != E::CURVE.name() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Setup bundle is for curve {}, expected {}",
+                            manifest.curve,
+                            E::CURVE.name()
+                        ),
+                    ));
+                }
+
+                bundle::check_circuit(&mut file, &manifest)?;
+
+                let bytes = bundle::read_entry(file, entry)?;
+                Ok(Box::new(Cursor::new(bytes)))
+            }
+            Artifact::Env(name) => {
+                debug!("Loading {} from environment variable {}", description, name);
+                let encoded = std::env::var(name).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Cannot read {} from ${}: {}", description, name, e),
+                    )
+                })?;
+
+                let bytes = BASE64.decode(encoded.trim()).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("${} is not valid base64: {}", name, e),
+                    )
+                })?;
+                Ok(Box::new(Cursor::new(bytes)))
+            }
+        }
+    }
+}
+
+impl Command {
+    /// Whether the command is generic over the pairing engine, so that `--curve` may pick one
+    /// other than BN254
+    fn supports_any_curve(&self) -> bool {
+        matches!(
+            self,
+            Command::CreateTrustedSetup { .. }
+                | Command::CreateProof { .. }
+                | Command::GenerateContract { .. }
+                | Command::VerifyProof { .. }
+                | Command::RunR1CS { .. }
+        )
+    }
+}
+
+// Run `$body` with `$E` the pairing engine of `curve`
+macro_rules! with_curve {
+    ($curve:expr, $E:ident => $body:expr) => {
+        match $curve {
+            Curve::Bn254 => {
+                type $E = Bn254;
+                $body
+            }
+            Curve::Bls12_381 => {
+                type $E = Bls12_381;
+                $body
+            }
+            Curve::Bw6_761 => {
+                type $E = BW6_761;
+                $body
+            }
+        }
+    };
+}
+
+/// Where to read the R1CS and witness from
+enum CircuitSource {
+    Files {
+        r1cs: PathBuf,
+        /// One witness file, or shards each assigning a disjoint set of variables
+        witness: Vec<PathBuf>,
+        witness_format: WitnessFormat,
+    },
+    /// One file holding the header followed by interleaved constraint and witness lines
+    Combined(PathBuf),
+    /// An R1CS file and one JSON object holding both the inputs and the witness
+    Assignment { r1cs: PathBuf, assignment: PathBuf },
+}
+
+impl CircuitSource {
+    // clap guarantees either a combined file, or an R1CS with a witness or an assignment
+    fn from_args(
+        r1cs: Option<PathBuf>,
+        witness: Vec<PathBuf>,
+        witness_format: Option<WitnessFormat>,
+        combined: Option<PathBuf>,
+        assignment: Option<PathBuf>,
+    ) -> Self {
+        match (combined, assignment) {
+            (Some(combined), _) => CircuitSource::Combined(combined),
+            (None, Some(assignment)) => CircuitSource::Assignment {
+                r1cs: r1cs.expect("an assignment needs an R1CS file"),
+                assignment,
+            },
+            (None, None) => {
+                let r1cs = r1cs.expect("either an R1CS or a combined file is required");
+                let first = witness
+                    .first()
+                    .expect("either a witness or a combined file is required");
+                let witness_format = WitnessFormat::resolve(witness_format, first);
+                CircuitSource::Files {
+                    r1cs,
+                    witness,
+                    witness_format,
+                }
+            }
+        }
+    }
+
+    fn load<E: SupportedCurve>(&self) -> io::Result<(R1CS<E>, Witness<E>)> {
+        match self {
+            CircuitSource::Files {
+                r1cs,
+                witness,
+                witness_format,
+            } => {
+                let r1cs = load_r1cs(open_file(r1cs, "R1CS")?)?;
+                let witness = load_witness_shards(
+                    witness,
+                    *witness_format,
+                    r1cs.constant_index,
+                    &r1cs.input_variables,
+                    &r1cs.witness_variables,
+                )?;
+                Ok((r1cs, witness))
+            }
+            CircuitSource::Combined(path) => {
+                let (r1cs_file, witness_file) =
+                    combined::parse_combined_file(open_file(path, "combined R1CS and witness")?)?;
+                check_curve::<E>(&r1cs_file.header)?;
+                let mut r1cs: R1CS<E> = r1cs_file.into();
+                convention::current().normalize(&mut r1cs);
+                warn_if_no_public_inputs(&r1cs)?;
+                let witness = witness_file.into();
+                validate::check_witness_covers(
+                    &r1cs.input_variables,
+                    &r1cs.witness_variables,
+                    &witness,
+                )?;
+                warn_if_extra_witness_variables(
+                    &r1cs.input_variables,
+                    &r1cs.witness_variables,
+                    &witness,
+                )?;
+                Ok((r1cs, witness))
+            }
+            CircuitSource::Assignment { r1cs, .. } => {
+                let r1cs = load_r1cs(open_file(r1cs, "R1CS")?)?;
+                let witness = self.load_witness(
+                    r1cs.constant_index,
+                    &r1cs.input_variables,
+                    &r1cs.witness_variables,
+                )?;
+                Ok((r1cs, witness))
+            }
+        }
+    }
+
+    // A combined file hashes as the R1CS file of its circuit would
+    fn r1cs_sha256(&self) -> io::Result<Vec<u8>> {
+        let path = match self {
+            CircuitSource::Files { r1cs, .. } | CircuitSource::Assignment { r1cs, .. } => r1cs,
+            CircuitSource::Combined(path) => path,
+        };
+        matrix_cache::r1cs_sha256(open_file(path, "R1CS")?)
+    }
+
+    /// Load only the witness, for proving from cached matrices. A combined file is read whole.
+    fn load_witness<E: SupportedCurve>(
+        &self,
+        constant_index: usize,
+        input_variables: &[usize],
+        witness_variables: &[usize],
+    ) -> io::Result<Witness<E>> {
+        match self {
+            CircuitSource::Files {
+                witness,
+                witness_format,
+                ..
+            } => load_witness_shards(
+                witness,
+                *witness_format,
+                constant_index,
+                input_variables,
+                witness_variables,
+            ),
+            CircuitSource::Combined(_) => Ok(self.load()?.1),
+            CircuitSource::Assignment { assignment, .. } => {
+                let (_, witness) = witness::parse_assignment(
+                    open_file(assignment, "inputs and witness")?,
+                    constant_index,
+                    input_variables,
+                )?;
+                validate::check_witness_covers(input_variables, witness_variables, &witness)?;
+                warn_if_extra_witness_variables(input_variables, witness_variables, &witness)?;
+                Ok(witness)
+            }
+        }
+    }
+}
+
+/// Where to read the public inputs of a proof from
+enum InputsSource {
+    File(PathBuf),
+    /// Concatenated 32-byte words in hex, in the order of the verifier's inputs
+    Words(String),
+    /// A snarkjs `public.json`
+    Snarkjs(PathBuf),
+    /// Several sources that must agree exactly, to catch encoding mistakes in any one of them
+    CrossCheck(Vec<InputsSource>),
+}
+
+impl InputsSource {
+    // clap guarantees at least one of them
+    fn from_args(
+        inputs: Option<PathBuf>,
+        inputs_words: Option<String>,
+        inputs_snarkjs: Option<PathBuf>,
+    ) -> Self {
+        let mut sources: Vec<InputsSource> = inputs
+            .map(InputsSource::File)
+            .into_iter()
+            .chain(inputs_words.map(InputsSource::Words))
+            .chain(inputs_snarkjs.map(InputsSource::Snarkjs))
+            .collect();
+        match sources.len() {
+            1 => sources.remove(0),
+            _ => InputsSource::CrossCheck(sources),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            InputsSource::File(path) => format!("inputs file {}", path.display()),
+            InputsSource::Words(_) => "input words".to_string(),
+            InputsSource::Snarkjs(path) => format!("snarkjs inputs {}", path.display()),
+            InputsSource::CrossCheck(_) => "cross-checked inputs".to_string(),
+        }
+    }
+
+    fn load<E: SupportedCurve>(&self) -> io::Result<Vec<E::ScalarField>> {
+        match self {
+            InputsSource::File(path) => load_inputs::<E, _>(open_file(path, "inputs")?),
+            InputsSource::Snarkjs(path) => {
+                inputs::parse_public_json(open_file(path, "snarkjs inputs")?)
+            }
+            InputsSource::CrossCheck(sources) => {
+                let (first, rest) = sources
+                    .split_first()
+                    .expect("a cross-check has several sources");
+                let expected = first.load::<E>()?;
+                for source in rest {
+                    let actual = source.load::<E>()?;
+                    if let Some(i) = (0..expected.len().max(actual.len()))
+                        .find(|&i| expected.get(i) != actual.get(i))
+                    {
+                        let show = |inputs: &[E::ScalarField]| {
+                            inputs
+                                .get(i)
+                                .map_or("nothing".to_string(), field::to_decimal_string)
+                        };
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "Public input {} is {} in the {} but {} in the {}",
+                                i,
+                                show(&expected),
+                                first.describe(),
+                                show(&actual),
+                                source.describe()
+                            ),
+                        ));
+                    }
+                }
+                info!("All {} inputs sources agree", sources.len());
+                Ok(expected)
+            }
+            InputsSource::Words(words) => {
+                E::CURVE.require_bn254("--inputs-words")?;
+                let (inputs, reduced) = eth::inputs_from_words(words)?;
+                for i in reduced {
+                    strict::warn(
+                        is_strict(),
+                        &format!(
+                            "Input word {} is not below the field modulus and was reduced",
+                            i
+                        ),
+                    )?;
+                }
+                Ok(expect_from_bn254(inputs))
+            }
+        }
+    }
+}
+
+/// What the prover works from: the R1CS to synthesize, or matrices it was already synthesized to
+enum Constraints<E: SupportedCurve> {
+    R1CS(R1CS<E>),
+    Cached(MatrixCache<E>),
+}
+
+fn warn_if_no_public_inputs<E: SupportedCurve>(r1cs: &R1CS<E>) -> io::Result<()> {
+    match validate::check_public_inputs(r1cs) {
+        Ok(()) => Ok(()),
+        Err(e) => strict::warn(
+            is_strict(),
+            &format!("{}. Such a proof only shows that some witness exists.", e),
+        ),
+    }
+}
+
+fn warn_if_extra_witness_variables<E: SupportedCurve>(
+    input_variables: &[usize],
+    witness_variables: &[usize],
+    witness: &Witness<E>,
+) -> io::Result<()> {
+    let extra = validate::extra_witness_variables(input_variables, witness_variables, witness);
+    match extra.first() {
+        None => Ok(()),
+        Some(v) => strict::warn(is_strict(), &format!(
+            "witness assigns {} variable(s) the R1CS does not declare, the first being {}. The witness may be stale, from another version of the circuit.",
+            extra.len(),
+            v
+        )),
+    }
+}
+
+// An R1CS over another field would parse, reduced into this one, and prove the wrong statement
+fn check_curve<E: SupportedCurve>(header: &Header) -> io::Result<()> {
+    validate::check_field::<E>(header).map_err(|e| {
+        error::with_context(
+            e,
+            &format!(
+                "The R1CS is not over the scalar field of {}, select its curve with --curve",
+                E::CURVE.name()
+            ),
+        )
+    })
+}
+
+// Only reached once the flags that need it were checked with `Curve::require_bn254`
+fn expect_bn254<T: Any, B: Any>(value: &T) -> &B {
+    curve::as_bn254(value).expect("BN254-only flags are refused over other curves")
+}
+
+fn expect_from_bn254<B: Any, T: Any>(value: B) -> T {
+    curve::from_bn254(value).expect("BN254-only flags are refused over other curves")
+}
+
+// EIP-2537 fixes the encoding of BLS12-381 points, so only BN254 json can be laid out otherwise
+fn check_eth_json<E: SupportedCurve>(
+    g2_order: G2Order,
+    json_naming: &JsonNaming,
+) -> io::Result<()> {
+    if E::CURVE == Curve::Bls12_381
+        && (g2_order != G2Order::Arkworks || *json_naming != JsonNaming::Arkworks)
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--g2-order and --json-naming only apply to BN254, BLS12-381 points are written in their EIP-2537 encoding",
+        ));
+    }
+    json_naming.check_g2_order(g2_order)
+}
+
+/// The eth-compatible verifying key as `-eth.json` holds it, and as a bundle does, with the
+/// default field names so it can be read back. Over BLS12-381 both are its EIP-2537 encoding.
+fn eth_vk_json<E: SupportedCurve>(
+    verifying_key: &VerifyingKey<E>,
+    g2_order: G2Order,
+    json_naming: &JsonNaming,
+) -> io::Result<(serde_json::Value, Vec<u8>)> {
+    if let Some(verifying_key) = curve::as_bls12_381::<_, VerifyingKey<Bls12_381>>(verifying_key) {
+        let json = serde_json::to_value(eip2537::EthVerifyingKey::from(verifying_key))?;
+        let bytes = serde_json::to_vec(&json)?;
+        return Ok((json, bytes));
+    }
+    let verifying_key: &VerifyingKey<Bn254> = expect_bn254(verifying_key);
+    let eth_vk = g2_order.apply_to_vk(&verifying_key.clone().into());
+    Ok((json_naming.vk_json(&eth_vk), serde_json::to_vec(&eth_vk)?))
+}
+
+fn load_r1cs<E: SupportedCurve, R: BufRead>(reader: R) -> io::Result<R1CS<E>> {
+    let r1cs_file = parse_r1cs(reader)?;
+    check_curve::<E>(&r1cs_file.header)?;
+    let mut r1cs: R1CS<E> = r1cs_file.into();
+    convention::current().normalize(&mut r1cs);
+    warn_if_no_public_inputs(&r1cs)?;
+    Ok(r1cs)
+}
+
+fn load_witness<E: SupportedCurve, R: BufRead>(reader: R) -> io::Result<Witness<E>> {
+    Ok(parse_witness_file(reader)?.into())
+}
+
+fn load_witness_file<E: SupportedCurve>(
+    path: &Path,
+    format: WitnessFormat,
+    constant_index: usize,
+    input_variables: &[usize],
+) -> io::Result<Witness<E>> {
+    let reader = open_file(path, "witness")?;
+    let witness = match format {
+        WitnessFormat::Json => load_witness(reader)?,
+        WitnessFormat::Bin => {
+            witness::parse_binary_witness(reader, constant_index, input_variables)?
+        }
+        WitnessFormat::Montgomery => {
+            witness::parse_montgomery_witness(reader, constant_index, input_variables)?
+        }
+    };
+    validate::check_constant_index(constant_index, witness.constant_index)?;
+    Ok(witness)
+}
+
+fn load_matrix_cache<E: SupportedCurve>(path: &Path) -> io::Result<MatrixCache<E>> {
+    debug!("Loading matrix cache from file {:}", path.display());
+    deserialize_from(
+        open_file(path, "matrix cache")?,
+        "matrix cache",
+        None,
+        artifact_options(),
+    )
+}
+
+// Shards are merged and must then assign every variable of the circuit
+fn load_witness_shards<E: SupportedCurve>(
+    paths: &[PathBuf],
+    format: WitnessFormat,
+    constant_index: usize,
+    input_variables: &[usize],
+    witness_variables: &[usize],
+) -> io::Result<Witness<E>> {
+    let witness = if let [path] = paths {
+        load_witness_file(path, format, constant_index, input_variables)?
+    } else {
+        if format != WitnessFormat::Json {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Binary witnesses assign variables by position and can't be sharded",
+            ));
+        }
+        let shards = paths
+            .iter()
+            .map(|path| load_witness_file(path, format, constant_index, input_variables))
+            .collect::<io::Result<Vec<_>>>()?;
+        let witness = witness::merge_witnesses(shards)?;
+        validate::check_witness_covers(input_variables, witness_variables, &witness)?;
+        witness
+    };
+    warn_if_extra_witness_variables(input_variables, witness_variables, &witness)?;
+    Ok(witness)
+}
+
+fn load_inputs<E: SupportedCurve, R: BufRead>(reader: R) -> io::Result<Vec<E::ScalarField>> {
+    let inputs: Inputs<E> = parse_inputs_file(reader)?;
+    Ok(inputs.inputs.into_iter().map(|(_, v)| v).collect())
+}
+
+// Set once from the global `--strict` and `--tag-artifacts` flags before any command runs
+static STRICT: AtomicBool = AtomicBool::new(false);
+static TAG_ARTIFACTS: AtomicBool = AtomicBool::new(false);
+
+fn is_strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+// How keys and proofs are written and read under the global flags
+fn artifact_options() -> serialization::Options {
+    serialization::Options {
+        tagged: TAG_ARTIFACTS.load(Ordering::Relaxed),
+        strict: is_strict(),
+    }
+}
+
+fn to_bytes<T: CanonicalSerialize>(value: &T, description: &str) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    serialize_to(value, &mut bytes, description, artifact_options())?;
+    Ok(bytes)
+}
+
+// Hex-encoded SHA-256 of the untagged uncompressed serialization, which identifies the value
+// whatever layout or tag its file has
+fn artifact_sha256<T: CanonicalSerialize>(value: &T, description: &str) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    serialize_to(
+        value,
+        &mut bytes,
+        description,
+        serialization::Options::default(),
+    )?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+fn not_deterministic(description: &str) -> io::Error {
+    error::prove_failed(format!(
+        "Seeded setup is not deterministic: the {} differs between two runs",
+        description
+    ))
+}
+
+/// Setup seeded from `entropy`. With `verify_determinism` it runs twice, failing unless both
+/// runs serialize to the same keys.
+fn seeded_setup<E: SupportedCurve>(
+    r1cs: R1CS<E>,
+    entropy: &[u8],
+    verify_determinism: bool,
+    progress: Option<&Progress>,
+) -> io::Result<(ProvingKey<E>, VerifyingKey<E>)> {
+    let rerun = verify_determinism.then(|| r1cs.clone());
+    let (proving_key, verifying_key) =
+        setup_with_progress(r1cs, &mut entropy::rng_from_entropy(entropy)?, progress)?;
+
+    if let Some(r1cs) = rerun {
+        debug!("Repeating the seeded setup to check it is deterministic");
+        let again = setup_with(r1cs, &mut entropy::rng_from_entropy(entropy)?)?;
+
+        if to_bytes(&proving_key, "proving key")? != to_bytes(&again.0, "proving key")? {
+            return Err(not_deterministic("proving key"));
+        }
+        if to_bytes(&verifying_key, "verifying key")? != to_bytes(&again.1, "verifying key")? {
+            return Err(not_deterministic("verifying key"));
+        }
+        info!("Seeded setup is deterministic");
+    }
+
+    Ok((proving_key, verifying_key))
+}
+
+// Write an output file. With `skip_unchanged` the output is rendered in memory first, and a file
+// already holding the same bytes is left alone so that its modification time is kept.
+fn write_output<F>(path: &Path, skip_unchanged: bool, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    if !skip_unchanged {
+        return write(&mut File::create(path)?);
+    }
+
+    let mut bytes = Vec::new();
+    write(&mut bytes)?;
+    let existing = match File::open(path) {
+        Ok(mut file) => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            Some(hasher.finalize())
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+    if existing == Some(Sha256::digest(&bytes)) {
+        info!("File {:} is unchanged, not rewriting it", path.display());
+        return Ok(());
+    }
+    std::fs::write(path, bytes)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_trusted_setup<E: SupportedCurve>(
+    r1cs_path: PathBuf,
+    matrix_cache: Option<PathBuf>,
+    pk_output: Option<PathBuf>,
+    vk_output: Option<PathBuf>,
+    ethereum: bool,
+    g2_order: G2Order,
+    json_naming: JsonNaming,
+    gnark: bool,
+    entropy: Option<PathBuf>,
+    verify_determinism: bool,
+    skip_unchanged: bool,
+    bundle: Option<PathBuf>,
+    disclose: Option<PathBuf>,
+    summary: bool,
+    fixed_inputs: Option<PathBuf>,
+) -> io::Result<()> {
+    if ethereum {
+        E::CURVE.require_ethereum("--ethereum")?;
+        check_eth_json::<E>(g2_order, &json_naming)?;
+    }
+    if gnark {
+        E::CURVE.require_bn254("--gnark")?;
+    }
+
+    debug!("Loading R1CS from file {:}", r1cs_path.display());
+    let r1cs_bytes = std::fs::read(&r1cs_path)?;
+    let r1cs = match matrix_cache {
+        Some(path) => {
+            let cache = load_matrix_cache::<E>(&path)?;
+            cache.check_r1cs(&matrix_cache::r1cs_sha256(r1cs_bytes.as_slice())?)?;
+            let r1cs = cache.to_r1cs();
+            warn_if_no_public_inputs(&r1cs)?;
+            r1cs
+        }
+        None => load_r1cs::<E, _>(r1cs_bytes.as_slice())?,
+    };
+    let public_inputs = r1cs.input_variables.len();
+    let input_variables = r1cs.input_variables.clone();
+    let randomness = match entropy {
+        Some(_) => disclosure::Randomness::EntropyFile,
+        None => disclosure::Randomness::System,
+    };
+
+    let (proving_key, verifying_key) = match entropy {
+        Some(path) => {
+            debug!("Loading entropy from file {:}", path.display());
+            seeded_setup(
+                r1cs,
+                &std::fs::read(&path)?,
+                verify_determinism,
+                progress::bar("setup").as_ref(),
+            )?
+        }
+        None => setup_with_progress(r1cs, &mut thread_rng(), progress::bar("setup").as_ref())?,
+    };
+
+    let verifying_key = match fixed_inputs {
+        Some(path) => {
+            debug!("Loading fixed inputs from file {:}", path.display());
+            let fixed = parse_inputs_file::<E, _>(open_file(&path, "fixed inputs")?)?;
+            info!(
+                "Folding {} fixed public inputs into the verifying key",
+                fixed.inputs.len()
+            );
+            fold::fold_fixed_inputs(&verifying_key, &input_variables, &fixed.inputs)?
+        }
+        None => verifying_key,
+    };
+
+    let eth_vk = match ethereum {
+        true => Some(eth_vk_json(&verifying_key, g2_order, &json_naming)?),
+        false => None,
+    };
+
+    let mut written = Vec::new();
+
+    if let Some(pk_output) = &pk_output {
+        info!("Serializing proving key to file {:}", pk_output.display());
+
+        write_output(pk_output, skip_unchanged, |out| {
+            serialize_to(&proving_key, out, "proving key", artifact_options())
+        })?;
+        written.push(pk_output.clone());
+    }
+
+    if let Some(vk_output) = &vk_output {
+        info!(
+            "Serializing verification key to file {:}",
+            vk_output.display()
+        );
+
+        write_output(vk_output, skip_unchanged, |out| {
+            serialize_to(&verifying_key, out, "verifying key", artifact_options())
+        })?;
+        written.push(vk_output.clone());
+
+        if let Some((eth_vk, _)) = &eth_vk {
+            let vk_output = sibling_json_path(vk_output, "-eth");
+
+            info!(
+                "Serializing eth-compatible verifying key to file {:}",
+                vk_output.display()
+            );
+            write_output(&vk_output, skip_unchanged, |out| {
+                out.write_all(eth_vk.to_string().as_bytes())
+            })?;
+            written.push(vk_output);
+        }
+
+        if gnark {
+            let vk_output = sibling_path(vk_output, "-gnark", "bin");
+
+            info!(
+                "Serializing gnark verifying key to file {:}",
+                vk_output.display()
+            );
+            write_output(&vk_output, skip_unchanged, |out| {
+                out.write_all(&gnark::verifying_key_bytes(
+                    expect_bn254(&proving_key),
+                    expect_bn254(&verifying_key),
+                ))
+            })?;
+            written.push(vk_output);
+        }
+    }
+
+    if let Some(bundle) = bundle {
+        let mut entries = Vec::new();
+
+        let mut bytes = Vec::new();
+        serialize_to(&proving_key, &mut bytes, "proving key", artifact_options())?;
+        entries.push((bundle::PROVING_KEY, bytes));
+
+        let mut bytes = Vec::new();
+        serialize_to(
+            &verifying_key,
+            &mut bytes,
+            "verifying key",
+            artifact_options(),
+        )?;
+        entries.push((bundle::VERIFYING_KEY, bytes));
+
+        if let Some((_, bundled)) = &eth_vk {
+            entries.push((bundle::VERIFYING_KEY_ETH, bundled.clone()));
+        }
+        entries.push((bundle::CIRCUIT, r1cs_bytes.clone()));
+
+        let manifest = bundle::Manifest {
+            curve: E::CURVE.name().to_string(),
+            circuit_sha256: bundle::circuit_sha256(&r1cs_bytes),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            files: entries.iter().map(|(name, _)| name.to_string()).collect(),
+        };
+
+        info!("Writing setup bundle to file {:}", bundle.display());
+
+        let mut zip = Cursor::new(Vec::new());
+        bundle::write_bundle(&mut zip, &manifest, &entries)?;
+        write_output(&bundle, skip_unchanged, |out| out.write_all(zip.get_ref()))?;
+        written.push(bundle);
+    }
+
+    if let Some(disclose) = disclose {
+        let mut disclosure = disclosure::Disclosure::new(
+            E::CURVE.name(),
+            hex::encode(Sha256::digest(&r1cs_bytes)),
+            artifact_sha256(&verifying_key, "verifying key")?,
+            randomness,
+        );
+        if skip_unchanged {
+            // Only the timestamp differs for an unchanged setup, so keep the existing one
+            let existing: Option<disclosure::Disclosure> = File::open(&disclose)
+                .ok()
+                .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok());
+            if let Some(existing) = existing.filter(|existing| existing.same_setup(&disclosure)) {
+                disclosure.created_at = existing.created_at;
+            }
+        }
+
+        info!("Writing setup disclosure to file {:}", disclose.display());
+
+        write_output(&disclose, skip_unchanged, |out| disclosure.write(out))?;
+        written.push(disclose);
+    }
+
+    if summary {
+        let mut summary = summary::SetupSummary::of(&proving_key, public_inputs);
+        for path in written {
+            let size = std::fs::metadata(&path)?.len();
+            summary.files.push((path, size));
+        }
+        println!("{}", summary);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_proof<E: SupportedCurve>(
+    proving_key: Artifact,
+    self_verify: Option<Artifact>,
+    circuit: CircuitSource,
+    matrix_cache: Option<PathBuf>,
+    output: PathBuf,
+    append: bool,
+    gnark: bool,
+    ethereum: bool,
+    g2_order: G2Order,
+    json_naming: JsonNaming,
+    commit_inputs: bool,
+    eip712: Option<EIP712Domain>,
+    seed: Option<u64>,
+    zk: bool,
+    time_limit: Option<Duration>,
+    check_witness: bool,
+    report_memory: bool,
+) -> io::Result<()> {
+    if gnark {
+        E::CURVE.require_bn254("--gnark")?;
+    }
+    if ethereum {
+        E::CURVE.require_ethereum("--ethereum")?;
+        check_eth_json::<E>(g2_order, &json_naming)?;
+    }
+    if commit_inputs {
+        E::CURVE.require_bn254("--commit-inputs")?;
+    }
+    if eip712.is_some() {
+        E::CURVE.require_bn254("--eip712")?;
+    }
+
+    let (constraints, witness) = match matrix_cache {
+        Some(path) => {
+            let cache = load_matrix_cache::<E>(&path)?;
+            cache.check_r1cs(&circuit.r1cs_sha256()?)?;
+            let witness = circuit.load_witness(
+                cache.constant_index(),
+                &cache.input_variables,
+                &cache.witness_variables,
+            )?;
+            (Constraints::Cached(cache), witness)
+        }
+        None => {
+            let (r1cs, witness) = circuit.load::<E>()?;
+            if check_witness {
+                validate::check_satisfied(&r1cs, &witness)?;
+            }
+            (Constraints::R1CS(r1cs), witness)
+        }
+    };
+
+    let min_size = match &constraints {
+        Constraints::R1CS(r1cs) => min_proving_key_size(r1cs),
+        Constraints::Cached(cache) => min_proving_key_size_for::<E>(
+            cache.input_variables.len(),
+            cache.witness_variables.len(),
+            cache.matrices.num_constraints,
+        ),
+    };
+    let proving_key: ProvingKey<E> = deserialize_from(
+        proving_key.open::<E>(bundle::PROVING_KEY, "proving key")?,
+        "proving key",
+        Some(min_size),
+        artifact_options(),
+    )?;
+
+    let public_inputs = witness.public_inputs();
+
+    let self_verify: Option<VerifyingKey<E>> = match self_verify {
+        Some(verifying_key) => Some(deserialize_from(
+            verifying_key.open::<E>(bundle::VERIFYING_KEY, "verifying key")?,
+            "verifying key",
+            Some(verifying_key_size::<E>(public_inputs.len())),
+            artifact_options(),
+        )?),
+        None => None,
+    };
+
+    if !zk {
+        strict::warn(
+            is_strict(),
+            "Creating a proof without zero-knowledge, do not use it in production",
+        )?;
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let progress = progress::bar("proving");
+    let proof = time_limit::run_with_limit(time_limit, move || match constraints {
+        Constraints::R1CS(r1cs) => {
+            prove_with_progress(&proving_key, r1cs, witness, zk, &mut rng, progress.as_ref())
+        }
+        Constraints::Cached(cache) => prove_cached_with(
+            &proving_key,
+            &cache,
+            &witness,
+            zk,
+            &mut rng,
+            progress.as_ref(),
+        ),
+    })?;
+
+    if report_memory {
+        memory::log_peak_rss("proving");
+    }
+
+    if let Some(verifying_key) = &self_verify {
+        if !verify(verifying_key, &proof, &public_inputs)? {
+            return Err(error::verification_failed(
+                "Proof does not verify against the verifying key, not writing it",
+            ));
+        }
+        info!("Proof passed self-verification");
+    }
+
+    if append {
+        info!("Appending proof to file {:}", output.display());
+
+        let file = OpenOptions::new().create(true).append(true).open(&output)?;
+        append_record(&proof, file, "proof", artifact_options())?;
+    } else {
+        info!("Serializing proof to file {:}", output.display());
+
+        serialize_to(
+            &proof,
+            File::create(output.clone())?,
+            "proof",
+            artifact_options(),
+        )?;
+    }
+
+    if gnark {
+        let gnark_output = sibling_path(&output, "-gnark", "bin");
+
+        info!(
+            "Serializing gnark proof to file {:}",
+            gnark_output.display()
+        );
+        std::fs::write(&gnark_output, gnark::proof_bytes(expect_bn254(&proof)))?;
+    }
+
+    let eth_proof = || {
+        let proof: &Proof<Bn254> = expect_bn254(&proof);
+        g2_order.apply_to_proof(&proof.clone().into())
+    };
+
+    if ethereum {
+        let eth_output = sibling_json_path(&output, "-eth");
+        let mut file = File::create(eth_output.clone())?;
+
+        info!(
+            "Serializing eth-compatible proof to file {:}",
+            eth_output.display()
+        );
+        let json = match curve::as_bls12_381::<_, Proof<Bls12_381>>(&proof) {
+            Some(proof) => serde_json::to_value(eip2537::EthProof::from(proof))?,
+            None => json_naming.proof_json(&eth_proof()),
+        };
+        file.write_all(json.to_string().as_bytes())?;
+    };
+
+    if commit_inputs {
+        let commitment_output = sibling_json_path(&output, "-commitment");
+        let public_inputs: &Vec<Fr> = expect_bn254(&public_inputs);
+        let commitment = eth::inputs_commitment(public_inputs);
+        let abi_commitment = eth::inputs_abi_commitment(public_inputs);
+
+        info!(
+            "Writing public inputs commitment to file {:}",
+            commitment_output.display()
+        );
+        serde_json::to_writer_pretty(
+            File::create(commitment_output)?,
+            &serde_json::json!({
+                "scheme": "keccak256",
+                "commitment": format!("0x{}", hex::encode(commitment)),
+                "abi_commitment": format!("0x{}", hex::encode(abi_commitment)),
+            }),
+        )?;
+    }
+
+    if let Some(domain) = eip712 {
+        let eip712_output = sibling_json_path(&output, "-eip712");
+        let typed_data = eth::eip712_typed_data(
+            &eth_proof(),
+            expect_bn254::<_, Vec<Fr>>(&public_inputs),
+            domain,
+        );
+
+        info!(
+            "Serializing EIP-712 typed data to file {:}",
+            eip712_output.display()
+        );
+        serde_json::to_writer_pretty(File::create(eip712_output)?, &typed_data)?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_proof<E: SupportedCurve>(
+    verifying_key: Artifact,
+    proof: PathBuf,
+    inputs: InputsSource,
+    ethereum: bool,
+    g2_order: G2Order,
+    records: bool,
+    results: Option<PathBuf>,
+    commitment: Option<String>,
+    stream_vk: bool,
+    continue_on_error: bool,
+) -> io::Result<bool> {
+    if ethereum {
+        E::CURVE.require_bn254("--ethereum")?;
+    }
+
+    let inputs = inputs.load::<E>()?;
+
+    if let Some(expected) = commitment {
+        E::CURVE.require_bn254("--commitment")?;
+        let actual = hex::encode(eth::inputs_commitment(expect_bn254::<_, Vec<Fr>>(&inputs)));
+        let expected = expected.trim_start_matches("0x").to_lowercase();
+        if actual != expected {
+            return Err(error::verification_failed(&format!(
+                "Public inputs do not match the commitment: expected 0x{}, inputs commit to 0x{}",
+                expected, actual
+            )));
+        }
+        info!("Public inputs match the commitment");
+    }
+
+    if stream_vk {
+        let proof: Proof<E> = deserialize_from(
+            open_file_or_url(&proof, "proof")?,
+            "proof",
+            Some(proof_size::<E>()),
+            artifact_options(),
+        )?;
+        let result = streaming::verify(
+            verifying_key.open::<E>(bundle::VERIFYING_KEY, "verifying key")?,
+            &proof,
+            &inputs,
+            artifact_options(),
+        )?;
+        info!("Proof verification result: {}", result);
+        return Ok(result);
+    }
+
+    let verifying_key: VerifyingKey<E> = if ethereum {
+        let reader =
+            verifying_key.open::<E>(bundle::VERIFYING_KEY_ETH, "eth-compatible verifying key")?;
+
+        let eth_vk: circom_eth::VerifyingKey = serde_json::from_reader(reader).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Failed to deserialize verifying key: {}", e),
+            )
+        })?;
+
+        expect_from_bn254(g2_order.vk_from_eth(&eth_vk)?)
+    } else {
+        deserialize_from(
+            verifying_key.open::<E>(bundle::VERIFYING_KEY, "verifying key")?,
+            "verifying key",
+            Some(verifying_key_size::<E>(inputs.len())),
+            artifact_options(),
+        )?
+    };
+
+    let proofs: Vec<io::Result<Proof<E>>> = if records {
+        let proofs = read_each_record(
+            open_file_or_url(&proof, "proofs")?,
+            "proof",
+            artifact_options(),
+        )?;
+        // Otherwise an empty or truncated proof log would pass without a proof being checked
+        if proofs.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Proofs file {} holds no proofs", proof.display()),
+            ));
+        }
+        if continue_on_error {
+            proofs
+        } else {
+            // Stop at the first record that can't be read
+            proofs
+                .into_iter()
+                .map(|proof| proof.map(Ok))
+                .collect::<io::Result<_>>()?
+        }
+    } else {
+        vec![Ok(deserialize_from(
+            open_file_or_url(&proof, "proof")?,
+            "proof",
+            Some(proof_size::<E>()),
+            artifact_options(),
+        )?)]
+    };
+    let n_proofs = proofs.len();
+
+    let mut log = match results {
+        Some(path) => {
+            debug!("Loading verification results from file {:}", path.display());
+            let readable: Vec<Proof<E>> = proofs
+                .iter()
+                .filter_map(|proof| proof.as_ref().ok().cloned())
+                .collect();
+            let mut batch = checkpoint::Batch::new(&verifying_key, &readable, &inputs)?;
+            batch.unreadable = (0..n_proofs).filter(|&i| proofs[i].is_err()).collect();
+            Some(checkpoint::ResultsLog::open(&path, &batch)?)
+        }
+        None => None,
+    };
+    let progress = if records {
+        progress::bar("verification")
+    } else {
+        None
+    };
+
+    let mut result = true;
+    // With --continue-on-error, each proof that could not be read or verified, and why
+    let mut failures = Vec::new();
+    for (i, proof) in proofs.into_iter().enumerate() {
+        let recorded = log.as_ref().and_then(|log| log.recorded.get(&i).copied());
+        let verified = match (proof, recorded) {
+            (Ok(_), Some(verified)) => {
+                debug!("Proof {} already recorded as verified: {}", i, verified);
+                Ok(verified)
+            }
+            (Ok(proof), None) => verify(&verifying_key, &proof, &inputs).and_then(|verified| {
+                if let Some(log) = log.as_mut() {
+                    log.append(i, verified)?;
+                }
+                Ok(verified)
+            }),
+            (Err(e), _) => Err(e),
+        };
+        match verified {
+            Ok(verified) => {
+                if records {
+                    info!("Proof {} verification result: {}", i, verified);
+                }
+                if !verified && continue_on_error {
+                    failures.push((i, error::verification_failed("proof does not verify")));
+                }
+                result &= verified;
+            }
+            Err(e) if continue_on_error => {
+                warn!("Proof {} could not be verified: {}", i, e);
+                failures.push((i, e));
+            }
+            Err(e) => return Err(e),
+        }
+        if let Some(progress) = &progress {
+            progress.report((i + 1) as f64 / n_proofs as f64);
+        }
+    }
+    if let Some(progress) = progress {
+        progress.finished();
+    }
+    // Proofs taken from the results file count too, so each of them must be recorded as verified
+    if let Some(log) = &log {
+        result &= log.all_verified(n_proofs);
+    }
+
+    if let Some((_, first)) = failures.first() {
+        // The summary fails the way the first failure would have without --continue-on-error
+        let summary = failures
+            .iter()
+            .map(|(i, e)| format!("\n  proof {}: {}", i, e))
+            .collect::<String>();
+        return Err(error::with_message(
+            first,
+            format!(
+                "{} of {} proofs failed:{}",
+                failures.len(),
+                n_proofs,
+                summary
+            ),
+        ));
+    }
+
+    info!("Proof verification result: {}", result);
+
+    Ok(result)
+}
+
+fn read_signing_key(path: &Path) -> io::Result<SigningKey> {
+    let key = match path.to_str().and_then(|s| s.strip_prefix('@')) {
+        Some(name) => std::env::var(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Environment variable {} with the signing key is not set",
+                    name
+                ),
+            )
+        })?,
+        None => std::fs::read_to_string(path)?,
+    };
+    attest::parse_signing_key(&key)
+}
+
+fn verify_attest(
+    verifying_key: PathBuf,
+    proof: PathBuf,
+    inputs: PathBuf,
+    signing_key: PathBuf,
+    out: PathBuf,
+) -> io::Result<bool> {
+    let signing_key = read_signing_key(&signing_key)?;
+    let inputs = load_inputs::<Bn254, _>(open_file(&inputs, "inputs")?)?;
+
+    let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+        open_file_or_url(&verifying_key, "verifying key")?,
+        "verifying key",
+        Some(verifying_key_size::<Bn254>(inputs.len())),
+        artifact_options(),
+    )?;
+    let proof: Proof<Bn254> = deserialize_from(
+        open_file_or_url(&proof, "proof")?,
+        "proof",
+        Some(proof_size::<Bn254>()),
+        artifact_options(),
+    )?;
+
+    let verified = verify(&verifying_key, &proof, &inputs)?;
+    info!("Proof verification result: {}", verified);
+
+    let claim = attest::Claim::new(
+        verified,
+        artifact_sha256(&verifying_key, "verifying key")?,
+        artifact_sha256(&proof, "proof")?,
+        hex::encode(eth::inputs_commitment(&inputs)),
+    );
+    let attestation = attest::Attestation::sign(&claim, &signing_key)?;
+
+    info!(
+        "Writing attestation signed by {} to file {:}",
+        attestation.signer,
+        out.display()
+    );
+    let mut writer = BufWriter::new(File::create(out)?);
+    attestation.write(&mut writer)?;
+    writer.flush()?;
+
+    Ok(verified)
+}
+
+fn verify_on_chain(
+    rpc_url: &str,
+    address: &str,
+    proof: PathBuf,
+    inputs: PathBuf,
+    verifying_key: Option<PathBuf>,
+) -> io::Result<bool> {
+    let address: Address = address.parse().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid contract address {}: {}", address, e),
+        )
+    })?;
+    let inputs = load_inputs::<Bn254, _>(open_file(&inputs, "inputs")?)?;
+    let proof: Proof<Bn254> = deserialize_from(
+        open_file_or_url(&proof, "proof")?,
+        "proof",
+        Some(proof_size::<Bn254>()),
+        artifact_options(),
+    )?;
+
+    info!("Calling verifyProof on {:?} through {}", address, rpc_url);
+    let calldata = eth::verify_proof_calldata(&proof.clone().into(), &inputs);
+    let accepted = eth::decode_bool(&rpc::eth_call(rpc_url, &address, &calldata)?)?;
+    info!("On-chain verification result: {}", accepted);
+
+    if let Some(verifying_key) = verifying_key {
+        let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+            open_file_or_url(&verifying_key, "verifying key")?,
+            "verifying key",
+            Some(verifying_key_size::<Bn254>(inputs.len())),
+            artifact_options(),
+        )?;
+        let verified = verify(&verifying_key, &proof, &inputs)?;
+        info!("Off-chain verification result: {}", verified);
+        if verified != accepted {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "The verifier at {:?} {} the proof but the verifying key {} it. The contract may have been generated from another key.",
+                    address,
+                    if accepted { "accepts" } else { "rejects" },
+                    if verified { "accepts" } else { "rejects" },
+                ),
+            ));
+        }
+    }
+
+    Ok(accepted)
+}
+
+fn run_r1cs<E: SupportedCurve>(
+    circuit: CircuitSource,
+    inputs: Option<PathBuf>,
+    check_witness: bool,
+    time_limit: Option<Duration>,
+    report_memory: bool,
+) -> io::Result<()> {
+    let (r1cs, witness) = circuit.load::<E>()?;
+    if check_witness {
+        validate::check_satisfied(&r1cs, &witness)?;
+    }
+
+    let inputs: Vec<E::ScalarField> = match inputs {
+        Some(inputs) => {
+            let inputs = parse_inputs_file::<E, _>(open_file(&inputs, "inputs")?)?;
+
+            // Catch a disagreement here rather than as an opaque verification failure
+            inputs.check_against(&witness)?;
+
+            inputs.inputs.into_iter().map(|(_, v)| v).collect()
+        }
+        // The inputs of an assignment object were checked against its witness when it was read
+        None => witness.public_inputs(),
+    };
+
+    let (proving_key, verifying_key) = setup(r1cs.clone())?;
+
+    if report_memory {
+        memory::log_peak_rss("setup");
+    }
+
+    let proof = time_limit::run_with_limit(time_limit, move || prove(&proving_key, r1cs, witness))?;
+
+    if report_memory {
+        memory::log_peak_rss("proving");
+    }
+
+    if verify(&verifying_key, &proof, &inputs)? {
+        Ok(())
+    } else {
+        Err(error::verification_failed("Proof verification failed"))
+    }
+}
+
+// Load the verifying key of a contract with its public input count, checked against `inputs`
+fn load_contract_key<E: SupportedCurve>(
+    verifying_key: &Path,
+    inputs: Option<PathBuf>,
+) -> io::Result<(VerifyingKey<E>, usize)> {
+    let inputs = match inputs {
+        Some(inputs) => Some(load_inputs::<E, _>(open_file(&inputs, "inputs")?)?),
+        None => None,
+    };
+
+    let verifying_key: VerifyingKey<E> = deserialize_from(
+        open_file(verifying_key, "verifying key")?,
+        "verifying key",
+        inputs
+            .as_ref()
+            .map(|inputs| verifying_key_size::<E>(inputs.len())),
+        artifact_options(),
+    )?;
+
+    // The first IC point is the constant term, the rest are one per public input
+    let n_inputs = verifying_key.gamma_abc_g1.len().saturating_sub(1);
+    if let Some(inputs) = &inputs {
+        if inputs.len() != n_inputs {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Inputs file has {} inputs but the verifying key has {}",
+                    inputs.len(),
+                    n_inputs
+                ),
+            ));
+        }
+    }
+    Ok((verifying_key, n_inputs))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn generate_contract(
+    curve: Curve,
+    verifying_key: PathBuf,
+    contract: PathBuf,
+    inputs: Option<PathBuf>,
+    expected_hash: Option<String>,
+    g2_order: G2Order,
+    language: Language,
+    post_process: Option<String>,
+) -> io::Result<()> {
+    let template = match curve {
+        Curve::Bn254 => {
+            let (verifying_key, n_inputs) = load_contract_key::<Bn254>(&verifying_key, inputs)?;
+            let eth_vk = circom_eth::VerifyingKey::from(verifying_key);
+            templates::verifier_groth16::render_contract(&eth_vk, n_inputs, g2_order, language)
+                .unwrap()
+        }
+        // EIP-2537 fixes the G2 coordinate order, so --g2-order does not apply
+        Curve::Bls12_381 if language == Language::Solidity => {
+            let (verifying_key, n_inputs) =
+                load_contract_key::<Bls12_381>(&verifying_key, inputs)?;
+            templates::verifier_groth16::render_eip2537_contract(&verifying_key, n_inputs)
+                .unwrap()
+        }
+        Curve::Bls12_381 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--curve bls12-381 contracts are only generated in solidity, for the EIP-2537 precompiles",
+            ))
+        }
+        curve => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "This command only supports --curve bn254 or bls12-381, not {}",
+                    curve.name()
+                ),
+            ))
+        }
+    };
+
+    let hash = templates::verifier_groth16::contract_hash(&template);
+    info!("Contract SHA-256: {}", hash);
+
+    if let Some(expected) = expected_hash {
+        let expected = expected.trim_start_matches("0x").to_lowercase();
+        if expected != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Contract hash mismatch: expected {}, rendered contract hashes to {}",
+                    expected, hash
+                ),
+            ));
+        }
+    }
+
+    let contents = match post_process {
+        Some(command) => {
+            info!("Post-processing contract with {:?}", command);
+            post_process::filter(&command, template.as_bytes())?
+        }
+        None => template.into_bytes(),
+    };
+
+    info!("Writing smart contract as {:}", contract.display());
+
+    let mut file = File::create(contract)?;
+    file.write_all(&contents)?;
+
+    Ok(())
+}
+
+fn generate_multi_contract(
+    verifying_keys: Vec<PathBuf>,
+    contract: PathBuf,
+    g2_order: G2Order,
+) -> io::Result<()> {
+    let mut eth_vks = Vec::new();
+    for path in &verifying_keys {
+        let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+            open_file(path, "verifying key")?,
+            "verifying key",
+            None,
+            artifact_options(),
+        )?;
+        if verifying_key.gamma_abc_g1.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Verifying key {} has no IC points", path.display()),
+            ));
+        }
+        info!(
+            "Circuit {}: {} with {} public inputs",
+            eth_vks.len(),
+            path.display(),
+            verifying_key.gamma_abc_g1.len() - 1
+        );
+        eth_vks.push(circom_eth::VerifyingKey::from(verifying_key));
+    }
+
+    let template = templates::verifier_groth16::render_multi_contract(&eth_vks, g2_order).unwrap();
+    info!(
+        "Contract SHA-256: {}",
+        templates::verifier_groth16::contract_hash(&template)
+    );
+
+    info!("Writing smart contract as {:}", contract.display());
+
+    let mut file = File::create(contract)?;
+    file.write_all(template.as_bytes())?;
+
+    Ok(())
+}
+
+// Prefix an error with the validation stage it came from
+fn stage<T>(name: &str, result: io::Result<T>) -> io::Result<T> {
+    result.map_err(|e| error::with_context(e, &format!("{} failed", name)))
+}
+
+fn prove_checked(
+    r1cs: PathBuf,
+    witness: PathBuf,
+    proving_key: PathBuf,
+    output: PathBuf,
+) -> io::Result<()> {
+    let (r1cs, witness) = load_checked(&r1cs, &witness)?;
+    info!("R1CS and witness passed all checks");
+
+    let proving_key: ProvingKey<Bn254> = stage(
+        "proving key loading",
+        deserialize_from(
+            open_file(&proving_key, "proving key")?,
+            "proving key",
+            Some(min_proving_key_size(&r1cs)),
+            artifact_options(),
+        ),
+    )?;
+
+    let proof = stage("proving", prove(&proving_key, r1cs, witness))?;
+
+    info!("Serializing proof to file {:}", output.display());
+
+    serialize_to(&proof, File::create(output)?, "proof", artifact_options())
+}
+
+fn check_reduced(
+    witness: Option<PathBuf>,
+    witness_format: Option<WitnessFormat>,
+    inputs: Option<PathBuf>,
+) -> io::Result<()> {
+    let mut unreduced = Vec::new();
+
+    if let Some(witness) = witness {
+        let reader = open_file(&witness, "witness")?;
+        let found = match WitnessFormat::resolve(witness_format, &witness) {
+            WitnessFormat::Json => validate::find_unreduced::<Bn254, _>(reader)?,
+            WitnessFormat::Bin | WitnessFormat::Montgomery => {
+                validate::find_unreduced_binary::<Bn254, _>(reader)?
+            }
+        };
+        unreduced.extend(found.into_iter().map(|found| ("witness", found)));
+    }
+    if let Some(inputs) = inputs {
+        let found = validate::find_unreduced::<Bn254, _>(open_file(&inputs, "inputs")?)?;
+        unreduced.extend(found.into_iter().map(|found| ("inputs", found)));
+    }
+
+    for (file, (var, value)) in &unreduced {
+        warn!(
+            "Variable {} in the {} file is {}, which is not reduced into the field",
+            var, file, value
+        );
+    }
+
+    if unreduced.is_empty() {
+        info!("Every value is reduced into the field");
+        return Ok(());
+    }
+    strict::warn(
+        is_strict(),
+        &format!("{} values are not reduced into the field", unreduced.len()),
+    )
+}
+
+/// Load an R1CS and JSON witness, running every check on them and naming the one that fails.
+fn load_checked(r1cs: &Path, witness: &Path) -> io::Result<(R1CS<Bn254>, Witness<Bn254>)> {
+    let r1cs_file = stage(
+        "R1CS parsing",
+        parse_r1cs::<Bn254, _>(open_file(r1cs, "R1CS")?),
+    )?;
+    stage(
+        "R1CS header check",
+        validate::check_header::<Bn254>(&r1cs_file.header),
+    )?;
+    stage("index-range check", validate::check_indices(&r1cs_file))?;
+
+    let witness_file = stage(
+        "witness parsing",
+        parse_witness_file::<Bn254, _>(open_file(witness, "witness")?),
+    )?;
+    stage(
+        "witness header check",
+        validate::check_header::<Bn254>(&witness_file.header),
+    )?;
+    stage(
+        "constant index check",
+        validate::check_constant_index(
+            r1cs_file.header.constant_index(),
+            witness_file.header.constant_index(),
+        ),
+    )?;
+
+    let mut r1cs: R1CS<Bn254> = r1cs_file.into();
+    convention::current().normalize(&mut r1cs);
+    let witness: Witness<Bn254> = witness_file.into();
+
+    stage(
+        "witness completeness check",
+        validate::check_witness_complete(&r1cs, &witness),
+    )?;
+    stage(
+        "extra witness variables check",
+        warn_if_extra_witness_variables(&r1cs.input_variables, &r1cs.witness_variables, &witness),
+    )?;
+    stage(
+        "satisfiability check",
+        validate::check_satisfied(&r1cs, &witness),
+    )?;
+
+    Ok((r1cs, witness))
+}
+
+fn self_check_resources(dir: &Path) -> io::Result<()> {
+    let fixtures = fixtures::discover(dir)?;
+    if fixtures.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "No R1CS, witness and inputs triples found in {}",
+                dir.display()
+            ),
+        ));
+    }
+
+    let outcomes: Vec<_> = fixtures
+        .iter()
+        .map(|fixture| {
+            info!("Checking fixture {}", fixture.name);
+            fixtures::Outcome {
+                name: fixture.name.clone(),
+                failure: check_fixture(fixture).err(),
+            }
+        })
+        .collect();
+    println!("{}", fixtures::Matrix(&outcomes));
+
+    let n_fixtures = outcomes.len();
+    let mut failures: Vec<_> = outcomes
+        .into_iter()
+        .filter_map(|outcome| Some((outcome.name, outcome.failure?)))
+        .collect();
+    for (name, (stage, err)) in &failures {
+        warn!("Fixture {} failed to {}: {}", name, stage.name(), err);
+    }
+
+    if failures.is_empty() {
+        return Ok(());
+    }
+    let n_failed = failures.len();
+    let (name, (_, first)) = failures.remove(0);
+    Err(error::with_context(
+        first,
+        &format!(
+            "{} of {} fixtures failed, first {}",
+            n_failed, n_fixtures, name
+        ),
+    ))
+}
+
+// The full cycle for one fixture, with the keys seeded like the test vectors
+fn check_fixture(fixture: &fixtures::Fixture) -> Result<(), (fixtures::Stage, io::Error)> {
+    use fixtures::Stage;
+    let at = |stage| move |err| (stage, err);
+
+    let (r1cs, witness) =
+        load_checked(&fixture.r1cs, &fixture.witness).map_err(at(Stage::Validate))?;
+    let inputs = open_file(&fixture.inputs, "inputs")
+        .and_then(load_inputs::<Bn254, _>)
+        .map_err(at(Stage::Validate))?;
+
+    let mut rng = StdRng::seed_from_u64(TEST_VECTOR_SEED);
+    let (pk, vk) = setup_with(r1cs.clone(), &mut rng).map_err(at(Stage::Prove))?;
+    let proof = prove_with(&pk, r1cs, witness, true, &mut rng).map_err(at(Stage::Prove))?;
+
+    match verify(&vk, &proof, &inputs) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err((
+            Stage::Verify,
+            error::verification_failed("Proof verification failed"),
+        )),
+        Err(err) => Err((Stage::Verify, err)),
+    }
+}
+
+// Test vectors must be reproducible, so setup and proving share one fixed seed
+const TEST_VECTOR_SEED: u64 = 0;
+
+fn spot_check(circuit: CircuitSource, samples: usize, seed: Option<u64>) -> io::Result<()> {
+    let (r1cs, witness) = circuit.load::<Bn254>()?;
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let n_constraints = r1cs.constraints.len();
+    let samples = samples.min(n_constraints);
+    let failures = validate::spot_check(&r1cs, &witness, samples, &mut rng);
+    for failure in &failures {
+        warn!("{}", failure);
+    }
 
-    info!("Writing smart contract as {:}", contract.display());
+    if failures.is_empty() {
+        info!(
+            "All {} sampled constraints of {} are satisfied",
+            samples, n_constraints
+        );
+        return Ok(());
+    }
+    Err(error::unsatisfied(format!(
+        "{} of {} sampled constraints are not satisfied",
+        failures.len(),
+        samples
+    )))
+}
 
-    let mut file = File::create(contract)?;
-    file.write_all(template.as_bytes())?;
+fn derive_vk(proving_key: PathBuf, out: PathBuf) -> io::Result<()> {
+    let proving_key: ProvingKey<Bn254> = deserialize_from(
+        open_file_or_url(&proving_key, "proving key")?,
+        "proving key",
+        None,
+        artifact_options(),
+    )?;
+
+    info!("Serializing verification key to file {:}", out.display());
+
+    serialize_to(
+        &proving_key.vk,
+        File::create(out)?,
+        "verifying key",
+        artifact_options(),
+    )
+}
+
+// Inputs are numbered from 1, after the constant. Only their order matters to verification.
+fn gen_random_inputs(verifying_key: PathBuf, out: PathBuf, seed: Option<u64>) -> io::Result<()> {
+    let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+        open_file_or_url(&verifying_key, "verifying key")?,
+        "verifying key",
+        None,
+        artifact_options(),
+    )?;
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let n_inputs = verifying_key.gamma_abc_g1.len().saturating_sub(1);
+    let inputs: Inputs<Bn254> = Inputs {
+        inputs: (1..=n_inputs).map(|i| (i, Fr::rand(&mut rng))).collect(),
+    };
+
+    info!(
+        "Writing {} random inputs to file {:}",
+        n_inputs,
+        out.display()
+    );
+
+    write_inputs_file(&inputs, BufWriter::new(File::create(out)?))
+}
+
+/// Whether the keys belong to the same setup. With a circuit, a proof of its witness made with the
+/// proving key must also verify with the verifying key.
+fn check_setup(
+    proving_key: PathBuf,
+    verifying_key: PathBuf,
+    circuit: Option<CircuitSource>,
+) -> io::Result<bool> {
+    let circuit = circuit.map(|circuit| circuit.load::<Bn254>()).transpose()?;
+
+    let proving_key: ProvingKey<Bn254> = deserialize_from(
+        open_file_or_url(&proving_key, "proving key")?,
+        "proving key",
+        circuit.as_ref().map(|(r1cs, _)| min_proving_key_size(r1cs)),
+        artifact_options(),
+    )?;
+    let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+        open_file_or_url(&verifying_key, "verifying key")?,
+        "verifying key",
+        None,
+        artifact_options(),
+    )?;
+
+    if proving_key.vk != verifying_key {
+        warn!("The verifying key differs from the one embedded in the proving key");
+        return Ok(false);
+    }
+    info!("The verifying key matches the one embedded in the proving key");
+
+    match circuit {
+        Some((r1cs, witness)) => {
+            let inputs = witness.public_inputs();
+            let proof = prove(&proving_key, r1cs, witness)?;
+            let result = verify(&verifying_key, &proof, &inputs)?;
+            info!("Roundtrip proof verification result: {}", result);
+            Ok(result)
+        }
+        None => Ok(true),
+    }
+}
+
+fn show_vk(verifying_key: PathBuf, g2_order: G2Order) -> io::Result<()> {
+    let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+        open_file_or_url(&verifying_key, "verifying key")?,
+        "verifying key",
+        None,
+        artifact_options(),
+    )?;
+
+    let eth_vk = g2_order.apply_to_vk(&verifying_key.into());
+    inspect::write_vk(&eth_vk, io::stdout().lock())
+}
+
+fn transcript_hash(verifying_key: PathBuf, proof: PathBuf, inputs: PathBuf) -> io::Result<()> {
+    let inputs = load_inputs::<Bn254, _>(open_file(&inputs, "inputs")?)?;
+    let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+        open_file_or_url(&verifying_key, "verifying key")?,
+        "verifying key",
+        Some(verifying_key_size::<Bn254>(inputs.len())),
+        artifact_options(),
+    )?;
+    if verifying_key.gamma_abc_g1.len() != inputs.len() + 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Verifying key expects {} public inputs but the inputs file has {}",
+                verifying_key.gamma_abc_g1.len().saturating_sub(1),
+                inputs.len()
+            ),
+        ));
+    }
+    let proof: Proof<Bn254> = deserialize_from(
+        open_file_or_url(&proof, "proof")?,
+        "proof",
+        Some(proof_size::<Bn254>()),
+        artifact_options(),
+    )?;
+
+    let hash = eth::transcript_hash(&verifying_key.into(), &proof.into(), &inputs);
+    println!("0x{}", hex::encode(hash));
+    Ok(())
+}
+
+fn inspect_proof(proof: PathBuf) -> io::Result<()> {
+    let proof: Proof<Bn254> = deserialize_unchecked_from(
+        open_file_or_url(&proof, "proof")?,
+        "proof",
+        Some(proof_size::<Bn254>()),
+        artifact_options(),
+    )?;
+
+    let checks = inspect::check_proof(&proof);
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let invalid: Vec<_> = checks
+        .iter()
+        .filter(|check| !check.is_valid())
+        .map(|check| check.name)
+        .collect();
+    if !invalid.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Proof has invalid points: {}", invalid.join(", ")),
+        ));
+    }
+    info!("Proof is structurally valid");
+    Ok(())
+}
+
+// True if the keys serialize to the same bytes, whatever layout their files use
+fn diff_vk(a: PathBuf, b: PathBuf, g2_order: G2Order) -> io::Result<bool> {
+    let load = |path: &Path| -> io::Result<VerifyingKey<Bn254>> {
+        deserialize_from(
+            open_file_or_url(path, "verifying key")?,
+            "verifying key",
+            None,
+            artifact_options(),
+        )
+    };
+    let (a, b) = (load(&a)?, load(&b)?);
+
+    if to_bytes(&a, "verifying key")? == to_bytes(&b, "verifying key")? {
+        info!("The verifying keys are identical");
+        return Ok(true);
+    }
+
+    // Keys that serialize differently differ in some point
+    let difference = inspect::first_difference(&a, &b).expect("keys differ");
+    info!("The verifying keys first differ at {}", difference);
+
+    let mut out = io::stdout().lock();
+    for (name, vk) in [("a", a), ("b", b)] {
+        writeln!(out, "{}:", name)?;
+        inspect::write_component(&g2_order.apply_to_vk(&vk.into()), &difference, &mut out)?;
+    }
+    Ok(false)
+}
+
+fn inputs_to_words(inputs: PathBuf, out: Option<PathBuf>) -> io::Result<()> {
+    let inputs = merge_inputs(vec![parse_inputs_file::<Bn254, _>(open_file(
+        &inputs, "inputs",
+    )?)?])?;
+    let values: Vec<Fr> = inputs.inputs.into_iter().map(|(_, v)| v).collect();
+
+    let mut lines = eth::inputs_to_words(&values).join("\n");
+    lines.push('\n');
+    match out {
+        Some(out) => {
+            info!(
+                "Writing {} input words to file {:}",
+                values.len(),
+                out.display()
+            );
+            std::fs::write(out, lines)
+        }
+        None => {
+            print!("{}", lines);
+            Ok(())
+        }
+    }
+}
+
+fn split_witness(witness: PathBuf, shards: usize, out_dir: PathBuf) -> io::Result<()> {
+    let file = parse_witness_file::<Bn254, _>(open_file(&witness, "witness")?)?;
+    let split = witness::split_witness_file(&file, shards)?;
+
+    let stem = witness
+        .file_stem()
+        .map_or("witness".into(), |stem| stem.to_string_lossy());
+    std::fs::create_dir_all(&out_dir)?;
+    let paths: Vec<PathBuf> = (0..shards)
+        .map(|i| out_dir.join(format!("{}-shard-{}.jsonl", stem, i)))
+        .collect();
+    for (shard, path) in split.iter().zip(&paths) {
+        info!(
+            "Writing {} witness values to file {:}",
+            shard.witness.len(),
+            path.display()
+        );
+        witness::write_witness_file(shard, BufWriter::new(File::create(path)?))?;
+    }
+
+    // The shards read back in order must be exactly the original entries
+    let mut expected = file.witness;
+    expected.sort_by_key(|(index, _)| *index);
+    let mut read_back = Vec::with_capacity(expected.len());
+    for path in &paths {
+        read_back
+            .extend(parse_witness_file::<Bn254, _>(open_file(path, "witness shard")?)?.witness);
+    }
+    if read_back != expected {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Witness shards read back do not reproduce the original witness",
+        ));
+    }
+    Ok(())
+}
+
+fn merge_inputs_files(inputs: Vec<PathBuf>, out: PathBuf) -> io::Result<()> {
+    let files = inputs
+        .iter()
+        .map(|path| parse_inputs_file(open_file(path, "inputs")?))
+        .collect::<io::Result<Vec<Inputs<Bn254>>>>()?;
+
+    let merged = merge_inputs(files)?;
+
+    info!(
+        "Writing {} merged inputs to file {:}",
+        merged.inputs.len(),
+        out.display()
+    );
+
+    write_inputs_file(&merged, BufWriter::new(File::create(out)?))
+}
+
+fn export_test_vectors(
+    r1cs: PathBuf,
+    witness: PathBuf,
+    inputs: PathBuf,
+    out: PathBuf,
+) -> io::Result<()> {
+    let r1cs = load_r1cs(open_file(&r1cs, "R1CS")?)?;
+
+    let witness = load_witness(open_file(&witness, "witness")?)?;
+
+    let inputs = parse_inputs_file(open_file(&inputs, "inputs")?)?;
+
+    inputs.check_against(&witness)?;
+
+    let inputs: Vec<Fr> = inputs.inputs.into_iter().map(|(_, v)| v).collect();
+
+    let mut rng = StdRng::seed_from_u64(TEST_VECTOR_SEED);
+
+    let (proving_key, verifying_key) = setup_with(r1cs.clone(), &mut rng)?;
+
+    let proof = prove_with(&proving_key, r1cs, witness, true, &mut rng)?;
+
+    let expected = verify(&verifying_key, &proof, &inputs)?;
+
+    let test_vector = eth::TestVector {
+        verifying_key: G2Order::Eth.apply_to_vk(&verifying_key.into()),
+        proof: G2Order::Eth.apply_to_proof(&proof.into()),
+        inputs: inputs.iter().map(eth::fr_to_u256).collect(),
+        expected,
+    };
+
+    info!("Writing test vectors to file {:}", out.display());
+
+    serde_json::to_writer_pretty(File::create(out)?, &test_vector)?;
+
+    Ok(())
+}
+
+fn check_unconstrained_inputs(r1cs: PathBuf) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    let unconstrained = validate::unconstrained_inputs(&r1cs);
+    for v in &unconstrained {
+        println!("{}", v);
+    }
+    for v in &unconstrained {
+        strict::warn(is_strict(), &format!(
+            "Public input {} is not used by any constraint, so proofs can be made for any value of it",
+            v
+        ))?;
+    }
+    info!(
+        "{} of {} public inputs are unconstrained",
+        unconstrained.len(),
+        r1cs.input_variables.len()
+    );
+
+    Ok(())
+}
+
+fn print_density(r1cs: PathBuf) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    println!("{}", density::Density::of(&r1cs.constraints));
+
+    Ok(())
+}
+
+fn print_redundancy(r1cs: PathBuf, samples: Option<usize>, seed: Option<u64>) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    let redundancy = match samples {
+        Some(samples) => {
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            rank::Redundancy::sampled(&r1cs.constraints, samples, &mut rng)
+        }
+        None => rank::Redundancy::exact(&r1cs.constraints),
+    };
+
+    println!("{}", redundancy);
+
+    Ok(())
+}
+
+fn print_profile(r1cs: PathBuf, top: usize) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    let costs = profile::most_expensive(&r1cs.constraints, top);
+
+    println!("{}", profile::CostTable(&costs));
+
+    Ok(())
+}
+
+fn write_graph(r1cs: PathBuf, out: PathBuf) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    info!("Writing DOT graph to file {:}", out.display());
+
+    graph::write_dot(&r1cs, BufWriter::new(File::create(out)?))
+}
+
+fn export_matrices(r1cs: PathBuf, out: PathBuf) -> io::Result<()> {
+    let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    let matrices = matrices::synthesize_matrices(r1cs)?;
+
+    info!("Writing constraint matrices to file {:}", out.display());
+
+    serde_json::to_writer(BufWriter::new(File::create(out)?), &matrices)?;
+
+    Ok(())
+}
+
+fn import_matrices(matrices: PathBuf, out: PathBuf) -> io::Result<()> {
+    let matrices: matrices::Matrices =
+        serde_json::from_reader(open_file(&matrices, "constraint matrices")?)?;
+
+    let r1cs_file = matrices.to_r1cs_file::<Bn254>()?;
+
+    info!(
+        "Writing R1CS with {} constraints to file {:}",
+        r1cs_file.constraints.len(),
+        out.display()
+    );
+
+    write_r1cs_file(&r1cs_file, BufWriter::new(File::create(out)?))
+}
+
+fn cache_matrices(r1cs: PathBuf, out: PathBuf) -> io::Result<()> {
+    let r1cs_bytes = std::fs::read(&r1cs)?;
+    let r1cs_sha256 = matrix_cache::r1cs_sha256(r1cs_bytes.as_slice())?;
+    let r1cs = load_r1cs::<Bn254, _>(r1cs_bytes.as_slice())?;
+
+    let cache = MatrixCache::build(r1cs, r1cs_sha256)?;
+
+    info!("Writing matrix cache to file {:}", out.display());
+
+    serialize_to(
+        &cache,
+        BufWriter::new(File::create(out)?),
+        "matrix cache",
+        artifact_options(),
+    )
+}
+
+fn benchmark_parse(r1cs: PathBuf, matrix_cache: Option<PathBuf>, runs: usize) -> io::Result<()> {
+    let r1cs_bytes = std::fs::read(&r1cs)?;
+    let r1cs_sha256 = matrix_cache::r1cs_sha256(r1cs_bytes.as_slice())?;
+
+    let cache_bytes = match matrix_cache {
+        Some(path) => std::fs::read(path)?,
+        None => to_bytes(
+            &MatrixCache::build(
+                load_r1cs::<Bn254, _>(r1cs_bytes.as_slice())?,
+                r1cs_sha256.clone(),
+            )?,
+            "matrix cache",
+        )?,
+    };
+    let cache: MatrixCache<Bn254> = deserialize_from(
+        cache_bytes.as_slice(),
+        "matrix cache",
+        None,
+        artifact_options(),
+    )?;
+    cache.check_r1cs(&r1cs_sha256)?;
+
+    info!("Timing the best of {} runs", runs);
+    let timings = benchmark::ParseTimings {
+        cold: benchmark::best_of(runs, || {
+            MatrixCache::<Bn254>::build(load_r1cs(r1cs_bytes.as_slice())?, Vec::new())
+        })?,
+        cached: benchmark::best_of(runs, || {
+            deserialize_from::<MatrixCache<Bn254>, _>(
+                cache_bytes.as_slice(),
+                "matrix cache",
+                None,
+                artifact_options(),
+            )
+        })?,
+    };
+    println!("{}", timings);
+
+    Ok(())
+}
+
+fn canonicalize_r1cs(r1cs: PathBuf, out: PathBuf, dedup: bool) -> io::Result<()> {
+    let r1cs_file = parse_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    let mut canonical = canonical::canonicalize(r1cs_file);
+
+    if dedup {
+        let removed = canonical::dedup(&mut canonical);
+        info!("Removed {} duplicate constraints", removed);
+    }
+
+    info!("Writing canonical R1CS to file {:}", out.display());
+
+    write_r1cs_file(&canonical, BufWriter::new(File::create(out)?))
+}
+
+fn fingerprint_r1cs(r1cs: PathBuf) -> io::Result<()> {
+    let r1cs_file = parse_r1cs::<Bn254, _>(open_file(&r1cs, "R1CS")?)?;
+
+    println!("{}", hex::encode(canonical::fingerprint(r1cs_file)?));
 
     Ok(())
 }
 
-fn main() -> io::Result<()> {
+fn main() {
     // Clap to handle command line arguments
+    let matches = Cli::clap().get_matches();
+    if matches.subcommand_name().is_none() && !matches.is_present("self-check-resources") {
+        clap::Error::with_description(
+            "A subcommand is required",
+            clap::ErrorKind::MissingSubcommand,
+        )
+        .exit();
+    }
+    let args = Cli::from_clap(&matches);
+    let error_json = args.error_json;
 
-    let args = Cli::from_args();
+    if let Err(err) = run(args) {
+        let code = error::classify(&err).exit_code();
+        if error_json {
+            eprintln!("{}", error::to_json(&err, matches.subcommand_name()));
+        } else {
+            eprintln!("Error: {:?}", err);
+        }
+        process::exit(code);
+    }
+}
 
+fn run(args: Cli) -> io::Result<()> {
     Builder::new()
         .filter(None, args.log_level)
+        .parse_write_style(&args.color)
         .format(|buf, record| {
-            // Use `buf`'s write_str or writeln_str methods
-            writeln!(buf, "{}: {}", record.level(), record.args())
+            // The styled level is only colored when the write style allows it
+            writeln!(
+                buf,
+                "{}: {}",
+                buf.default_styled_level(record.level()),
+                record.args()
+            )
         })
         .init();
 
-    match args.command {
+    STRICT.store(args.strict, Ordering::Relaxed);
+    TAG_ARTIFACTS.store(args.tag_artifacts, Ordering::Relaxed);
+    progress::set_bar(args.progress);
+    convention::set(args.constraint_convention);
+
+    if let Some(threads) = args.threads {
+        if threads == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "--threads must be at least 1",
+            ));
+        }
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Could not configure the thread pool: {}", e),
+                )
+            })?;
+    }
+
+    let command = match (args.self_check_resources, args.command) {
+        (Some(dir), _) => return self_check_resources(&dir),
+        (None, Some(command)) => command,
+        (None, None) => unreachable!("main checks a subcommand is given"),
+    };
+    if args.curve != Curve::Bn254 && !command.supports_any_curve() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "This command only supports --curve bn254, not {}",
+                args.curve.name()
+            ),
+        ));
+    }
+
+    match command {
         Command::CreateTrustedSetup {
             r1cs,
+            matrix_cache,
             proving_key,
             verifying_key,
             ethereum,
+            g2_order,
+            json_naming,
+            gnark,
+            entropy,
+            verify_determinism,
+            skip_unchanged,
+            bundle,
+            disclose,
+            summary,
+            fixed_inputs,
         } => {
-            create_trusted_setup(r1cs, proving_key, verifying_key, ethereum)?;
+            with_curve!(args.curve, E => create_trusted_setup::<E>(
+                r1cs,
+                matrix_cache,
+                proving_key,
+                verifying_key,
+                ethereum,
+                g2_order,
+                json_naming,
+                gnark,
+                entropy,
+                verify_determinism,
+                skip_unchanged,
+                bundle,
+                disclose,
+                summary,
+                fixed_inputs
+            ))?;
         }
         Command::GenerateContract {
             verifying_key,
             contract,
             inputs,
+            expected_hash,
+            g2_order,
+            language,
+            post_process,
+        } => {
+            generate_contract(
+                args.curve,
+                verifying_key,
+                contract,
+                inputs,
+                expected_hash,
+                g2_order,
+                language,
+                post_process,
+            )?;
+        }
+        Command::GenerateMultiContract {
+            verifying_key,
+            contract,
+            g2_order,
         } => {
-            generate_contract(verifying_key, contract, inputs)?;
+            generate_multi_contract(verifying_key, contract, g2_order)?;
         }
         Command::CreateProof {
             proving_key,
+            bundle,
+            verifying_key,
+            self_verify,
             witness,
+            witness_format,
             r1cs,
+            combined,
+            assignment,
+            matrix_cache,
             proof,
+            append,
+            gnark,
             ethereum,
+            g2_order,
+            json_naming,
+            commit_inputs,
+            eip712,
+            eip712_name,
+            eip712_version,
+            chain_id,
+            verifying_contract,
+            seed,
+            no_zk,
+            time_limit,
+            check_witness,
         } => {
-            create_proof(proving_key, witness, r1cs, proof, ethereum)?;
+            let eip712 = eip712.then(|| EIP712Domain {
+                name: Some(eip712_name),
+                version: Some(eip712_version),
+                chain_id: chain_id.map(U256::from),
+                verifying_contract,
+                salt: None,
+            });
+            let self_verify = match (self_verify, &verifying_key, &bundle) {
+                (false, _, _) => None,
+                (true, None, None) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "--self-verify needs --verifying-key or --bundle",
+                    ))
+                }
+                (true, _, _) => Some(Artifact::from_args(verifying_key, bundle.clone())),
+            };
+            let proving_key = Artifact::from_args(proving_key, bundle);
+            let circuit =
+                CircuitSource::from_args(r1cs, witness, witness_format, combined, assignment);
+            let time_limit = time_limit.map(Duration::from_secs);
+            with_curve!(args.curve, E => create_proof::<E>(
+                proving_key,
+                self_verify,
+                circuit,
+                matrix_cache,
+                proof,
+                append,
+                gnark,
+                ethereum,
+                g2_order,
+                json_naming,
+                commit_inputs,
+                eip712,
+                seed,
+                !no_zk,
+                time_limit,
+                check_witness,
+                args.report_memory
+            ))?;
         }
         Command::VerifyProof {
             verifying_key,
+            bundle,
             proof,
             inputs,
+            inputs_words,
+            inputs_snarkjs,
             ethereum,
+            g2_order,
+            records,
+            results,
+            continue_on_error,
+            commitment,
+            stream_vk,
+        } => {
+            let verifying_key = Artifact::from_args(verifying_key, bundle);
+            let inputs = InputsSource::from_args(inputs, inputs_words, inputs_snarkjs);
+            let verified = with_curve!(args.curve, E => verify_proof::<E>(
+                verifying_key,
+                proof,
+                inputs,
+                ethereum,
+                g2_order,
+                records,
+                results,
+                commitment,
+                stream_vk,
+                continue_on_error
+            ))?;
+            if args.report_memory {
+                memory::log_peak_rss("verification");
+            }
+            if !verified {
+                return Err(error::verification_failed("Proof verification failed"));
+            }
+        }
+        Command::VerifyAttest {
+            verifying_key,
+            proof,
+            inputs,
+            signing_key,
+            out,
+        } => {
+            if !verify_attest(verifying_key, proof, inputs, signing_key, out)? {
+                return Err(error::verification_failed("Proof verification failed"));
+            }
+        }
+        Command::VerifyOnChain {
+            rpc_url,
+            address,
+            proof,
+            inputs,
+            verifying_key,
+        } => {
+            if !verify_on_chain(&rpc_url, &address, proof, inputs, verifying_key)? {
+                return Err(error::verification_failed(
+                    "On-chain verifier rejected the proof",
+                ));
+            }
+        }
+        Command::RunR1CS {
+            r1cs,
+            witness,
+            witness_format,
+            combined,
+            assignment,
+            inputs,
+            check_witness,
+            time_limit,
+        } => {
+            let circuit = CircuitSource::from_args(
+                r1cs,
+                witness.into_iter().collect(),
+                witness_format,
+                combined,
+                assignment,
+            );
+            let time_limit = time_limit.map(Duration::from_secs);
+            with_curve!(args.curve, E => run_r1cs::<E>(
+                circuit,
+                inputs,
+                check_witness,
+                time_limit,
+                args.report_memory
+            ))?;
+        }
+        Command::ProveChecked {
+            r1cs,
+            witness,
+            proving_key,
+            proof,
+        } => {
+            prove_checked(r1cs, witness, proving_key, proof)?;
+        }
+        Command::CheckReduced {
+            witness,
+            witness_format,
+            inputs,
+        } => {
+            check_reduced(witness, witness_format, inputs)?;
+        }
+        Command::SpotCheck {
+            r1cs,
+            witness,
+            witness_format,
+            samples,
+            seed,
+        } => {
+            let circuit =
+                CircuitSource::from_args(Some(r1cs), vec![witness], witness_format, None, None);
+            spot_check(circuit, samples, seed)?;
+        }
+        Command::DeriveVk { proving_key, out } => {
+            derive_vk(proving_key, out)?;
+        }
+        Command::GenRandomInputs {
+            verifying_key,
+            out,
+            seed,
+        } => {
+            gen_random_inputs(verifying_key, out, seed)?;
+        }
+        Command::CheckSetup {
+            proving_key,
+            verifying_key,
+            roundtrip,
+            r1cs,
+            witness,
+        } => {
+            let circuit = roundtrip.then(|| {
+                CircuitSource::from_args(r1cs, witness.into_iter().collect(), None, None, None)
+            });
+            if !check_setup(proving_key, verifying_key, circuit)? {
+                return Err(error::verification_failed(
+                    "Proving key and verifying key are not from the same setup",
+                ));
+            }
+        }
+        Command::TranscriptHash {
+            verifying_key,
+            proof,
+            inputs,
+        } => {
+            transcript_hash(verifying_key, proof, inputs)?;
+        }
+        Command::ShowVk {
+            verifying_key,
+            g2_order,
+        } => {
+            show_vk(verifying_key, g2_order)?;
+        }
+        Command::InspectProof { proof } => {
+            inspect_proof(proof)?;
+        }
+        Command::InputsToWords { inputs, out } => {
+            inputs_to_words(inputs, out)?;
+        }
+        Command::DiffVk { a, b, g2_order } => {
+            if !diff_vk(a, b, g2_order)? {
+                return Err(error::verification_failed("Verifying keys differ"));
+            }
+        }
+        Command::MergeInputs { inputs, out } => {
+            merge_inputs_files(inputs, out)?;
+        }
+        Command::SplitWitness {
+            witness,
+            shards,
+            out_dir,
+        } => {
+            split_witness(witness, shards, out_dir)?;
+        }
+        Command::ExportTestVectors {
+            r1cs,
+            witness,
+            inputs,
+            out,
         } => {
-            verify_proof(verifying_key, proof, inputs, ethereum)?;
+            export_test_vectors(r1cs, witness, inputs, out)?;
+        }
+        Command::UnconstrainedInputs { r1cs } => {
+            check_unconstrained_inputs(r1cs)?;
+        }
+        Command::Density { r1cs } => {
+            print_density(r1cs)?;
+        }
+        Command::Redundancy {
+            r1cs,
+            samples,
+            seed,
+        } => {
+            print_redundancy(r1cs, samples, seed)?;
+        }
+        Command::Profile { r1cs, top } => {
+            print_profile(r1cs, top)?;
+        }
+        Command::Graph { r1cs, out } => {
+            write_graph(r1cs, out)?;
+        }
+        Command::ExportMatrices { r1cs, out } => {
+            export_matrices(r1cs, out)?;
         }
-        Command::RunR1CS {
+        Command::ImportMatrices { matrices, out } => {
+            import_matrices(matrices, out)?;
+        }
+        Command::CacheMatrices { r1cs, out } => {
+            cache_matrices(r1cs, out)?;
+        }
+        Command::BenchmarkParse {
             r1cs,
-            witness,
-            inputs,
+            matrix_cache,
+            runs,
         } => {
-            run_r1cs(r1cs, witness, inputs)?;
+            benchmark_parse(r1cs, matrix_cache, runs)?;
+        }
+        Command::Canonicalize { r1cs, out, dedup } => {
+            canonicalize_r1cs(r1cs, out, dedup)?;
+        }
+        Command::Fingerprint { r1cs } => {
+            fingerprint_r1cs(r1cs)?;
         }
     }
 
@@ -515,26 +3468,1641 @@ fn main() -> io::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::remove_file;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use std::io::Read;
     use std::path::PathBuf;
 
+    const R1CS_JSONL: &str = include_str!("../test/resources/prog-r1cs.jsonl");
+    const WITNESS_JSONL: &str = include_str!("../test/resources/prog-witness.jsonl");
+    const INPUTS_JSONL: &str = include_str!("../test/resources/prog-inputs.jsonl");
+
+    fn roundtrip<T: CanonicalSerialize + CanonicalDeserialize>(value: &T) -> T {
+        let mut bytes = Vec::new();
+        serialize_to(value, &mut bytes, "value", artifact_options()).unwrap();
+        deserialize_from(bytes.as_slice(), "value", None, artifact_options()).unwrap()
+    }
+
+    #[test]
+    fn test_constant_index_last_variable() {
+        // The fixture circuit with variables 0 and 2 swapped, so the constant one comes last
+        let header = R1CS_JSONL.lines().next().unwrap().replace(
+            r#""output_variables":[2]"#,
+            r#""output_variables":[0],"constant_index":2"#,
+        );
+        let r1cs = format!(
+            "{}\n{}",
+            header,
+            r#"{"A":[["1",2]],"B":[["55",2],["11",1],["21888242871839275222246405745257275088548364400416034343698204186575808495616",0]],"C":[["0",2]]}"#
+        );
+        let witness = format!("{}\n[1,\"1\"]\n[0,\"66\"]", header);
+
+        let relabeled = load_r1cs::<Bn254, _>(r1cs.as_bytes()).unwrap();
+        assert_eq!(relabeled.constant_index, 2);
+        assert_eq!(relabeled.witness_variables, vec![0]);
+        let cache = MatrixCache::build(relabeled.clone(), Vec::new()).unwrap();
+        assert_eq!(cache.constant_index(), 2);
+
+        // Relabeling variables leaves the matrices, and so the keys, unchanged
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let (pk, vk) = setup_with(r1cs, &mut StdRng::seed_from_u64(0)).unwrap();
+        let (relabeled_pk, _) =
+            setup_with(relabeled.clone(), &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(relabeled_pk, pk);
+
+        let witness = load_witness::<Bn254, _>(witness.as_bytes()).unwrap();
+        assert_eq!(witness.constant_index, 2);
+        let proof = prove_with(&pk, relabeled, witness, true, &mut thread_rng()).unwrap();
+        let inputs = load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap();
+        assert!(verify(&vk, &proof, &inputs).unwrap());
+
+        // A witness generated for the default constant does not fit this circuit
+        let default = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        assert!(validate::check_constant_index(2, default.constant_index).is_err());
+    }
+
+    #[test]
+    fn test_end_to_end_in_memory() {
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let inputs = load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap();
+
+        let (pk, vk) = setup(r1cs.clone()).unwrap();
+        let (pk, vk) = (roundtrip(&pk), roundtrip(&vk));
+
+        let proof = roundtrip(&prove(&pk, r1cs, witness).unwrap());
+
+        assert!(verify(&vk, &proof, &inputs).unwrap());
+        assert!(!verify(&vk, &proof, &[Fr::from(2u64)]).unwrap());
+    }
+
+    #[test]
+    fn test_no_zk_seeded_proofs_are_identical() {
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let inputs = load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap();
+
+        let (pk, vk) = setup(r1cs.clone()).unwrap();
+
+        let mut proofs = (0..2).map(|_| {
+            let mut rng = StdRng::seed_from_u64(42);
+            prove_with(&pk, r1cs.clone(), witness.clone(), false, &mut rng).unwrap()
+        });
+        let (first, second) = (proofs.next().unwrap(), proofs.next().unwrap());
+
+        assert_eq!(first, second);
+        assert!(verify(&vk, &first, &inputs).unwrap());
+    }
+
+    #[test]
+    fn test_self_check_resources() {
+        self_check_resources(Path::new("test/resources")).unwrap();
+        assert!(self_check_resources(Path::new("src")).is_err());
+    }
+
+    #[test]
+    fn test_run_r1cs_combined() {
+        let combined = PathBuf::from("test/resources/prog-combined.jsonl");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        run_r1cs::<Bn254>(
+            CircuitSource::Combined(combined),
+            Some(inputs),
+            false,
+            None,
+            false,
+        )
+        .unwrap();
+
+        // A file cut off before its last witness line fails to load, before any proving
+        let dir = tempfile::tempdir().unwrap();
+        let truncated = dir.path().join("combined.jsonl");
+        let contents = std::fs::read_to_string("test/resources/prog-combined.jsonl").unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        std::fs::write(&truncated, lines[..lines.len() - 1].join("\n")).unwrap();
+        let err = CircuitSource::Combined(truncated)
+            .load::<Bn254>()
+            .unwrap_err();
+        assert_eq!(error::classify(&err), error::Category::Parse);
+        assert!(err.to_string().contains("no value for variable 2"));
+    }
+
+    #[test]
+    fn test_run_r1cs_assignment() {
+        let circuit = CircuitSource::from_args(
+            Some(PathBuf::from("test/resources/prog-r1cs.jsonl")),
+            Vec::new(),
+            None,
+            None,
+            Some(PathBuf::from("test/resources/prog-assignment.json")),
+        );
+        run_r1cs::<Bn254>(circuit, None, false, None, false).unwrap();
+    }
+
+    #[test]
+    fn test_run_r1cs_check_witness() {
+        let dir = tempfile::tempdir().unwrap();
+        let witness = dir.path().join("witness.jsonl");
+        let header = WITNESS_JSONL.lines().next().unwrap();
+        std::fs::write(&witness, format!("{}\n[1,\"1\"]\n[2,\"67\"]\n", header)).unwrap();
+        let circuit = CircuitSource::from_args(
+            Some(PathBuf::from("test/resources/prog-r1cs.jsonl")),
+            vec![witness.clone()],
+            None,
+            None,
+            None,
+        );
+
+        let err = run_r1cs::<Bn254>(circuit, None, true, None, false).unwrap_err();
+        assert_eq!(error::classify(&err), error::Category::Unsatisfied);
+        assert!(err.to_string().contains("constraint 0 is not satisfied"));
+    }
+
+    #[test]
+    fn test_run_r1cs_witness_shards() {
+        let shard = |i| PathBuf::from(format!("test/resources/prog-witness-shard-{}.jsonl", i));
+        let circuit = |witness| CircuitSource::Files {
+            r1cs: PathBuf::from("test/resources/prog-r1cs.jsonl"),
+            witness,
+            witness_format: WitnessFormat::Json,
+        };
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        run_r1cs::<Bn254>(
+            circuit(vec![shard(0), shard(1)]),
+            Some(inputs.clone()),
+            false,
+            None,
+            false,
+        )
+        .unwrap();
+
+        let err = circuit(vec![shard(0), shard(0)])
+            .load::<Bn254>()
+            .unwrap_err();
+        assert!(err.to_string().contains("more than one witness shard"));
+
+        // A shard assigning a variable the R1CS does not declare is only warned about, as for a
+        // single witness file
+        let dir = tempfile::tempdir().unwrap();
+        let stale = dir.path().join("stale-shard.jsonl");
+        let shard_1 = std::fs::read_to_string(shard(1)).unwrap();
+        std::fs::write(&stale, format!("{}\n[7,\"1\"]\n", shard_1.trim_end())).unwrap();
+        let (_, witness) = circuit(vec![shard(0), stale]).load::<Bn254>().unwrap();
+        assert_eq!(witness.witness_variables.len(), 2);
+    }
+
+    #[test]
+    fn test_import_matrices_proves_identically() {
+        let dir = tempfile::tempdir().unwrap();
+        let matrices = dir.path().join("matrices.json");
+        let imported = dir.path().join("r1cs.jsonl");
+        export_matrices(
+            PathBuf::from("test/resources/prog-r1cs.jsonl"),
+            matrices.clone(),
+        )
+        .unwrap();
+        import_matrices(matrices, imported.clone()).unwrap();
+
+        let proof_from = |r1cs: R1CS<Bn254>| {
+            let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+            let mut rng = StdRng::seed_from_u64(TEST_VECTOR_SEED);
+            let (proving_key, _) = setup_with(r1cs.clone(), &mut rng).unwrap();
+            prove_with(&proving_key, r1cs, witness, true, &mut rng).unwrap()
+        };
+        let original = proof_from(load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap());
+        let rebuilt =
+            proof_from(load_r1cs::<Bn254, _>(open_file(&imported, "R1CS").unwrap()).unwrap());
+        assert_eq!(rebuilt, original);
+    }
+
+    #[test]
+    fn test_skip_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let entropy = dir.path().join("entropy");
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let bundle = dir.path().join("bundle.zip");
+        let disclosure = dir.path().join("disclosure.json");
+        std::fs::write(&entropy, [7u8; 32]).unwrap();
+
+        let run = || {
+            create_trusted_setup::<Bn254>(
+                r1cs.clone(),
+                None,
+                Some(pk.clone()),
+                Some(vk.clone()),
+                true,
+                G2Order::Eth,
+                JsonNaming::Arkworks,
+                false,
+                Some(entropy.clone()),
+                false,
+                true,
+                Some(bundle.clone()),
+                Some(disclosure.clone()),
+                false,
+                None,
+            )
+            .unwrap()
+        };
+        let modified = |path: &Path| std::fs::metadata(path).unwrap().modified().unwrap();
+        let read_disclosure = || -> disclosure::Disclosure {
+            serde_json::from_reader(File::open(&disclosure).unwrap()).unwrap()
+        };
+
+        run();
+        let vk_bytes = std::fs::read(&vk).unwrap();
+        // As if the first setup ran long ago, which alone does not change the disclosure
+        let mut earlier = read_disclosure();
+        earlier.created_at = 1;
+        earlier.write(File::create(&disclosure).unwrap()).unwrap();
+        let before = [modified(&pk), modified(&bundle), modified(&disclosure)];
+        std::fs::write(&vk, b"stale").unwrap();
+
+        run();
+        assert_eq!(
+            [modified(&pk), modified(&bundle), modified(&disclosure)],
+            before
+        );
+        assert_eq!(std::fs::read(&vk).unwrap(), vk_bytes);
+        assert_eq!(read_disclosure().created_at, 1);
+    }
+
+    #[test]
+    fn test_fixed_inputs() {
+        let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let no_inputs = dir.path().join("no-inputs.jsonl");
+
+        // The circuit's only public input is fixed
+        create_trusted_setup::<Bn254>(
+            r1cs.clone(),
+            None,
+            Some(pk.clone()),
+            Some(vk.clone()),
+            false,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            false,
+            false,
+            None,
+            None,
+            false,
+            Some(inputs.clone()),
+        )
+        .unwrap();
+        let proving_key: ProvingKey<Bn254> = deserialize_from(
+            File::open(&pk).unwrap(),
+            "proving key",
+            None,
+            artifact_options(),
+        )
+        .unwrap();
+        let folded: VerifyingKey<Bn254> = deserialize_from(
+            File::open(&vk).unwrap(),
+            "verifying key",
+            None,
+            artifact_options(),
+        )
+        .unwrap();
+        assert_eq!(folded.gamma_abc_g1.len(), 1);
+        assert_eq!(proving_key.vk.gamma_abc_g1.len(), 2);
+
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let proof = prove(&proving_key, r1cs, witness).unwrap();
+        assert!(verify(&folded, &proof, &[]).unwrap());
+        assert!(verify(&proving_key.vk, &proof, &[Fr::from(1u64)]).unwrap());
+
+        std::fs::write(&no_inputs, "").unwrap();
+        let mut proof_bytes = Vec::new();
+        serialize_to(&proof, &mut proof_bytes, "proof", artifact_options()).unwrap();
+        let proof_path = dir.path().join("proof");
+        std::fs::write(&proof_path, proof_bytes).unwrap();
+        assert!(verify_proof::<Bn254>(
+            Artifact::File(vk.clone()),
+            proof_path.clone(),
+            InputsSource::File(no_inputs.clone()),
+            false,
+            G2Order::Eth,
+            false,
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn test_cross_checked_inputs() {
+        let inputs = Some(PathBuf::from("test/resources/prog-inputs.jsonl"));
+        let snarkjs = Some(PathBuf::from("test/resources/prog-public.json"));
+        let words = format!("0x{:0>64}", "1");
+
+        let agreeing = InputsSource::from_args(inputs.clone(), Some(words), snarkjs);
+        assert_eq!(agreeing.load::<Bn254>().unwrap(), vec![Fr::from(1u64)]);
+
+        let other = format!("0x{:0>64}", "2");
+        let err = InputsSource::from_args(inputs, Some(other), None)
+            .load::<Bn254>()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Public input 0 is 1 in the inputs file"));
+    }
+
+    #[test]
+    fn test_gen_random_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let vk = dir.path().join("vk");
+        let out = dir.path().join("inputs.jsonl");
+
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+        serialize_to(
+            &verifying_key,
+            File::create(&vk).unwrap(),
+            "verifying key",
+            artifact_options(),
+        )
+        .unwrap();
+        let proof = prove(&proving_key, r1cs, witness).unwrap();
+
+        gen_random_inputs(vk.clone(), out.clone(), Some(1)).unwrap();
+        let inputs = load_inputs::<Bn254, _>(open_file(&out, "inputs").unwrap()).unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert!(!verify(&verifying_key, &proof, &inputs).unwrap());
+
+        gen_random_inputs(vk, out.clone(), Some(1)).unwrap();
+        assert_eq!(
+            load_inputs::<Bn254, _>(open_file(&out, "inputs").unwrap()).unwrap(),
+            inputs
+        );
+    }
+
+    #[test]
+    fn test_derive_vk() {
+        let dir = tempfile::tempdir().unwrap();
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let inputs = load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+        serialize_to(
+            &proving_key,
+            File::create(&pk).unwrap(),
+            "proving key",
+            artifact_options(),
+        )
+        .unwrap();
+
+        derive_vk(pk, vk.clone()).unwrap();
+        let derived: VerifyingKey<Bn254> = deserialize_from(
+            File::open(&vk).unwrap(),
+            "verifying key",
+            None,
+            artifact_options(),
+        )
+        .unwrap();
+        assert_eq!(derived, verifying_key);
+
+        let proof = prove(&proving_key, r1cs, witness).unwrap();
+        assert!(verify(&derived, &proof, &inputs).unwrap());
+    }
+
+    #[test]
+    fn test_verify_attest() {
+        let dir = tempfile::tempdir().unwrap();
+        let vk = dir.path().join("vk");
+        let proof = dir.path().join("proof");
+        let key = dir.path().join("key");
+        let out = dir.path().join("attestation.json");
+
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+        let proof_value = prove(&proving_key, r1cs, witness).unwrap();
+        // A tagged key hashes the same as an untagged one, as the disclosure hashes it
+        let vk_file = File::create(&vk).unwrap();
+        let tagged = serialization::Options {
+            tagged: true,
+            ..Default::default()
+        };
+        serialize_to(&verifying_key, vk_file, "verifying key", tagged).unwrap();
+        serialize_to(
+            &proof_value,
+            File::create(&proof).unwrap(),
+            "proof",
+            artifact_options(),
+        )
+        .unwrap();
+        std::fs::write(&key, format!("0x{:0>64}\n", "2a")).unwrap();
+
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+        assert!(verify_attest(vk, proof, inputs, key, out.clone()).unwrap());
+
+        let attestation: attest::Attestation =
+            serde_json::from_reader(File::open(&out).unwrap()).unwrap();
+        let claim = attestation.verify().unwrap();
+        assert!(claim.verified);
+        assert_eq!(
+            claim.verifying_key_sha256,
+            artifact_sha256(&verifying_key, "verifying key").unwrap()
+        );
+        assert_eq!(
+            claim.inputs_keccak256,
+            hex::encode(eth::inputs_commitment(
+                &load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap()
+            ))
+        );
+    }
+
+    // Answer one JSON-RPC request over HTTP with `result`, returning the request body
+    #[cfg(feature = "rpc")]
+    fn serve_rpc_once(result: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.trim_end().split_once(": ") {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.parse().unwrap();
+                    }
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = format!(r#"{{"jsonrpc":"2.0","id":1,"result":"{}"}}"#, result);
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, server)
+    }
+
+    #[cfg(feature = "rpc")]
+    #[test]
+    fn test_verify_on_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let vk = dir.path().join("vk");
+        let proof = dir.path().join("proof");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+        let address = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf";
+
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let witness = load_witness::<Bn254, _>(WITNESS_JSONL.as_bytes()).unwrap();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+        let proof_value = prove(&proving_key, r1cs, witness).unwrap();
+        serialize_to(
+            &verifying_key,
+            File::create(&vk).unwrap(),
+            "verifying key",
+            artifact_options(),
+        )
+        .unwrap();
+        serialize_to(
+            &proof_value,
+            File::create(&proof).unwrap(),
+            "proof",
+            artifact_options(),
+        )
+        .unwrap();
+        let accepted = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        let rejected = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+        let (url, server) = serve_rpc_once(accepted);
+        assert!(verify_on_chain(
+            &url,
+            address,
+            proof.clone(),
+            inputs.clone(),
+            Some(vk.clone())
+        )
+        .unwrap());
+        let request: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert_eq!(request["method"], "eth_call");
+        assert_eq!(request["params"][0]["to"], address);
+        let calldata = eth::verify_proof_calldata(
+            &proof_value.into(),
+            &load_inputs::<Bn254, _>(INPUTS_JSONL.as_bytes()).unwrap(),
+        );
+        assert_eq!(
+            request["params"][0]["data"],
+            format!("0x{}", hex::encode(calldata))
+        );
+
+        let (url, server) = serve_rpc_once(rejected);
+        assert!(!verify_on_chain(&url, address, proof.clone(), inputs.clone(), None).unwrap());
+        server.join().unwrap();
+
+        // A contract for another key disagrees with the local one
+        let (url, server) = serve_rpc_once(rejected);
+        let err =
+            verify_on_chain(&url, address, proof.clone(), inputs, Some(vk.clone())).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("rejects the proof but the verifying key accepts it"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_check_setup() {
+        let r1cs_path = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let witness = PathBuf::from("test/resources/prog-witness.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let other_vk = dir.path().join("other-vk");
+
+        let r1cs = load_r1cs::<Bn254, _>(open_file(&r1cs_path, "R1CS").unwrap()).unwrap();
+        let (proving_key, verifying_key) = setup(r1cs.clone()).unwrap();
+        let (_, other_verifying_key) = setup(r1cs).unwrap();
+        serialize_to(
+            &proving_key,
+            File::create(&pk).unwrap(),
+            "proving key",
+            artifact_options(),
+        )
+        .unwrap();
+        serialize_to(
+            &verifying_key,
+            File::create(&vk).unwrap(),
+            "verifying key",
+            artifact_options(),
+        )
+        .unwrap();
+        serialize_to(
+            &other_verifying_key,
+            File::create(&other_vk).unwrap(),
+            "verifying key",
+            artifact_options(),
+        )
+        .unwrap();
+
+        let circuit = CircuitSource::from_args(
+            Some(r1cs_path.clone()),
+            vec![witness.clone()],
+            None,
+            None,
+            None,
+        );
+        assert!(check_setup(pk.clone(), vk.clone(), Some(circuit)).unwrap());
+        assert!(!check_setup(pk.clone(), other_vk.clone(), None).unwrap());
+
+        // A proof that fails self-verification is never written
+        let proof = dir.path().join("proof");
+        let circuit = CircuitSource::from_args(Some(r1cs_path), vec![witness], None, None, None);
+        let result = create_proof::<Bn254>(
+            Artifact::File(pk.clone()),
+            Some(Artifact::File(other_vk.clone())),
+            circuit,
+            None,
+            proof.clone(),
+            false,
+            false,
+            false,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            None,
+            true,
+            None,
+            false,
+            false,
+        );
+        assert!(result.is_err());
+        assert!(!proof.exists());
+    }
+
+    #[test]
+    fn test_seeded_setup_is_deterministic() {
+        let r1cs = load_r1cs::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        let entropy = [7u8; entropy::MIN_ENTROPY_BYTES];
+
+        let (pk, vk) = seeded_setup(r1cs.clone(), &entropy, true, None).unwrap();
+        let (other_pk, other_vk) = seeded_setup(r1cs, &entropy, false, None).unwrap();
+        assert_eq!(pk, other_pk);
+        assert_eq!(vk, other_vk);
+    }
+
+    #[test]
+    fn test_read_buffer_size() {
+        assert_eq!(read_buffer_size(0), MIN_READ_BUFFER_SIZE);
+        assert_eq!(read_buffer_size(64 * 1024 * 1024), 64 * 1024);
+        assert_eq!(read_buffer_size(u64::MAX), MAX_READ_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn test_artifact_from_env() {
+        std::env::set_var("ARKWORKS_BRIDGE_TEST_KEY", BASE64.encode([1, 2, 3]));
+
+        let artifact = Artifact::from_args(Some(PathBuf::from("@ARKWORKS_BRIDGE_TEST_KEY")), None);
+        let mut bytes = Vec::new();
+        artifact
+            .open::<Bn254>(bundle::PROVING_KEY, "proving key")
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        assert_eq!(bytes, vec![1, 2, 3]);
+
+        let missing = Artifact::from_args(Some(PathBuf::from("@ARKWORKS_BRIDGE_UNSET")), None);
+        assert!(missing
+            .open::<Bn254>(bundle::PROVING_KEY, "proving key")
+            .is_err());
+    }
+
+    #[test]
+    fn test_cli_parses_every_command() {
+        let commands: [&[&str]; 47] = [
+            &[
+                "create-trusted-setup",
+                "-r",
+                "r1cs",
+                "-p",
+                "pk",
+                "-v",
+                "vk",
+                "--entropy",
+                "entropy.bin",
+                "--verify-determinism",
+                "--json-naming",
+                "snarkjs",
+            ],
+            &[
+                "create-trusted-setup",
+                "-r",
+                "r1cs",
+                "--bundle",
+                "setup.zip",
+                "--strict",
+                "--summary",
+                "--disclose",
+                "disclosure.json",
+                "--fixed-inputs",
+                "fixed.jsonl",
+            ],
+            &[
+                "generate-contract",
+                "-v",
+                "vk",
+                "-c",
+                "out.sol",
+                "-i",
+                "inputs",
+                "--expected-hash",
+                "0xabc",
+                "--g2-order",
+                "arkworks",
+                "--post-process",
+                "forge fmt -r -",
+            ],
+            &[
+                "generate-contract",
+                "-v",
+                "vk",
+                "-c",
+                "verifier.cairo",
+                "-i",
+                "inputs",
+                "--language",
+                "cairo",
+            ],
+            &[
+                "create-proof",
+                "-p",
+                "pk",
+                "-w",
+                "witness",
+                "-r",
+                "r1cs",
+                "--proof",
+                "out",
+                "--verifying-key",
+                "vk",
+                "--self-verify",
+                "--json-naming",
+                "a=pi_a,b=pi_b",
+                "--seed",
+                "7",
+                "--no-zk",
+                "--threads",
+                "2",
+            ],
+            &[
+                "create-proof",
+                "--bundle",
+                "setup.zip",
+                "-w",
+                "witness",
+                "-r",
+                "r1cs",
+                "--proof",
+                "out",
+                "--append",
+            ],
+            &[
+                "verify-proof",
+                "-v",
+                "vk",
+                "-p",
+                "proof",
+                "-i",
+                "inputs",
+                "--records",
+                "--results",
+                "results.jsonl",
+            ],
+            &[
+                "verify-proof",
+                "-v",
+                "vk",
+                "-p",
+                "proof",
+                "-i",
+                "inputs",
+                "--stream-vk",
+            ],
+            &[
+                "verify-proof",
+                "--bundle",
+                "setup.zip",
+                "-p",
+                "proof",
+                "-i",
+                "inputs",
+            ],
+            &[
+                "run-r1cs",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness.bin",
+                "--witness-format",
+                "bin",
+                "-i",
+                "inputs",
+                "--time-limit",
+                "60",
+            ],
+            &["run-r1cs", "--combined", "combined.jsonl", "-i", "inputs"],
+            &["run-r1cs", "-r", "r1cs", "--assignment", "assignment.json"],
+            &[
+                "run-r1cs",
+                "--curve",
+                "bls12-381",
+                "--combined",
+                "combined.jsonl",
+                "-i",
+                "inputs",
+            ],
+            &[
+                "create-proof",
+                "-p",
+                "pk",
+                "-r",
+                "r1cs",
+                "--assignment",
+                "assignment.json",
+                "--proof",
+                "proof",
+            ],
+            &[
+                "create-proof",
+                "-p",
+                "pk",
+                "--combined",
+                "combined.jsonl",
+                "--proof",
+                "out",
+            ],
+            &[
+                "prove-checked",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness",
+                "-p",
+                "pk",
+                "--proof",
+                "out",
+            ],
+            &[
+                "export-test-vectors",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness",
+                "-i",
+                "inputs",
+                "-o",
+                "vectors.json",
+            ],
+            &["density", "-r", "r1cs"],
+            &["unconstrained-inputs", "-r", "r1cs"],
+            &["profile", "-r", "r1cs", "-t", "5"],
+            &[
+                "redundancy",
+                "-r",
+                "r1cs",
+                "--samples",
+                "1000",
+                "--seed",
+                "7",
+            ],
+            &["graph", "-r", "r1cs", "-o", "circuit.dot"],
+            &["export-matrices", "-r", "r1cs", "-o", "matrices.json"],
+            &["import-matrices", "-m", "matrices.json", "-o", "r1cs"],
+            &["cache-matrices", "-r", "r1cs", "-o", "matrices.bin"],
+            &["check-reduced", "-i", "inputs", "--strict"],
+            &["diff-vk", "-a", "a", "-b", "b", "--error-json"],
+            &["inspect-proof", "-p", "proof"],
+            &["inputs-to-words", "-i", "inputs", "-o", "words"],
+            &["split-witness", "-w", "witness", "-n", "4", "-o", "shards"],
+            &[
+                "verify-attest",
+                "-v",
+                "vk",
+                "-p",
+                "proof",
+                "-i",
+                "inputs",
+                "-s",
+                "@KEY",
+                "-o",
+                "attestation.json",
+            ],
+            &["generate-multi-contract", "-v", "a", "-v", "b", "-c", "out"],
+            &["benchmark-parse", "-r", "r1cs", "--runs", "5"],
+            &["transcript-hash", "-v", "vk", "-p", "proof", "-i", "inputs"],
+            &["--self-check-resources", "test/resources"],
+            &[
+                "run-r1cs",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness",
+                "-i",
+                "inputs",
+                "--constraint-convention",
+                "abc-zero",
+            ],
+            &[
+                "spot-check",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness",
+                "--samples",
+                "10",
+                "--seed",
+                "1",
+            ],
+            &[
+                "verify-proof",
+                "-v",
+                "vk",
+                "-p",
+                "proof",
+                "--inputs-words",
+                "0x01",
+                "--inputs-snarkjs",
+                "public.json",
+            ],
+            &["diff-vk", "-a", "vk", "-b", "other-vk"],
+            &["canonicalize", "-r", "r1cs", "-o", "out", "--dedup"],
+            &["fingerprint", "-r", "r1cs"],
+            &[
+                "verify-on-chain",
+                "--rpc-url",
+                "http://localhost:8545",
+                "-a",
+                "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+                "-p",
+                "proof",
+                "-i",
+                "inputs",
+                "-v",
+                "vk",
+            ],
+            &["show-vk", "-v", "vk", "--g2-order", "arkworks"],
+            &[
+                "merge-inputs",
+                "-i",
+                "a.jsonl",
+                "-i",
+                "b.jsonl",
+                "-o",
+                "out",
+            ],
+            &["derive-vk", "-p", "pk", "-o", "vk"],
+            &[
+                "gen-random-inputs",
+                "-v",
+                "vk",
+                "-o",
+                "inputs",
+                "--seed",
+                "3",
+            ],
+            &[
+                "check-setup",
+                "-p",
+                "pk",
+                "-v",
+                "vk",
+                "--roundtrip",
+                "-r",
+                "r1cs",
+                "-w",
+                "witness",
+            ],
+        ];
+        for args in commands {
+            let argv = std::iter::once("arkworks-bridge").chain(args.iter().copied());
+            assert!(
+                Cli::from_iter_safe(argv).is_ok(),
+                "failed to parse {:?}",
+                args
+            );
+        }
+
+        let both = [
+            "arkworks-bridge",
+            "verify-proof",
+            "-v",
+            "vk",
+            "--bundle",
+            "setup.zip",
+        ];
+        assert!(Cli::from_iter_safe(both.iter().chain(&["-p", "proof", "-i", "inputs"])).is_err());
+
+        let combined_and_r1cs = [
+            "arkworks-bridge",
+            "run-r1cs",
+            "-r",
+            "r1cs",
+            "--combined",
+            "combined.jsonl",
+            "-i",
+            "inputs",
+        ];
+        assert!(Cli::from_iter_safe(combined_and_r1cs).is_err());
+    }
+
+    #[test]
+    fn test_read_eth_verifying_key_of_earlier_releases() {
+        // Written by v0.2.0 with `--ethereum`, G2 coordinates in arkworks order
+        let vk = PathBuf::from("test/resources/v0.2.0/vk-eth.json");
+        let proof = PathBuf::from("test/resources/v0.2.0/proof");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        let argv = [
+            "arkworks-bridge",
+            "verify-proof",
+            "-e",
+            "-v",
+            "vk",
+            "-p",
+            "proof",
+            "-i",
+            "inputs",
+        ];
+        match Cli::from_iter_safe(argv).unwrap().command {
+            Some(Command::VerifyProof { g2_order, .. }) => assert_eq!(g2_order, G2Order::Arkworks),
+            command => panic!("parsed {:?}", command),
+        }
+
+        let verify = |g2_order| {
+            verify_proof::<Bn254>(
+                Artifact::File(vk.clone()),
+                proof.clone(),
+                InputsSource::File(inputs.clone()),
+                true,
+                g2_order,
+                false,
+                None,
+                None,
+                false,
+                false,
+            )
+        };
+        assert!(verify(G2Order::Arkworks).unwrap());
+        let err = verify(G2Order::Eth).unwrap_err();
+        assert!(err.to_string().contains("--g2-order arkworks"));
+
+        // A key written now with the default order reads back the same
+        let dir = tempfile::tempdir().unwrap();
+        let eth_vk = dir.path().join("vk-eth.json");
+        let verifying_key: VerifyingKey<Bn254> = deserialize_from(
+            File::open("test/resources/v0.2.0/vk").unwrap(),
+            "verifying key",
+            None,
+            artifact_options(),
+        )
+        .unwrap();
+        let written = G2Order::Arkworks.apply_to_vk(&verifying_key.into());
+        serde_json::to_writer(File::create(&eth_vk).unwrap(), &written).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&eth_vk).unwrap(),
+            std::fs::read_to_string(&vk).unwrap().trim_end()
+        );
+    }
+
     #[test]
     fn test_end_to_end() {
         let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
         let witness = PathBuf::from("test/resources/prog-witness.jsonl");
-        let pk = PathBuf::from("test/resources/prog-pk");
-        let vk = PathBuf::from("test/resources/prog-vk");
-        let proof = PathBuf::from("test/resources/prog-proof");
+        let dir = tempfile::tempdir().unwrap();
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let proof = dir.path().join("proof");
         let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
 
         // ethereum is set to false because the tests aren't picking up the template for some reason?
-        create_trusted_setup(r1cs.clone(), pk.clone(), vk.clone(), false).unwrap();
-        create_proof(pk.clone(), witness, r1cs, proof.clone(), true).unwrap();
-        assert!(verify_proof(vk.clone(), proof.clone(), inputs, false).unwrap());
-
-        // Clean up
-        remove_file(pk).unwrap();
-        remove_file(vk).unwrap();
-        remove_file(proof).unwrap();
+        create_trusted_setup::<Bn254>(
+            r1cs.clone(),
+            None,
+            Some(pk.clone()),
+            Some(vk.clone()),
+            false,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            false,
+            false,
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        create_proof::<Bn254>(
+            Artifact::File(pk.clone()),
+            Some(Artifact::File(vk.clone())),
+            CircuitSource::Files {
+                r1cs,
+                witness: vec![witness],
+                witness_format: WitnessFormat::Json,
+            },
+            None,
+            proof.clone(),
+            false,
+            false,
+            true,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            true,
+            None,
+            None,
+            true,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        assert!(verify_proof::<Bn254>(
+            Artifact::File(vk.clone()),
+            proof.clone(),
+            InputsSource::File(inputs.clone()),
+            false,
+            G2Order::Eth,
+            false,
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap());
+        // Streaming the verifying key gives the same result
+        assert!(verify_proof::<Bn254>(
+            Artifact::File(vk.clone()),
+            proof.clone(),
+            InputsSource::File(inputs.clone()),
+            false,
+            G2Order::Eth,
+            false,
+            None,
+            None,
+            true,
+            false,
+        )
+        .unwrap());
+
+        // The commitment sidecar binds the proof to its public inputs
+        let commitment_path = sibling_json_path(&proof, "-commitment");
+        let sidecar: serde_json::Value =
+            serde_json::from_reader(File::open(&commitment_path).unwrap()).unwrap();
+        let commitment = sidecar["commitment"].as_str().unwrap().to_string();
+        let verify_commitment = |commitment: String| {
+            verify_proof::<Bn254>(
+                Artifact::File(vk.clone()),
+                proof.clone(),
+                InputsSource::File(inputs.clone()),
+                false,
+                G2Order::Eth,
+                false,
+                None,
+                Some(commitment),
+                false,
+                false,
+            )
+        };
+        assert!(verify_commitment(commitment).unwrap());
+        assert_eq!(
+            sidecar["abi_commitment"].as_str().unwrap().len(),
+            2 + 2 * 32
+        );
+        assert!(verify_commitment(format!("0x{}", "00".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn test_end_to_end_bls12_381() {
+        const BN254_MODULUS: &str =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        const BLS12_381_MODULUS: &str =
+            "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+        let bn254_r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let r1cs = dir.path().join("r1cs.jsonl");
+        let witness = dir.path().join("witness.jsonl");
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let proof = dir.path().join("proof");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        // The fixture circuit over the BLS12-381 scalar field, where -1 is a different element
+        std::fs::write(
+            &r1cs,
+            R1CS_JSONL
+                .replace(
+                    "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+                    "52435875175126190479447740508185965837690552500527637822603658699938581184512",
+                )
+                .replace(BN254_MODULUS, BLS12_381_MODULUS),
+        )
+        .unwrap();
+        std::fs::write(
+            &witness,
+            WITNESS_JSONL.replace(BN254_MODULUS, BLS12_381_MODULUS),
+        )
+        .unwrap();
+
+        let setup = |r1cs: PathBuf, ethereum: bool, g2_order: G2Order| {
+            create_trusted_setup::<Bls12_381>(
+                r1cs,
+                None,
+                Some(pk.clone()),
+                Some(vk.clone()),
+                ethereum,
+                g2_order,
+                JsonNaming::Arkworks,
+                false,
+                None,
+                false,
+                false,
+                None,
+                None,
+                false,
+                None,
+            )
+        };
+        // A BN254 circuit would prove the wrong statement over this field
+        let err = setup(bn254_r1cs, false, G2Order::Arkworks).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("not over the scalar field of bls12-381"));
+        // EIP-2537 fixes the G2 coordinate order
+        let err = setup(r1cs.clone(), true, G2Order::Eth).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        setup(r1cs.clone(), true, G2Order::Arkworks).unwrap();
+        create_proof::<Bls12_381>(
+            Artifact::File(pk.clone()),
+            Some(Artifact::File(vk.clone())),
+            CircuitSource::Files {
+                r1cs: r1cs.clone(),
+                witness: vec![witness.clone()],
+                witness_format: WitnessFormat::Json,
+            },
+            None,
+            proof.clone(),
+            false,
+            false,
+            true,
+            G2Order::Arkworks,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            None,
+            true,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        let verify = |inputs: InputsSource| {
+            verify_proof::<Bls12_381>(
+                Artifact::File(vk.clone()),
+                proof.clone(),
+                inputs,
+                false,
+                G2Order::Eth,
+                false,
+                None,
+                None,
+                false,
+                false,
+            )
+        };
+        assert!(verify(InputsSource::File(inputs)).unwrap());
+        assert!(verify(InputsSource::Words(format!("0x{:0>64}", "1"))).is_err());
+
+        // The -eth.json files hold the points in their EIP-2537 encoding
+        let eth_vk: eip2537::EthVerifyingKey =
+            serde_json::from_reader(File::open(dir.path().join("vk-eth.json")).unwrap()).unwrap();
+        let eth_proof: eip2537::EthProof =
+            serde_json::from_reader(File::open(dir.path().join("proof-eth.json")).unwrap())
+                .unwrap();
+        let vk: VerifyingKey<Bls12_381> = deserialize_from(
+            File::open(&vk).unwrap(),
+            "verifying key",
+            None,
+            artifact_options(),
+        )
+        .unwrap();
+        assert_eq!(eth_vk, eip2537::EthVerifyingKey::from(&vk));
+        assert_eq!(eth_proof.b.len(), 2 + 2 * eip2537::G2_SIZE);
+
+        let contract = dir.path().join("verifier.sol");
+        let generate = |language: Language| {
+            generate_contract(
+                Curve::Bls12_381,
+                dir.path().join("vk"),
+                contract.clone(),
+                None,
+                None,
+                G2Order::Eth,
+                language,
+                None,
+            )
+        };
+        generate(Language::Solidity).unwrap();
+        assert!(std::fs::read_to_string(&contract)
+            .unwrap()
+            .contains(&eth_vk.ic[1][2..]));
+        assert!(generate(Language::Noir).is_err());
+    }
+
+    #[test]
+    fn test_generate_contract_input_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let write_vk = |n_inputs: usize| {
+            let vk = VerifyingKey::<Bn254> {
+                gamma_abc_g1: vec![Default::default(); n_inputs + 1],
+                ..Default::default()
+            };
+            let path = dir.path().join(format!("vk-{}", n_inputs));
+            serialize_to(
+                &vk,
+                File::create(&path).unwrap(),
+                "verifying key",
+                artifact_options(),
+            )
+            .unwrap();
+            path
+        };
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+        let contract = dir.path().join("verifier.sol");
+        let generate = |vk: PathBuf, inputs: Option<PathBuf>| {
+            generate_contract(
+                Curve::Bn254,
+                vk,
+                contract.clone(),
+                inputs,
+                None,
+                G2Order::Eth,
+                Language::Solidity,
+                None,
+            )
+        };
+
+        // The input count comes from the key, with or without an inputs file
+        generate(write_vk(3), None).unwrap();
+        let rendered = std::fs::read_to_string(&contract).unwrap();
+        assert!(rendered.contains("uint[3] memory input"));
+        assert!(rendered.contains("Pairing.G1Point[4] IC;"));
+
+        generate(write_vk(1), Some(inputs.clone())).unwrap();
+        assert!(std::fs::read_to_string(&contract)
+            .unwrap()
+            .contains("uint[1] memory input"));
+
+        let err = generate(write_vk(3), Some(inputs)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err
+            .to_string()
+            .contains("1 inputs but the verifying key has 3"));
+    }
+
+    #[test]
+    fn test_end_to_end_bundle() {
+        let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let witness = PathBuf::from("test/resources/prog-witness.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("bundle.zip");
+        let disclosure = dir.path().join("disclosure.json");
+        let proof = dir.path().join("proof");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        create_trusted_setup::<Bn254>(
+            r1cs.clone(),
+            None,
+            None,
+            None,
+            true,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            false,
+            false,
+            Some(bundle.clone()),
+            Some(disclosure.clone()),
+            true,
+            None,
+        )
+        .unwrap();
+
+        let disclosure: disclosure::Disclosure =
+            serde_json::from_reader(File::open(&disclosure).unwrap()).unwrap();
+        assert!(disclosure.single_party);
+        assert_eq!(disclosure.curve, "bn254");
+        assert_eq!(disclosure.randomness, disclosure::Randomness::System);
+
+        let manifest = bundle::read_manifest(File::open(&bundle).unwrap()).unwrap();
+        assert_eq!(manifest.files.len(), 4);
+
+        let setup = Artifact::Bundle(bundle.clone());
+        create_proof::<Bn254>(
+            setup,
+            None,
+            CircuitSource::Files {
+                r1cs,
+                witness: vec![witness],
+                witness_format: WitnessFormat::Json,
+            },
+            None,
+            proof.clone(),
+            false,
+            false,
+            false,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            None,
+            true,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+
+        let setup = Artifact::Bundle(bundle.clone());
+        assert!(verify_proof::<Bn254>(
+            setup,
+            proof.clone(),
+            InputsSource::File(inputs.clone()),
+            false,
+            G2Order::Eth,
+            false,
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap());
+
+        // The eth-compatible verifying key is read back in the order it was written
+        let setup = Artifact::Bundle(bundle.clone());
+        assert!(verify_proof::<Bn254>(
+            setup,
+            proof.clone(),
+            InputsSource::File(inputs),
+            true,
+            G2Order::Eth,
+            false,
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap());
+
+        // A bundle whose circuit was swapped is refused
+        let entries: Vec<(&str, Vec<u8>)> = manifest
+            .files
+            .iter()
+            .map(|name| {
+                let bytes = match name.as_str() {
+                    bundle::CIRCUIT => b"another circuit".to_vec(),
+                    name => bundle::read_entry(File::open(&bundle).unwrap(), name).unwrap(),
+                };
+                (name.as_str(), bytes)
+            })
+            .collect();
+        bundle::write_bundle(File::create(&bundle).unwrap(), &manifest, &entries).unwrap();
+        let err = Artifact::Bundle(bundle)
+            .open::<Bn254>(bundle::VERIFYING_KEY, "verifying key")
+            .err()
+            .unwrap();
+        assert_eq!(error::classify(&err), error::Category::Parse);
+    }
+
+    #[test]
+    fn test_append_proofs() {
+        let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let witness = PathBuf::from("test/resources/prog-witness.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let pk = dir.path().join("pk");
+        let vk = dir.path().join("vk");
+        let proofs = dir.path().join("proofs");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+
+        create_trusted_setup::<Bn254>(
+            r1cs.clone(),
+            None,
+            Some(pk.clone()),
+            Some(vk.clone()),
+            false,
+            G2Order::Eth,
+            JsonNaming::Arkworks,
+            false,
+            None,
+            false,
+            false,
+            None,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+        for _ in 0..2 {
+            create_proof::<Bn254>(
+                Artifact::File(pk.clone()),
+                None,
+                CircuitSource::Files {
+                    r1cs: r1cs.clone(),
+                    witness: vec![witness.clone()],
+                    witness_format: WitnessFormat::Json,
+                },
+                None,
+                proofs.clone(),
+                true,
+                false,
+                false,
+                G2Order::Eth,
+                JsonNaming::Arkworks,
+                false,
+                None,
+                None,
+                true,
+                None,
+                false,
+                false,
+            )
+            .unwrap();
+        }
+
+        let records: Vec<Proof<Bn254>> =
+            serialization::read_records(File::open(&proofs).unwrap(), "proof", artifact_options())
+                .unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(verify_proof::<Bn254>(
+            Artifact::File(vk.clone()),
+            proofs.clone(),
+            InputsSource::File(inputs.clone()),
+            false,
+            G2Order::Eth,
+            true,
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap());
+
+        // Results are written as proofs are verified, and recorded results are not checked again
+        let results = dir.path().join("results.jsonl");
+        let verify_with_results = || {
+            verify_proof::<Bn254>(
+                Artifact::File(vk.clone()),
+                proofs.clone(),
+                InputsSource::File(inputs.clone()),
+                false,
+                G2Order::Eth,
+                true,
+                Some(results.clone()),
+                None,
+                false,
+                false,
+            )
+        };
+        assert!(verify_with_results().unwrap());
+        let written = std::fs::read_to_string(&results).unwrap();
+        assert_eq!(written.lines().count(), 3);
+        // Every proof is already recorded as verified, so nothing is appended
+        assert!(verify_with_results().unwrap());
+        assert_eq!(std::fs::read_to_string(&results).unwrap(), written);
+        let batch = written.lines().next().unwrap();
+        std::fs::write(
+            &results,
+            format!("{}\n{{\"proof\":0,\"verified\":false}}\n", batch),
+        )
+        .unwrap();
+        assert!(!verify_with_results().unwrap());
+        assert_eq!(
+            std::fs::read_to_string(&results).unwrap().lines().count(),
+            3
+        );
+
+        // A record that is not a proof stops the batch, unless it continues past errors
+        let mut file = OpenOptions::new().append(true).open(&proofs).unwrap();
+        file.write_all(&(proof_size::<Bn254>() as u64).to_le_bytes())
+            .unwrap();
+        file.write_all(&vec![0xff; proof_size::<Bn254>()]).unwrap();
+        drop(file);
+        let verify_records = |continue_on_error: bool| {
+            verify_proof::<Bn254>(
+                Artifact::File(vk.clone()),
+                proofs.clone(),
+                InputsSource::File(inputs.clone()),
+                false,
+                G2Order::Eth,
+                true,
+                None,
+                None,
+                false,
+                continue_on_error,
+            )
+        };
+        assert!(verify_records(false).is_err());
+        let err = verify_records(true).unwrap_err();
+        assert_eq!(error::classify(&err), error::Category::Parse);
+        assert!(err
+            .to_string()
+            .starts_with("1 of 3 proofs failed:\n  proof 2: "));
+    }
+
+    #[test]
+    fn test_export_test_vectors_is_reproducible() {
+        let r1cs = PathBuf::from("test/resources/prog-r1cs.jsonl");
+        let witness = PathBuf::from("test/resources/prog-witness.jsonl");
+        let inputs = PathBuf::from("test/resources/prog-inputs.jsonl");
+        let dir = tempfile::tempdir().unwrap();
+        let outputs = [
+            dir.path().join("vectors-1.json"),
+            dir.path().join("vectors-2.json"),
+        ];
+
+        for out in &outputs {
+            export_test_vectors(r1cs.clone(), witness.clone(), inputs.clone(), out.clone())
+                .unwrap();
+        }
+
+        let first = std::fs::read_to_string(&outputs[0]).unwrap();
+        assert_eq!(first, std::fs::read_to_string(&outputs[1]).unwrap());
+
+        let vectors: serde_json::Value = serde_json::from_str(&first).unwrap();
+        assert_eq!(vectors["expected"], true);
     }
 }
@@ -0,0 +1,235 @@
+use crate::circuit::Circuit;
+use crate::error;
+use crate::field::{parse_coefficient, to_decimal_string};
+use crate::header::Header;
+use crate::r1cs::{R1CSFile, R1C, R1CS};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, Matrix, SynthesisMode,
+};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub row: usize,
+    pub col: usize,
+    pub value: String,
+}
+
+/// The A, B, C matrices as arkworks builds them when synthesizing the circuit.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Matrices {
+    pub num_instance_variables: usize,
+    pub num_witness_variables: usize,
+    pub num_constraints: usize,
+    /// R1CS variable index of each matrix column. Columns are the variables themselves when absent.
+    #[serde(default)]
+    pub column_variables: Vec<usize>,
+    pub a: Vec<Entry>,
+    pub b: Vec<Entry>,
+    pub c: Vec<Entry>,
+}
+
+fn entries<E: Pairing>(matrix: &Matrix<E::ScalarField>) -> Vec<Entry> {
+    matrix
+        .iter()
+        .enumerate()
+        .flat_map(|(row, terms)| {
+            terms.iter().map(move |(value, col)| Entry {
+                row,
+                col: *col,
+                value: to_decimal_string(value),
+            })
+        })
+        .collect()
+}
+
+/// R1CS variable index of each matrix column. Columns follow allocation order: the constant,
+/// then inputs, then witness variables.
+pub fn column_variables(
+    constant_index: usize,
+    input_variables: &[usize],
+    witness_variables: &[usize],
+) -> Vec<usize> {
+    std::iter::once(constant_index)
+        .chain(input_variables.iter().copied())
+        .chain(witness_variables.iter().copied())
+        .collect()
+}
+
+/// Synthesize the circuit in setup mode, without a witness, and return the matrices arkworks
+/// builds for it.
+pub fn constraint_matrices<E: Pairing>(
+    r1cs: R1CS<E>,
+) -> io::Result<ConstraintMatrices<E::ScalarField>> {
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+
+    let circuit = Circuit {
+        r1cs,
+        witness: None,
+        progress: None,
+    };
+    circuit
+        .generate_constraints(cs.clone())
+        .map_err(|e| error::prove_failed(format!("Failed to synthesize circuit: {}", e)))?;
+    cs.finalize();
+
+    cs.to_matrices().ok_or_else(|| {
+        error::prove_failed("Constraint system did not construct matrices".to_string())
+    })
+}
+
+/// Synthesize the circuit in setup mode, without a witness, and read back its matrices.
+pub fn synthesize_matrices<E: Pairing>(r1cs: R1CS<E>) -> io::Result<Matrices> {
+    let column_variables = column_variables(
+        r1cs.constant_index,
+        &r1cs.input_variables,
+        &r1cs.witness_variables,
+    );
+    let matrices = constraint_matrices(r1cs)?;
+
+    Ok(Matrices {
+        num_instance_variables: matrices.num_instance_variables,
+        num_witness_variables: matrices.num_witness_variables,
+        num_constraints: matrices.num_constraints,
+        column_variables,
+        a: entries::<E>(&matrices.a),
+        b: entries::<E>(&matrices.b),
+        c: entries::<E>(&matrices.c),
+    })
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl Matrices {
+    /// Rebuild an R1CS file from the matrices: one constraint per row, with each column mapped
+    /// back to its variable. The first instance column is the constant one, and the rest are the
+    /// public inputs. Output variables are not recorded in the matrices and are left empty.
+    pub fn to_r1cs_file<E: Pairing>(&self) -> io::Result<R1CSFile<E>> {
+        let n_variables = self.num_instance_variables + self.num_witness_variables;
+        let column_variables = if self.column_variables.is_empty() {
+            (0..n_variables).collect()
+        } else {
+            self.column_variables.clone()
+        };
+
+        let mut sorted = column_variables.clone();
+        sorted.sort_unstable();
+        if self.num_instance_variables == 0 || !sorted.into_iter().eq(0..n_variables) {
+            return Err(invalid(format!(
+                "column_variables must assign each of the {} variables to one column, starting with the constant",
+                n_variables
+            )));
+        }
+
+        let mut constraints = vec![
+            R1C {
+                a: Vec::new(),
+                b: Vec::new(),
+                c: Vec::new(),
+                src: None,
+            };
+            self.num_constraints
+        ];
+        for (name, entries) in [("a", &self.a), ("b", &self.b), ("c", &self.c)] {
+            for entry in entries {
+                let variable = *column_variables.get(entry.col).ok_or_else(|| {
+                    invalid(format!(
+                        "Matrix {} entry in column {} is out of range 0..{}",
+                        name, entry.col, n_variables
+                    ))
+                })?;
+                let row = constraints.get_mut(entry.row).ok_or_else(|| {
+                    invalid(format!(
+                        "Matrix {} entry in row {} is out of range 0..{}",
+                        name, entry.row, self.num_constraints
+                    ))
+                })?;
+                let value = parse_coefficient(&entry.value).map_err(invalid)?;
+                let terms = match name {
+                    "a" => &mut row.a,
+                    "b" => &mut row.b,
+                    _ => &mut row.c,
+                };
+                terms.push((value, variable));
+            }
+        }
+
+        let constant_index = column_variables[0];
+        let header = Header {
+            extension_degree: 1,
+            field_characteristic: E::ScalarField::MODULUS.into(),
+            curve: None,
+            input_variables: column_variables[1..self.num_instance_variables].to_vec(),
+            n_constraints: self.num_constraints,
+            n_variables,
+            output_variables: Vec::new(),
+            constant_index: (constant_index != 0).then_some(constant_index),
+        };
+        Ok(R1CSFile {
+            header,
+            constraints,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn test_synthesize_matrices() {
+        let r1cs: R1CS<Bn254> =
+            parse_r1cs_file(include_str!("../test/resources/prog-r1cs.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        let matrices = synthesize_matrices(r1cs).unwrap();
+
+        assert_eq!(matrices.num_instance_variables, 2);
+        assert_eq!(matrices.num_witness_variables, 1);
+        assert_eq!(matrices.num_constraints, 1);
+        assert_eq!(matrices.column_variables, vec![0, 1, 2]);
+        assert_eq!(
+            matrices.a,
+            vec![Entry {
+                row: 0,
+                col: 0,
+                value: "1".to_string()
+            }]
+        );
+        assert_eq!(matrices.b.len(), 3);
+        assert!(matrices.c.is_empty());
+    }
+
+    #[test]
+    fn test_matrices_to_r1cs_file() {
+        let file = parse_r1cs_file::<Bn254, _>(
+            include_str!("../test/resources/prog-r1cs.jsonl").as_bytes(),
+        )
+        .unwrap();
+        let matrices = synthesize_matrices::<Bn254>(file.clone().into()).unwrap();
+
+        let rebuilt = matrices.to_r1cs_file::<Bn254>().unwrap();
+        assert_eq!(rebuilt.header.input_variables, file.header.input_variables);
+        assert_eq!(rebuilt.header.n_variables, file.header.n_variables);
+        let again = synthesize_matrices::<Bn254>(rebuilt.into()).unwrap();
+        assert_eq!(again.a, matrices.a);
+        assert_eq!(again.b, matrices.b);
+        assert_eq!(again.c, matrices.c);
+
+        let out_of_range = Matrices {
+            num_constraints: 0,
+            ..matrices
+        };
+        let err = out_of_range.to_r1cs_file::<Bn254>().unwrap_err();
+        assert!(err.to_string().contains("row 0 is out of range"));
+    }
+}
@@ -0,0 +1,372 @@
+use crate::error;
+use crate::matrices::{column_variables, constraint_matrices};
+use crate::r1cs::{header_is_last, BINARY_MAGIC, R1C, R1CS};
+use crate::witness::Witness;
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_groth16::{Groth16, Proof, ProvingKey};
+use ark_relations::r1cs::{ConstraintMatrices, Matrix};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+
+// A constraint line of an R1CS or combined file, or a witness line of a combined file, whose
+// terms are hashed without its `type` tag so both file kinds hash the same
+#[derive(Deserialize)]
+struct Line {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(rename = "A", default)]
+    a: Value,
+    #[serde(rename = "B", default)]
+    b: Value,
+    #[serde(rename = "C", default)]
+    c: Value,
+}
+
+/// SHA-256 of an R1CS file: of the hash of its header line followed by the hash of its
+/// constraints, in order. The header may be the first or the last line, and the witness lines
+/// of a combined file are skipped, so an R1CS file and a combined file of the same circuit hash
+/// the same. A binary R1CS is hashed as it is.
+pub fn r1cs_sha256<R: BufRead>(mut reader: R) -> io::Result<Vec<u8>> {
+    if reader.fill_buf()?.starts_with(BINARY_MAGIC) {
+        let mut hasher = Sha256::new();
+        io::copy(&mut reader, &mut hasher)?;
+        return Ok(hasher.finalize().to_vec());
+    }
+
+    let mut lines = reader.lines();
+    let first = lines.next().transpose()?.unwrap_or_default();
+    let header_last = header_is_last(&first);
+    let mut constraints = Sha256::new();
+    let mut hash_line = |line: String| -> io::Result<()> {
+        let line: Line = serde_json::from_str(&line)?;
+        if line.kind.as_deref() != Some("witness") {
+            constraints.update(serde_json::to_vec(&(line.a, line.b, line.c))?);
+        }
+        Ok(())
+    };
+
+    let mut header = first;
+    if header_last {
+        for next in lines {
+            hash_line(std::mem::replace(&mut header, next?))?;
+        }
+    } else {
+        for line in lines {
+            hash_line(line?)?;
+        }
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(header.as_bytes()));
+    hasher.update(constraints.finalize());
+    Ok(hasher.finalize().to_vec())
+}
+
+/// The constraint matrices of a circuit, synthesized once so proofs can skip synthesis. A cache
+/// is matched to its circuit by the hash of the whole R1CS file.
+pub struct MatrixCache<E: Pairing> {
+    pub r1cs_sha256: Vec<u8>,
+    pub input_variables: Vec<usize>,
+    pub witness_variables: Vec<usize>,
+    pub matrices: ConstraintMatrices<E::ScalarField>,
+}
+
+impl<E: Pairing> MatrixCache<E> {
+    pub fn build(r1cs: R1CS<E>, r1cs_sha256: Vec<u8>) -> io::Result<Self> {
+        let input_variables = r1cs.input_variables.clone();
+        let witness_variables = r1cs.witness_variables.clone();
+        Ok(MatrixCache {
+            r1cs_sha256,
+            input_variables,
+            witness_variables,
+            matrices: constraint_matrices(r1cs)?,
+        })
+    }
+
+    pub fn check_r1cs(&self, r1cs_sha256: &[u8]) -> io::Result<()> {
+        if self.r1cs_sha256 != r1cs_sha256 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Matrix cache was built from another R1CS file, or before the R1CS changed",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The constant one variable. Variables are numbered from 0, so it is the one index that is
+    /// neither an input nor a witness variable.
+    pub fn constant_index(&self) -> usize {
+        let n_variables = 1 + self.input_variables.len() + self.witness_variables.len();
+        let mut assigned = vec![false; n_variables];
+        for &v in self.input_variables.iter().chain(&self.witness_variables) {
+            if v < n_variables {
+                assigned[v] = true;
+            }
+        }
+        assigned.iter().position(|a| !a).unwrap_or(0)
+    }
+
+    /// The R1CS the matrices were synthesized from, up to merged terms and their order.
+    pub fn to_r1cs(&self) -> R1CS<E> {
+        let columns = column_variables(
+            self.constant_index(),
+            &self.input_variables,
+            &self.witness_variables,
+        );
+        let terms = |row: &[(E::ScalarField, usize)]| {
+            row.iter()
+                .map(|(coeff, col)| (*coeff, columns[*col]))
+                .collect()
+        };
+
+        R1CS {
+            constant_index: self.constant_index(),
+            input_variables: self.input_variables.clone(),
+            witness_variables: self.witness_variables.clone(),
+            constraints: (0..self.matrices.num_constraints)
+                .map(|i| R1C {
+                    a: terms(&self.matrices.a[i]),
+                    b: terms(&self.matrices.b[i]),
+                    c: terms(&self.matrices.c[i]),
+                    src: None,
+                })
+                .collect(),
+        }
+    }
+
+    // The value of every matrix column: the constant one, then inputs, then witness variables
+    fn assignment(&self, witness: &Witness<E>) -> io::Result<Vec<E::ScalarField>> {
+        let lookup = |values: &HashMap<usize, E::ScalarField>, index: &usize, kind: &str| {
+            values.get(index).copied().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Witness has no value for {} {}", kind, index),
+                )
+            })
+        };
+
+        let mut assignment = vec![E::ScalarField::ONE];
+        for index in &self.input_variables {
+            assignment.push(lookup(&witness.input_variables, index, "public input")?);
+        }
+        for index in &self.witness_variables {
+            assignment.push(lookup(
+                &witness.witness_variables,
+                index,
+                "witness variable",
+            )?);
+        }
+        Ok(assignment)
+    }
+
+    /// Prove with randomizers `r` and `s` straight from the cached matrices.
+    pub fn prove(
+        &self,
+        proving_key: &ProvingKey<E>,
+        witness: &Witness<E>,
+        r: E::ScalarField,
+        s: E::ScalarField,
+    ) -> io::Result<Proof<E>> {
+        Groth16::<E>::create_proof_with_reduction_and_matrices(
+            proving_key,
+            r,
+            s,
+            &self.matrices,
+            self.matrices.num_instance_variables,
+            self.matrices.num_constraints,
+            &self.assignment(witness)?,
+        )
+        .map_err(|err| error::prove_failed(format!("Failed to create proof: {}", err)))
+    }
+}
+
+// Only the R1CS hash, the variables and the matrices are stored, the dimensions follow from
+// them
+impl<E: Pairing> CanonicalSerialize for MatrixCache<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.r1cs_sha256
+            .serialize_with_mode(&mut writer, compress)?;
+        self.input_variables
+            .serialize_with_mode(&mut writer, compress)?;
+        self.witness_variables
+            .serialize_with_mode(&mut writer, compress)?;
+        self.matrices.a.serialize_with_mode(&mut writer, compress)?;
+        self.matrices.b.serialize_with_mode(&mut writer, compress)?;
+        self.matrices.c.serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.r1cs_sha256.serialized_size(compress)
+            + self.input_variables.serialized_size(compress)
+            + self.witness_variables.serialized_size(compress)
+            + self.matrices.a.serialized_size(compress)
+            + self.matrices.b.serialized_size(compress)
+            + self.matrices.c.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for MatrixCache<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        let n_rows = self.matrices.a.len();
+        let n_columns = 1 + self.input_variables.len() + self.witness_variables.len();
+        let in_range = |matrix: &Matrix<E::ScalarField>| {
+            matrix
+                .iter()
+                .flatten()
+                .all(|(_, column)| *column < n_columns)
+        };
+
+        if self.matrices.b.len() != n_rows
+            || self.matrices.c.len() != n_rows
+            || ![&self.matrices.a, &self.matrices.b, &self.matrices.c]
+                .into_iter()
+                .all(in_range)
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for MatrixCache<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let r1cs_sha256 = Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let input_variables: Vec<usize> =
+            Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let witness_variables: Vec<usize> =
+            Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let a: Matrix<E::ScalarField> =
+            Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let b: Matrix<E::ScalarField> =
+            Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+        let c: Matrix<E::ScalarField> =
+            Vec::deserialize_with_mode(&mut reader, compress, validate)?;
+
+        let non_zero = |matrix: &Matrix<E::ScalarField>| matrix.iter().map(Vec::len).sum();
+        let cache = MatrixCache {
+            r1cs_sha256,
+            matrices: ConstraintMatrices {
+                num_instance_variables: 1 + input_variables.len(),
+                num_witness_variables: witness_variables.len(),
+                num_constraints: a.len(),
+                a_num_non_zero: non_zero(&a),
+                b_num_non_zero: non_zero(&b),
+                c_num_non_zero: non_zero(&c),
+                a,
+                b,
+                c,
+            },
+            input_variables,
+            witness_variables,
+        };
+
+        if validate == Validate::Yes {
+            cache.check()?;
+        }
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use crate::serialization::{deserialize_from, serialize_to, Options};
+    use crate::witness::parse_witness_file;
+    use crate::{prove_with, setup_with};
+    use ark_bn254::{Bn254, Fr};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const R1CS_FILE: &str = include_str!("../test/resources/prog-r1cs.jsonl");
+
+    fn fixture() -> (R1CS<Bn254>, MatrixCache<Bn254>) {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_FILE.as_bytes()).unwrap().into();
+        let hash = r1cs_sha256(R1CS_FILE.as_bytes()).unwrap();
+        let cache = MatrixCache::build(r1cs.clone(), hash).unwrap();
+
+        let mut bytes = Vec::new();
+        serialize_to(&cache, &mut bytes, "matrix cache", Options::default()).unwrap();
+        let cache =
+            deserialize_from(bytes.as_slice(), "matrix cache", None, Options::default()).unwrap();
+        (r1cs, cache)
+    }
+
+    #[test]
+    fn test_binary_r1cs_is_hashed_whole() {
+        // The same up to the first newline, which is all a header line hash would cover
+        let mut binary = BINARY_MAGIC.to_vec();
+        binary.extend([1, 0, 0, 0, b'\n', 1, 2, 3]);
+        let mut changed = binary.clone();
+        *changed.last_mut().unwrap() = 4;
+        assert_ne!(
+            r1cs_sha256(binary.as_slice()).unwrap(),
+            r1cs_sha256(changed.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cached_proof_matches_synthesized_proof() {
+        let (r1cs, cache) = fixture();
+        let witness: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        // The keys from the R1CS rebuilt out of the cache are the keys of the original
+        let mut rng = StdRng::seed_from_u64(0);
+        let (pk, _) = setup_with(r1cs.clone(), &mut rng).unwrap();
+        let (cached_pk, _) = setup_with(cache.to_r1cs(), &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(cached_pk, pk);
+
+        let expected = prove_with(&pk, r1cs, witness.clone(), false, &mut rng).unwrap();
+        let proof = cache
+            .prove(&pk, &witness, Fr::from(0u64), Fr::from(0u64))
+            .unwrap();
+        assert_eq!(proof, expected);
+    }
+
+    #[test]
+    fn test_r1cs_mismatch() {
+        let (_, cache) = fixture();
+        let hash = r1cs_sha256(R1CS_FILE.as_bytes()).unwrap();
+        assert!(cache.check_r1cs(&hash).is_ok());
+
+        let check = |other: String| {
+            assert_ne!(other, R1CS_FILE);
+            let err = cache
+                .check_r1cs(&r1cs_sha256(other.as_bytes()).unwrap())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        };
+        check(R1CS_FILE.replacen("\"n_constraints\":1", "\"n_constraints\":2", 1));
+        // The counts are unchanged, only a coefficient of the constraint is
+        check(R1CS_FILE.replacen("[\"55\",0]", "[\"56\",0]", 1));
+    }
+
+    #[test]
+    fn test_combined_file_hashes_as_its_r1cs() {
+        let combined = include_str!("../test/resources/prog-combined.jsonl");
+        let hash = r1cs_sha256(combined.as_bytes()).unwrap();
+        assert_eq!(hash, r1cs_sha256(R1CS_FILE.as_bytes()).unwrap());
+
+        // Witness lines are not part of the circuit
+        let other_witness = combined.replace("\"value\":\"66\"", "\"value\":\"67\"");
+        assert_eq!(r1cs_sha256(other_witness.as_bytes()).unwrap(), hash);
+    }
+}
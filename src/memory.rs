@@ -0,0 +1,19 @@
+use log::{info, warn};
+use std::fs;
+
+/// Peak resident set size of this process in kB, as reported by `VmHWM` in
+/// `/proc/self/status`. Only available on Linux.
+pub fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+pub fn log_peak_rss(phase: &str) {
+    match peak_rss_kb() {
+        Some(kb) => info!("Peak memory after {}: {:.1} MiB", phase, kb as f64 / 1024.0),
+        None => warn!("Peak memory is not available on this platform"),
+    }
+}
@@ -0,0 +1,71 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Run `command` through the shell with `input` on its stdin and return its stdout. A command
+/// that cannot start or exits unsuccessfully is an error carrying its stderr.
+pub fn filter(command: &str, input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to run post-process command {:?}: {}", command, e),
+            )
+        })?;
+
+    // Feed stdin from another thread so a command that writes before reading everything can't
+    // deadlock against us
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output()?;
+    // A command that exits without reading its input closes the pipe, which is not our error
+    let _ = writer.join().expect("stdin writer panicked");
+
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "Post-process command {:?} failed with {}: {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        ));
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let output = filter("tr a-z A-Z", b"contract Verifier {}").unwrap();
+        assert_eq!(output, b"CONTRACT VERIFIER {}");
+
+        let err = filter("echo broken >&2; exit 3", b"").unwrap_err();
+        assert!(err.to_string().contains("broken"));
+    }
+}
@@ -0,0 +1,85 @@
+use ark_ec::pairing::Pairing;
+use arkworks_bridge::r1cs::R1C;
+use std::fmt;
+
+/// Number of terms in each linear combination of one constraint.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ConstraintCost {
+    pub index: usize,
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+impl ConstraintCost {
+    pub fn total(&self) -> usize {
+        self.a + self.b + self.c
+    }
+}
+
+/// The `k` constraints with the most terms, largest first. Ties keep file order.
+pub fn most_expensive<E: Pairing>(constraints: &[R1C<E>], k: usize) -> Vec<ConstraintCost> {
+    let mut costs: Vec<ConstraintCost> = constraints
+        .iter()
+        .enumerate()
+        .map(|(index, constraint)| ConstraintCost {
+            index,
+            a: constraint.a.len(),
+            b: constraint.b.len(),
+            c: constraint.c.len(),
+        })
+        .collect();
+    costs.sort_by_key(|cost| std::cmp::Reverse(cost.total()));
+    costs.truncate(k);
+    costs
+}
+
+/// Ranked table of constraint costs.
+pub struct CostTable<'a>(pub &'a [ConstraintCost]);
+
+impl fmt::Display for CostTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>4}  {:>10}  {:>6}  {:>6}  {:>6}  {:>6}",
+            "rank", "constraint", "A", "B", "C", "total"
+        )?;
+        for (rank, cost) in self.0.iter().enumerate() {
+            write!(
+                f,
+                "\n{:>4}  {:>10}  {:>6}  {:>6}  {:>6}  {:>6}",
+                rank + 1,
+                cost.index,
+                cost.a,
+                cost.b,
+                cost.c,
+                cost.total()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use arkworks_bridge::r1cs::parse_r1cs_file;
+
+    #[test]
+    fn test_most_expensive() {
+        let r1cs = r#"{"extension_degree":1,"field_characteristic":"21888242871839275222246405745257275088548364400416034343698204186575808495617","input_variables":[1],"n_constraints":3,"n_variables":3,"output_variables":[2]}
+{"A":[["1",0]],"B":[["1",1]],"C":[["1",2]]}
+{"A":[["1",0],["2",1],["3",2]],"B":[["1",0]],"C":[["1",2]]}
+{"A":[["1",1]],"B":[["1",1]],"C":[["1",2],["1",0]]}"#;
+        let file = parse_r1cs_file::<Bn254, _>(r1cs.as_bytes()).unwrap();
+
+        let top = most_expensive(&file.constraints, 2);
+        let indices: Vec<usize> = top.iter().map(|cost| cost.index).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert_eq!(top[0].total(), 5);
+
+        let table = CostTable(&top).to_string();
+        assert_eq!(table.lines().count(), 3);
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Share of setup or proving reported while synthesizing the constraints. arkworks reports no
+/// progress through the FFTs and MSMs that follow, so the rest arrives in one step at the end.
+pub const SYNTHESIS_SHARE: f64 = 0.5;
+
+// Synthesis reports at most this many times, however many constraints there are
+const SYNTHESIS_STEPS: usize = 100;
+
+const BAR_WIDTH: usize = 30;
+
+/// Callback invoked with an estimate of the fraction of setup or proving done, from 0 to 1.
+#[derive(Clone)]
+pub struct Progress(Arc<dyn Fn(f64) + Send + Sync>);
+
+impl Progress {
+    pub fn new<F: Fn(f64) + Send + Sync + 'static>(callback: F) -> Self {
+        Progress(Arc::new(callback))
+    }
+
+    pub fn report(&self, fraction: f64) {
+        (self.0)(fraction.clamp(0.0, 1.0))
+    }
+
+    /// Report `done` of `total` constraints synthesized, every hundredth of them.
+    pub fn synthesized(&self, done: usize, total: usize) {
+        let step = |n: usize| n * SYNTHESIS_STEPS / total;
+        if done == total || step(done) != step(done - 1) {
+            self.report(SYNTHESIS_SHARE * done as f64 / total as f64);
+        }
+    }
+
+    pub fn finished(&self) {
+        self.report(1.0)
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Progress")
+    }
+}
+
+// Set once from the global `--progress` flag before any command runs
+static BAR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_bar(enabled: bool) {
+    BAR.store(enabled, Ordering::Relaxed);
+}
+
+/// A progress bar on stderr for `--progress`, or `None` without it.
+pub fn bar(label: &'static str) -> Option<Progress> {
+    BAR.load(Ordering::Relaxed).then(|| {
+        Progress::new(move |fraction| {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r{}", render(label, fraction));
+            if fraction >= 1.0 {
+                let _ = writeln!(stderr);
+            }
+        })
+    })
+}
+
+fn render(label: &str, fraction: f64) -> String {
+    let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+    format!(
+        "{} [{}{}] {:>3}%",
+        label,
+        "#".repeat(filled),
+        " ".repeat(BAR_WIDTH - filled),
+        (fraction * 100.0).round()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_synthesis_reports_are_throttled() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let progress = Progress::new(move |fraction| sink.lock().unwrap().push(fraction));
+
+        for done in 1..=1000 {
+            progress.synthesized(done, 1000);
+        }
+        progress.finished();
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), SYNTHESIS_STEPS + 1);
+        assert_eq!(reports[SYNTHESIS_STEPS - 1], SYNTHESIS_SHARE);
+        assert_eq!(reports[SYNTHESIS_STEPS], 1.0);
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("proving", 0.5),
+            format!("proving [{}{}]  50%", "#".repeat(15), " ".repeat(15))
+        );
+    }
+}
@@ -1,13 +1,33 @@
+use crate::field::{to_decimal_string, JsonCoefficient};
 use crate::header::Header;
-use ark_bn254::Bn254;
 use ark_ec::pairing::Pairing;
-use serde::{Deserialize, Deserializer};
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
 use std::collections::HashSet;
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, Read, Write};
 use std::str::FromStr; // Import IntoDeserializer trait
 
+// A term is either a `["coeff", var]` tuple or a `{"coeff": "...", "var": ...}` object. Either
+// coefficient may also be a json number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Term {
+    Tuple(JsonCoefficient, usize),
+    Object { coeff: JsonCoefficient, var: usize },
+}
+
+impl Term {
+    fn into_parts(self) -> (JsonCoefficient, usize) {
+        match self {
+            Term::Tuple(coeff, var) => (coeff, var),
+            Term::Object { coeff, var } => (coeff, var),
+        }
+    }
+}
+
 fn deserialize_coeff_tuple_vec<'de, D, E>(
     deserializer: D,
 ) -> Result<Vec<(E::ScalarField, usize)>, D::Error>
@@ -16,26 +36,59 @@ where
     E: Pairing,
     E::ScalarField: FromStr,
 {
-    let vec: Vec<(String, usize)> = Deserialize::deserialize(deserializer)?;
+    let vec: Vec<Term> = Deserialize::deserialize(deserializer)?;
     vec.into_iter()
+        .map(Term::into_parts)
         .map(|(coeff, var)| {
-            E::ScalarField::from_str(&coeff)
+            coeff
+                .parse()
                 .map(|field_element| (field_element, var))
-                .map_err(|_| serde::de::Error::custom("Error in ScalarField parser"))
+                .map_err(serde::de::Error::custom)
         })
         .collect()
 }
 
-#[derive(Deserialize, Debug, Clone)]
+fn serialize_coeff_tuple_vec<S, E>(
+    terms: &[(E::ScalarField, usize)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    E: Pairing,
+{
+    serializer.collect_seq(
+        terms
+            .iter()
+            .map(|(coeff, var)| (to_decimal_string(coeff), *var)),
+    )
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct R1C<E: Pairing> {
-    #[serde(rename = "A", deserialize_with = "deserialize_coeff_tuple_vec::<_, E>")]
+    #[serde(
+        rename = "A",
+        serialize_with = "serialize_coeff_tuple_vec::<_, E>",
+        deserialize_with = "deserialize_coeff_tuple_vec::<_, E>"
+    )]
     pub a: Vec<(E::ScalarField, usize)>,
-    #[serde(rename = "B", deserialize_with = "deserialize_coeff_tuple_vec::<_, E>")]
+    #[serde(
+        rename = "B",
+        serialize_with = "serialize_coeff_tuple_vec::<_, E>",
+        deserialize_with = "deserialize_coeff_tuple_vec::<_, E>"
+    )]
     pub b: Vec<(E::ScalarField, usize)>,
-    #[serde(rename = "C", deserialize_with = "deserialize_coeff_tuple_vec::<_, E>")]
+    #[serde(
+        rename = "C",
+        serialize_with = "serialize_coeff_tuple_vec::<_, E>",
+        deserialize_with = "deserialize_coeff_tuple_vec::<_, E>"
+    )]
     pub c: Vec<(E::ScalarField, usize)>,
+    /// Where the constraint came from, e.g. `foo.circom:42`, for error messages. Also read as `label`.
+    #[serde(default, alias = "label", skip_serializing_if = "Option::is_none")]
+    pub src: Option<String>,
 }
 
+#[derive(Clone, Debug)]
 pub struct R1CSFile<E: Pairing> {
     pub header: Header,
     pub constraints: Vec<R1C<E>>,
@@ -43,6 +96,7 @@ pub struct R1CSFile<E: Pairing> {
 
 #[derive(Clone, Debug)]
 pub struct R1CS<E: Pairing> {
+    pub constant_index: usize,
     pub input_variables: Vec<usize>,
     pub witness_variables: Vec<usize>,
     pub constraints: Vec<R1C<E>>,
@@ -50,8 +104,10 @@ pub struct R1CS<E: Pairing> {
 
 impl<E: Pairing> From<R1CSFile<E>> for R1CS<E> {
     fn from(file: R1CSFile<E>) -> Self {
-        // The 0 variable is always the constant 1
-        let var_set: HashSet<usize> = (1..file.header.n_variables).collect();
+        let constant_index = file.header.constant_index();
+        let var_set: HashSet<usize> = (0..file.header.n_variables)
+            .filter(|v| *v != constant_index)
+            .collect();
         let input_vars_set: HashSet<usize> =
             file.header.input_variables.clone().into_iter().collect();
 
@@ -63,6 +119,7 @@ impl<E: Pairing> From<R1CSFile<E>> for R1CS<E> {
         witness_variables.sort();
 
         R1CS {
+            constant_index,
             input_variables,
             witness_variables,
             constraints: file.constraints,
@@ -70,7 +127,14 @@ impl<E: Pairing> From<R1CSFile<E>> for R1CS<E> {
     }
 }
 
-pub fn parse_r1cs_file(reader: BufReader<File>) -> io::Result<R1CSFile<Bn254>> {
+/// True if an R1CS file starting with `first_line` has its header on the last line instead.
+/// Streaming generators write it last, once the counts are known.
+pub fn header_is_last(first_line: &str) -> bool {
+    serde_json::from_str::<Value>(first_line).is_ok_and(|line| line.get("A").is_some())
+}
+
+/// Parse an R1CS file with its header on either the first or the last line.
+pub fn parse_r1cs_file<E: Pairing, R: BufRead>(reader: R) -> io::Result<R1CSFile<E>> {
     let mut lines = reader.lines();
 
     // Read and parse header line
@@ -78,18 +142,416 @@ pub fn parse_r1cs_file(reader: BufReader<File>) -> io::Result<R1CSFile<Bn254>> {
         io::ErrorKind::NotFound,
         "Header line not found",
     ))??;
-    let header: Header = serde_json::from_str(&header_line).expect("Error parsing header");
+    if header_is_last(&header_line) {
+        return parse_header_last(header_line, lines);
+    }
+    let header: Header = serde_json::from_str(&header_line).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Error parsing header: {}", e),
+        )
+    })?;
 
     // Read and parse constraints
-    let constraints: Vec<R1C<Bn254>> = lines
-        .map(|line| {
-            let line = line.expect("Error reading line");
-            serde_json::from_str(&line).expect("Error parsing constraint")
+    let mut constraints = Vec::new();
+    for line in lines {
+        constraints.push(parse_constraint(&line?, constraints.len())?);
+    }
+    check_constraint_count(&header, constraints.len())?;
+
+    Ok(R1CSFile {
+        header,
+        constraints,
+    })
+}
+
+fn parse_constraint<E: Pairing>(line: &str, index: usize) -> io::Result<R1C<E>> {
+    serde_json::from_str(line).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Error parsing constraint {}: {}", index, e),
+        )
+    })
+}
+
+/// Check a header declares the number of constraints a file was found to hold, wherever in the
+/// file the header is.
+pub fn check_constraint_count(header: &Header, n_constraints: usize) -> io::Result<()> {
+    if header.n_constraints != n_constraints {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Header declares {} constraints but the file contains {}",
+                header.n_constraints, n_constraints
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_header_last<E: Pairing, I: Iterator<Item = io::Result<String>>>(
+    first_line: String,
+    lines: I,
+) -> io::Result<R1CSFile<E>> {
+    let mut constraints = Vec::new();
+    let mut line = first_line;
+    // Every line is a constraint until the one without a successor
+    for next in lines {
+        constraints.push(parse_constraint(&line, constraints.len())?);
+        line = next?;
+    }
+
+    let header: Header = serde_json::from_str(&line).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The R1CS file starts with a constraint, but its last line is not a header: {}",
+                e
+            ),
+        )
+    })?;
+    check_constraint_count(&header, constraints.len())?;
+
+    Ok(R1CSFile {
+        header,
+        constraints,
+    })
+}
+
+/// Write an R1CS in the same JSONL layout `parse_r1cs_file` reads: a header line followed by
+/// one constraint per line.
+pub fn write_r1cs_file<E: Pairing, W: Write>(file: &R1CSFile<E>, mut writer: W) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, &file.header)?;
+    writeln!(writer)?;
+    for constraint in &file.constraints {
+        serde_json::to_writer(&mut writer, constraint)?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// The first bytes of circom's binary `.r1cs` format.
+pub const BINARY_MAGIC: &[u8; 4] = b"r1cs";
+
+// Sections of the binary format that are read. The others, like the wire to label map, are
+// skipped.
+const HEADER_SECTION: u32 = 1;
+const CONSTRAINTS_SECTION: u32 = 2;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_section<R: Read>(reader: &mut R, size: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(size).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != size {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+// The header section, and the width of the field elements in the constraints section
+fn read_binary_header(mut section: &[u8]) -> io::Result<(Header, usize)> {
+    let field_size = read_u32(&mut section)? as usize;
+    // Checked before allocating, and so that a zero width can't make every term read nothing
+    if field_size == 0 || field_size % 8 != 0 || field_size > section.len() {
+        return Err(invalid(format!(
+            "Binary R1CS header has an invalid field size of {} bytes",
+            field_size
+        )));
+    }
+    let mut prime = vec![0u8; field_size];
+    section.read_exact(&mut prime)?;
+    let n_wires = read_u32(&mut section)? as usize;
+    let n_public_outputs = read_u32(&mut section)? as usize;
+    let n_public_inputs = read_u32(&mut section)? as usize;
+    let _n_private_inputs = read_u32(&mut section)?;
+    let _n_labels = read_u64(&mut section)?;
+    let n_constraints = read_u32(&mut section)? as usize;
+
+    // Wire 0 is the constant one, followed by the public outputs, then the public inputs
+    let header = Header {
+        extension_degree: 1,
+        field_characteristic: BigUint::from_bytes_le(&prime),
+        curve: None,
+        input_variables: (1..=n_public_outputs + n_public_inputs).collect(),
+        n_constraints,
+        n_variables: n_wires,
+        output_variables: (1..=n_public_outputs).collect(),
+        constant_index: None,
+    };
+    Ok((header, field_size))
+}
+
+fn read_binary_lc<F: PrimeField>(
+    section: &mut &[u8],
+    field_size: usize,
+) -> io::Result<Vec<(F, usize)>> {
+    let n_terms = read_u32(section)?;
+    let mut coeff = vec![0u8; field_size];
+    (0..n_terms)
+        .map(|_| -> io::Result<(F, usize)> {
+            let wire = read_u32(section)? as usize;
+            section.read_exact(&mut coeff)?;
+            Ok((F::from_le_bytes_mod_order(&coeff), wire))
+        })
+        .collect()
+}
+
+/// Parse circom's binary `.r1cs` format: the magic `r1cs`, the version, then sections of which
+/// the header and the constraints are read. Coefficients are little-endian integers as wide as
+/// the header's field size. Circom makes its outputs and public inputs, in that order after the
+/// constant one, the public inputs of the R1CS.
+pub fn parse_r1cs_binary<E: Pairing, R: Read>(mut reader: R) -> io::Result<R1CSFile<E>> {
+    let truncated = |e: io::Error| match e.kind() {
+        io::ErrorKind::UnexpectedEof => invalid("Binary R1CS file is truncated".to_string()),
+        _ => e,
+    };
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic).map_err(truncated)?;
+    if &magic != BINARY_MAGIC {
+        return Err(invalid(
+            "Not a binary R1CS file, which starts with `r1cs`".to_string(),
+        ));
+    }
+    let version = read_u32(&mut reader).map_err(truncated)?;
+    if version != 1 {
+        return Err(invalid(format!(
+            "Unsupported binary R1CS version {}, expected 1",
+            version
+        )));
+    }
+
+    // The sections may come in any order, so the constraints are kept as bytes until the
+    // header gives their width
+    let mut header = None;
+    let mut constraints = None;
+    let n_sections = read_u32(&mut reader).map_err(truncated)?;
+    for _ in 0..n_sections {
+        let section_type = read_u32(&mut reader).map_err(truncated)?;
+        let size = read_u64(&mut reader).map_err(truncated)?;
+        match section_type {
+            HEADER_SECTION => {
+                let section = read_section(&mut reader, size).map_err(truncated)?;
+                header = Some(read_binary_header(&section).map_err(truncated)?);
+            }
+            CONSTRAINTS_SECTION => {
+                constraints = Some(read_section(&mut reader, size).map_err(truncated)?)
+            }
+            _ => {
+                read_section(&mut reader, size).map_err(truncated)?;
+            }
+        }
+    }
+
+    let (header, field_size) =
+        header.ok_or_else(|| invalid("Binary R1CS file has no header section".to_string()))?;
+    let bytes = constraints
+        .ok_or_else(|| invalid("Binary R1CS file has no constraints section".to_string()))?;
+
+    let mut section = bytes.as_slice();
+    let constraints = (0..header.n_constraints)
+        .map(|_| -> io::Result<R1C<E>> {
+            Ok(R1C {
+                a: read_binary_lc(&mut section, field_size)?,
+                b: read_binary_lc(&mut section, field_size)?,
+                c: read_binary_lc(&mut section, field_size)?,
+                src: None,
+            })
         })
-        .collect();
+        .collect::<io::Result<Vec<_>>>()
+        .map_err(truncated)?;
+    if !section.is_empty() {
+        return Err(invalid(format!(
+            "Binary R1CS constraints section has {} bytes after its {} constraints",
+            section.len(),
+            header.n_constraints
+        )));
+    }
 
     Ok(R1CSFile {
         header,
         constraints,
     })
 }
+
+/// Parse an R1CS file in either format, telling circom's binary format by its magic bytes.
+pub fn parse_r1cs<E: Pairing, R: BufRead>(mut reader: R) -> io::Result<R1CSFile<E>> {
+    if reader.fill_buf()?.starts_with(BINARY_MAGIC) {
+        parse_r1cs_binary(reader)
+    } else {
+        parse_r1cs_file(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::BigInteger;
+
+    #[test]
+    fn test_header_last() {
+        let file = include_str!("../test/resources/prog-r1cs.jsonl");
+        let (header, constraints) = file.split_once('\n').unwrap();
+        let streamed = format!("{}{}\n", constraints, header);
+
+        let first = parse_r1cs_file::<Bn254, _>(file.as_bytes()).unwrap();
+        let last = parse_r1cs_file::<Bn254, _>(streamed.as_bytes()).unwrap();
+        assert_eq!(last.header.n_variables, first.header.n_variables);
+        assert_eq!(last.constraints.len(), 1);
+        assert_eq!(
+            crate::matrix_cache::r1cs_sha256(streamed.as_bytes()).unwrap(),
+            crate::matrix_cache::r1cs_sha256(file.as_bytes()).unwrap()
+        );
+
+        // The counts in a trailing header are checked against what was read
+        let short = format!(
+            "{}{}",
+            constraints,
+            header.replace("\"n_constraints\":1", "\"n_constraints\":2")
+        );
+        let err = parse_r1cs_file::<Bn254, _>(short.as_bytes()).unwrap_err();
+        assert!(err.to_string().contains("declares 2 constraints"));
+        assert!(parse_r1cs_file::<Bn254, _>(constraints.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_header_first_errors() {
+        let file = include_str!("../test/resources/prog-r1cs.jsonl");
+        let parse = |file: &str| parse_r1cs_file::<Bn254, _>(file.as_bytes()).unwrap_err();
+
+        // The count in a leading header is checked just like a trailing one
+        let long = file.replace("\"n_constraints\":1", "\"n_constraints\":2");
+        assert!(parse(&long).to_string().contains("declares 2 constraints"));
+        let err = parse(&format!("{}{{\"A\":", file));
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("Error parsing constraint 1"));
+        let err = parse(&file.replacen("{", "[", 1));
+        assert!(err.to_string().contains("Error parsing header"));
+    }
+
+    // The fixture circuit as circom would write it, with variable 1 a public input
+    fn binary_fixture() -> Vec<u8> {
+        let file = include_str!("../test/resources/prog-r1cs.jsonl");
+        let constraints = parse_r1cs_file::<Bn254, _>(file.as_bytes())
+            .unwrap()
+            .constraints;
+
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(Fr::MODULUS.to_bytes_le());
+        for count in [3u32, 0, 1, 1] {
+            header.extend(count.to_le_bytes());
+        }
+        header.extend(3u64.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+
+        let mut section = Vec::new();
+        for constraint in &constraints {
+            for lc in [&constraint.a, &constraint.b, &constraint.c] {
+                section.extend((lc.len() as u32).to_le_bytes());
+                for (coeff, var) in lc {
+                    section.extend((*var as u32).to_le_bytes());
+                    section.extend(coeff.into_bigint().to_bytes_le());
+                }
+            }
+        }
+
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        // The constraints first, which the format allows
+        for (section_type, contents) in [(CONSTRAINTS_SECTION, section), (HEADER_SECTION, header)] {
+            bytes.extend(section_type.to_le_bytes());
+            bytes.extend((contents.len() as u64).to_le_bytes());
+            bytes.extend(contents);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_binary_r1cs() {
+        let file = include_str!("../test/resources/prog-r1cs.jsonl");
+        let jsonl = parse_r1cs_file::<Bn254, _>(file.as_bytes()).unwrap();
+        let bytes = binary_fixture();
+
+        let binary = parse_r1cs::<Bn254, _>(bytes.as_slice()).unwrap();
+        assert_eq!(binary.header.field_characteristic, Fr::MODULUS.into());
+        assert_eq!(binary.header.input_variables, vec![1]);
+        assert_eq!(binary.header.n_variables, 3);
+        assert_eq!(binary.constraints.len(), 1);
+        assert_eq!(binary.constraints[0].a, jsonl.constraints[0].a);
+        assert_eq!(binary.constraints[0].b, jsonl.constraints[0].b);
+        assert_eq!(binary.constraints[0].c, jsonl.constraints[0].c);
+
+        // JSONL is still told apart from the binary format
+        assert_eq!(
+            parse_r1cs::<Bn254, _>(file.as_bytes())
+                .unwrap()
+                .header
+                .n_variables,
+            3
+        );
+
+        let err = parse_r1cs_binary::<Bn254, _>(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert!(err.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn test_corrupt_binary_r1cs() {
+        let bytes = binary_fixture();
+        // The header section comes last, its field size 64 bytes from the end
+        let field_size_at = bytes.len() - 64;
+        for field_size in [0u32, 7, u32::MAX] {
+            let mut corrupt = bytes.clone();
+            corrupt[field_size_at..field_size_at + 4].copy_from_slice(&field_size.to_le_bytes());
+            let err = parse_r1cs_binary::<Bn254, _>(corrupt.as_slice()).unwrap_err();
+            assert!(err.to_string().contains("invalid field size"), "{}", err);
+        }
+
+        // A byte past the last constraint, with the constraints section grown to hold it
+        let size = u64::from_le_bytes(bytes[16..24].try_into().unwrap());
+        let mut corrupt = bytes.clone();
+        corrupt[16..24].copy_from_slice(&(size + 1).to_le_bytes());
+        corrupt.insert(24 + size as usize, 0);
+        let err = parse_r1cs_binary::<Bn254, _>(corrupt.as_slice()).unwrap_err();
+        assert!(
+            err.to_string().contains("1 bytes after its 1 constraints"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_tuple_and_object_terms() {
+        let tuples: R1C<Bn254> =
+            serde_json::from_str(r#"{"A":[["2",3]],"B":[["1",0]],"C":[["5",1],["7",2]]}"#).unwrap();
+        let objects: R1C<Bn254> = serde_json::from_str(
+            r#"{"A":[{"coeff":"2","var":3}],"B":[{"coeff":"1","var":0}],"C":[{"coeff":"5","var":1},["7",2]]}"#,
+        )
+        .unwrap();
+        let numbers: R1C<Bn254> =
+            serde_json::from_str(r#"{"A":[[2,3]],"B":[{"coeff":1,"var":0}],"C":[[5,1],["7",2]]}"#)
+                .unwrap();
+
+        for constraint in [tuples, objects, numbers] {
+            assert_eq!(constraint.a, vec![(Fr::from(2u64), 3)]);
+            assert_eq!(constraint.b, vec![(Fr::from(1u64), 0)]);
+            assert_eq!(constraint.c, vec![(Fr::from(5u64), 1), (Fr::from(7u64), 2)]);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::r1cs::R1C;
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, Zero};
+use rand::seq::index;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// A constraint as one sparse row of `[A | B | C]`, columns keyed by matrix and variable
+type Row<F> = BTreeMap<(usize, usize), F>;
+
+fn row<E: Pairing>(constraint: &R1C<E>) -> Row<E::ScalarField> {
+    let mut row = BTreeMap::new();
+    for (matrix, terms) in [&constraint.a, &constraint.b, &constraint.c]
+        .into_iter()
+        .enumerate()
+    {
+        for (coeff, var) in terms {
+            *row.entry((matrix, *var))
+                .or_insert_with(E::ScalarField::zero) += coeff;
+        }
+    }
+    row.retain(|_, coeff| !coeff.is_zero());
+    row
+}
+
+// Gaussian elimination one row at a time: each row is reduced by the pivots found so far, keyed by
+// their leading column, and becomes a pivot itself unless nothing is left of it
+fn rank<F: Field>(rows: impl Iterator<Item = Row<F>>) -> usize {
+    let mut pivots: BTreeMap<(usize, usize), Row<F>> = BTreeMap::new();
+    for mut row in rows {
+        while let Some((&lead, &coeff)) = row.iter().next() {
+            let pivot = match pivots.get(&lead) {
+                Some(pivot) => pivot,
+                None => {
+                    let scale = coeff.inverse().expect("zero entries are removed");
+                    row.values_mut().for_each(|value| *value *= scale);
+                    pivots.insert(lead, row);
+                    break;
+                }
+            };
+            // Pivots are scaled to lead with one, so this cancels the leading entry
+            for (column, value) in pivot {
+                let entry = row.entry(*column).or_insert_with(F::zero);
+                *entry -= coeff * value;
+                if entry.is_zero() {
+                    row.remove(column);
+                }
+            }
+        }
+    }
+    pivots.len()
+}
+
+// Constraints identical to an earlier one, up to the order of A and B. Unlike other dependent rows
+// these are implied by the constraint they repeat.
+fn duplicates<'a, E: Pairing>(constraints: impl Iterator<Item = &'a R1C<E>>) -> usize {
+    let mut seen = BTreeSet::new();
+    constraints
+        .filter(|constraint| {
+            let mut matrices = [Vec::new(), Vec::new(), Vec::new()];
+            for ((matrix, var), coeff) in row(constraint) {
+                matrices[matrix].push((var, coeff));
+            }
+            let [a, b, c] = matrices;
+            let (a, b) = if a <= b { (a, b) } else { (b, a) };
+            !seen.insert((a, b, c))
+        })
+        .count()
+}
+
+/// The linear rank of the constraints as rows of the matrix `[A | B | C]`, and how many of them are
+/// duplicates. A row that is linearly dependent on others is not implied by them, since
+/// `A·B = C` is quadratic: a multiple or a sum of other constraints may still be needed. Only the
+/// duplicates can be removed without changing what the circuit proves.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Redundancy {
+    pub n_constraints: usize,
+    /// Constraints the rank was computed over, all of them unless sampled
+    pub checked: usize,
+    pub rank: usize,
+    /// Checked constraints identical to an earlier checked one, up to swapping A and B
+    pub duplicates: usize,
+}
+
+impl Redundancy {
+    /// The exact rank over every constraint. Elimination fills in the sparse rows, so this can
+    /// take very long for large circuits.
+    pub fn exact<E: Pairing>(constraints: &[R1C<E>]) -> Self {
+        Redundancy {
+            n_constraints: constraints.len(),
+            checked: constraints.len(),
+            rank: rank(constraints.iter().map(row)),
+            duplicates: duplicates(constraints.iter()),
+        }
+    }
+
+    /// The rank over `samples` constraints chosen uniformly at random.
+    pub fn sampled<E: Pairing, R: Rng>(
+        constraints: &[R1C<E>],
+        samples: usize,
+        rng: &mut R,
+    ) -> Self {
+        let samples = samples.min(constraints.len());
+        let mut indices = index::sample(rng, constraints.len(), samples).into_vec();
+        indices.sort_unstable();
+        Redundancy {
+            n_constraints: constraints.len(),
+            checked: samples,
+            rank: rank(indices.iter().map(|&i| row(&constraints[i]))),
+            duplicates: duplicates(indices.iter().map(|&i| &constraints[i])),
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.checked == self.n_constraints
+    }
+
+    /// Linearly dependent constraints among those checked. A duplicate with A and B swapped is
+    /// not one, as its row differs.
+    pub fn redundant(&self) -> usize {
+        self.checked - self.rank
+    }
+
+    /// The share of dependent constraints in the sample, applied to the whole circuit. A
+    /// dependency only shows when every constraint in it was sampled, so this underestimates.
+    pub fn estimated_redundant(&self) -> usize {
+        match self.checked {
+            0 => 0,
+            checked => self.redundant() * self.n_constraints / checked,
+        }
+    }
+}
+
+impl fmt::Display for Redundancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "constraints: {}", self.n_constraints)?;
+        if self.is_exact() {
+            writeln!(f, "rank: {}", self.rank)?;
+            writeln!(f, "linearly dependent constraints: {}", self.redundant())?;
+            write!(f, "duplicate constraints: {}", self.duplicates)
+        } else {
+            writeln!(f, "sampled constraints: {}", self.checked)?;
+            writeln!(f, "rank of sample: {}", self.rank)?;
+            writeln!(f, "linearly dependent in sample: {}", self.redundant())?;
+            writeln!(f, "duplicates in sample: {}", self.duplicates)?;
+            write!(
+                f,
+                "estimated linearly dependent constraints: at least {}",
+                self.estimated_redundant()
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn constraint(a: u64, b: u64, c: u64, var: usize) -> R1C<Bn254> {
+        R1C {
+            a: vec![(Fr::from(a), var)],
+            b: vec![(Fr::from(b), var)],
+            c: vec![(Fr::from(c), var + 1), (Fr::zero(), 0)],
+            src: None,
+        }
+    }
+
+    #[test]
+    fn test_dependent_constraints_are_found() {
+        let constraints = vec![
+            constraint(1, 1, 1, 1),
+            constraint(1, 1, 1, 2),
+            // A duplicate, a multiple and a sum of the first two
+            constraint(1, 1, 1, 1),
+            constraint(3, 3, 3, 2),
+            R1C {
+                a: vec![(Fr::from(1u64), 1), (Fr::from(1u64), 2)],
+                b: vec![(Fr::from(1u64), 1), (Fr::from(1u64), 2)],
+                c: vec![(Fr::from(1u64), 2), (Fr::from(1u64), 3)],
+                src: None,
+            },
+            constraint(1, 2, 1, 1),
+        ];
+
+        let exact = Redundancy::exact(&constraints);
+        assert_eq!(exact.rank, 3);
+        assert_eq!(exact.redundant(), 3);
+        // Only the duplicate is implied: the multiple and the sum are dependent rows, but
+        // 9·A·B = 3·C does not follow from A·B = C
+        assert_eq!(exact.duplicates, 1);
+        assert!(exact
+            .to_string()
+            .contains("linearly dependent constraints: 3\nduplicate constraints: 1"));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let all = Redundancy::sampled(&constraints, 100, &mut rng);
+        assert_eq!(all, exact);
+        let sampled = Redundancy::sampled(&constraints, 3, &mut rng);
+        assert_eq!(sampled.checked, 3);
+        assert_eq!(sampled.estimated_redundant(), sampled.redundant() * 2);
+    }
+
+    #[test]
+    fn test_duplicates_up_to_swapping_a_and_b() {
+        let mut swapped = constraint(1, 2, 1, 1);
+        std::mem::swap(&mut swapped.a, &mut swapped.b);
+        // Terms are merged and zero ones dropped before comparing
+        let mut split = constraint(1, 2, 1, 1);
+        split.c = vec![(Fr::from(1u64), 2), (Fr::zero(), 1)];
+        let constraints = vec![
+            constraint(1, 2, 1, 1),
+            swapped,
+            split,
+            constraint(2, 2, 1, 1),
+        ];
+
+        let exact = Redundancy::exact(&constraints);
+        assert_eq!(exact.duplicates, 2);
+        // Swapping A and B gives a different row, so only the split copy is linearly dependent
+        assert_eq!(exact.redundant(), 1);
+    }
+}
@@ -0,0 +1,51 @@
+use std::io;
+use std::path::Path;
+
+/// The URL, if a path argument is actually an http(s) URL.
+pub fn as_url(path: &Path) -> Option<&str> {
+    path.to_str()
+        .filter(|s| s.starts_with("http://") || s.starts_with("https://"))
+}
+
+/// Download the whole body of `url` into memory.
+#[cfg(feature = "http")]
+pub fn fetch(url: &str) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let response = ureq::get(url).call().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to fetch {}: {}", url, e),
+        )
+    })?;
+
+    let mut bytes = Vec::new();
+    response.into_reader().read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(not(feature = "http"))]
+pub fn fetch(url: &str) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "Cannot fetch {}: arkworks-bridge was built without the `http` feature",
+            url
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_url() {
+        assert_eq!(
+            as_url(Path::new("https://example.com/vk")),
+            Some("https://example.com/vk")
+        );
+        assert_eq!(as_url(Path::new("test/resources/prog-vk")), None);
+        assert_eq!(as_url(Path::new("ftp://example.com/vk")), None);
+    }
+}
@@ -0,0 +1,100 @@
+use ethers_core::types::Address;
+use ethers_core::utils::hex;
+use serde_json::{json, Value};
+use std::io;
+
+/// JSON-RPC request for an `eth_call` of `data` on the contract at `to`, against the latest block.
+#[cfg_attr(not(feature = "rpc"), allow(dead_code))]
+pub fn eth_call_request(to: &Address, data: &[u8]) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [
+            {"to": format!("{:?}", to), "data": format!("0x{}", hex::encode(data))},
+            "latest"
+        ]
+    })
+}
+
+/// The bytes an `eth_call` returned, or its error. A reverted call is an error, with the reason
+/// if the node gave one.
+#[cfg_attr(not(feature = "rpc"), allow(dead_code))]
+pub fn parse_eth_call_response(response: &str) -> io::Result<Vec<u8>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let response: Value = serde_json::from_str(response)
+        .map_err(|e| invalid(format!("RPC response is not JSON: {}", e)))?;
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map_or_else(|| error.to_string(), str::to_string);
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("eth_call failed: {}", message),
+        ));
+    }
+
+    let result = response
+        .get("result")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid(format!("RPC response has no result: {}", response)))?;
+    hex::decode(result.trim_start_matches("0x"))
+        .map_err(|e| invalid(format!("eth_call result is not hex: {}", e)))
+}
+
+/// Run an `eth_call` through the JSON-RPC endpoint at `url`.
+#[cfg(feature = "rpc")]
+pub fn eth_call(url: &str, to: &Address, data: &[u8]) -> io::Result<Vec<u8>> {
+    let response = ureq::post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&eth_call_request(to, data).to_string())
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                format!("Failed to call {}: {}", url, e),
+            )
+        })?;
+
+    parse_eth_call_response(&response.into_string()?)
+}
+
+#[cfg(not(feature = "rpc"))]
+pub fn eth_call(url: &str, _to: &Address, _data: &[u8]) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!(
+            "Cannot call {}: arkworks-bridge was built without the `rpc` feature",
+            url
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eth_call_request_and_response() {
+        let to: Address = "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+            .parse()
+            .unwrap();
+        let request = eth_call_request(&to, &[0xab, 0x01]);
+        assert_eq!(request["method"], "eth_call");
+        assert_eq!(
+            request["params"][0]["to"],
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+        assert_eq!(request["params"][0]["data"], "0xab01");
+
+        let ok = r#"{"jsonrpc":"2.0","id":1,"result":"0x0001"}"#;
+        assert_eq!(parse_eth_call_response(ok).unwrap(), vec![0, 1]);
+
+        let reverted = r#"{"jsonrpc":"2.0","id":1,"error":{"code":3,"message":"execution reverted: verifier-bad-input"}}"#;
+        let err = parse_eth_call_response(reverted).unwrap_err();
+        assert!(err.to_string().contains("verifier-bad-input"));
+
+        assert!(parse_eth_call_response(r#"{"jsonrpc":"2.0","id":1}"#).is_err());
+    }
+}
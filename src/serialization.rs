@@ -0,0 +1,491 @@
+use crate::r1cs::R1CS;
+use crate::strict;
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate,
+};
+use std::io::{self, Cursor, Read, Write};
+
+// Every serialized vector is prefixed with its length as a u64
+const LENGTH_PREFIX_SIZE: usize = 8;
+
+/// Start of an artifact in a versioned format, followed by one format version byte. Artifacts
+/// without it are in the original tag-less uncompressed layout, which stays readable.
+pub const FORMAT_TAG: &[u8; 4] = b"ARKB";
+
+/// Tagged format version written under `--tag-artifacts`: the tag and version are followed by the
+/// major and minor version of ark-serialize that wrote the uncompressed payload.
+pub const TAGGED_VERSION: u8 = 3;
+
+/// Major and minor version of the ark-serialize dependency, recorded in version 3 artifacts.
+/// Keep it in step with Cargo.toml.
+pub const ARK_SERIALIZE_VERSION: [u8; 2] = [0, 4];
+
+/// How artifacts are written and read. The CLI sets these from its global `--tag-artifacts` and
+/// `--strict` flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Write artifacts behind a version 3 format tag
+    pub tagged: bool,
+    /// Refuse, rather than warn about, a tagged artifact from another ark-serialize version
+    pub strict: bool,
+}
+
+// Layout of the payload for each tagged format version. Only the version written is read.
+fn tagged_layout(version: u8) -> io::Result<Compress> {
+    match version {
+        TAGGED_VERSION => Ok(Compress::No),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Unsupported artifact format version {}, only version {} is read",
+                version, TAGGED_VERSION
+            ),
+        )),
+    }
+}
+
+pub type Peeked<R> = io::Chain<Cursor<Vec<u8>>, R>;
+
+/// Read the format tag if there is one, returning the layout of the payload that follows.
+/// Otherwise the bytes read while looking for it are put back in front of the payload.
+pub fn detect_layout<R: Read>(
+    mut reader: R,
+    options: Options,
+) -> io::Result<(Compress, Peeked<R>)> {
+    let tagged_len = FORMAT_TAG.len() + 1;
+    let mut prefix = vec![0u8; tagged_len];
+    let mut filled = 0;
+    while filled < tagged_len {
+        match reader.read(&mut prefix[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    prefix.truncate(filled);
+
+    if filled == tagged_len && prefix.starts_with(FORMAT_TAG) {
+        let version = prefix[FORMAT_TAG.len()];
+        let layout = tagged_layout(version)?;
+        let mut written = [0u8; 2];
+        reader.read_exact(&mut written).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Artifact format tag is truncated",
+            )
+        })?;
+        check_ark_version(written, options)?;
+        Ok((layout, Cursor::new(Vec::new()).chain(reader)))
+    } else {
+        Ok((Compress::No, Cursor::new(prefix).chain(reader)))
+    }
+}
+
+// Artifacts from another ark-serialize release may have a different layout
+fn check_ark_version(written: [u8; 2], options: Options) -> io::Result<()> {
+    if written != ARK_SERIALIZE_VERSION {
+        strict::warn(options.strict, &format!(
+            "Artifact was written with ark-serialize {}.{} but this build uses {}.{}, and may not read it correctly",
+            written[0], written[1], ARK_SERIALIZE_VERSION[0], ARK_SERIALIZE_VERSION[1]
+        ))?;
+    }
+    Ok(())
+}
+
+/// Serialize an arkworks value uncompressed, behind a version 3 format tag if `options.tagged`.
+pub fn serialize_to<T: CanonicalSerialize, W: Write>(
+    value: &T,
+    mut writer: W,
+    description: &str,
+    options: Options,
+) -> io::Result<()> {
+    if options.tagged {
+        writer.write_all(FORMAT_TAG)?;
+        writer.write_all(&[TAGGED_VERSION])?;
+        writer.write_all(&ARK_SERIALIZE_VERSION)?;
+    }
+    value.serialize_uncompressed(&mut writer).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("Failed to serialize {}: {}", description, e),
+        )
+    })
+}
+
+/// Deserialize an arkworks value, either tagged with `FORMAT_TAG` or in the tag-less
+/// uncompressed layout. Running out of data is reported as a truncated file, including
+/// `expected_size` when the caller knows how large the uncompressed artifact should be.
+pub fn deserialize_from<T: CanonicalDeserialize, R: Read>(
+    reader: R,
+    description: &str,
+    expected_size: Option<usize>,
+    options: Options,
+) -> io::Result<T> {
+    deserialize_with(reader, description, expected_size, options, Validate::Yes)
+}
+
+/// Like `deserialize_from`, but without checking that points are on the curve and in the
+/// prime-order subgroup, for reporting on them separately.
+pub fn deserialize_unchecked_from<T: CanonicalDeserialize, R: Read>(
+    reader: R,
+    description: &str,
+    expected_size: Option<usize>,
+    options: Options,
+) -> io::Result<T> {
+    deserialize_with(reader, description, expected_size, options, Validate::No)
+}
+
+fn deserialize_with<T: CanonicalDeserialize, R: Read>(
+    reader: R,
+    description: &str,
+    expected_size: Option<usize>,
+    options: Options,
+    validate: Validate,
+) -> io::Result<T> {
+    let (layout, reader) = detect_layout(reader, options)?;
+    let expected_size = expected_size.filter(|_| layout == Compress::No);
+    let mut reader = CountingReader {
+        inner: reader,
+        count: 0,
+    };
+
+    T::deserialize_with_mode(&mut reader, layout, validate).map_err(|e| match e {
+        SerializationError::IoError(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            let expected = match expected_size {
+                Some(size) => format!("expected at least {} bytes", size),
+                None => "expected more data".to_string(),
+            };
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Failed to deserialize {}: file appears truncated or corrupt: {}, found {}",
+                    description, expected, reader.count
+                ),
+            )
+        }
+        _ => io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to deserialize {}: {}", description, e),
+        ),
+    })
+}
+
+struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+}
+
+/// Write `value` as a record prefixed with its length as a little-endian u64, so several values
+/// can share one append-only file.
+pub fn append_record<T: CanonicalSerialize, W: Write>(
+    value: &T,
+    mut writer: W,
+    description: &str,
+    options: Options,
+) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(value.uncompressed_size());
+    serialize_to(value, &mut bytes, description, options)?;
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(&bytes)
+}
+
+/// Read back every record written by `append_record`, in order.
+pub fn read_records<T: CanonicalDeserialize, R: Read>(
+    reader: R,
+    description: &str,
+    options: Options,
+) -> io::Result<Vec<T>> {
+    read_each_record(reader, description, options)?
+        .into_iter()
+        .collect()
+}
+
+/// Like `read_records`, but a record that can't be deserialized is returned as its error in
+/// place, and the records after it are still read. Only a truncated length prefix or a failed
+/// read stops the file being read.
+pub fn read_each_record<T: CanonicalDeserialize, R: Read>(
+    mut reader: R,
+    description: &str,
+    options: Options,
+) -> io::Result<Vec<io::Result<T>>> {
+    let mut records = Vec::new();
+    loop {
+        let mut length = [0u8; LENGTH_PREFIX_SIZE];
+        let mut filled = 0;
+        while filled < LENGTH_PREFIX_SIZE {
+            match reader.read(&mut length[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+        match filled {
+            0 => return Ok(records),
+            LENGTH_PREFIX_SIZE => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "Failed to deserialize {} record {}: length prefix is truncated",
+                        description,
+                        records.len()
+                    ),
+                ))
+            }
+        }
+        let length = u64::from_le_bytes(length) as usize;
+
+        let mut bytes = Vec::new();
+        (&mut reader).take(length as u64).read_to_end(&mut bytes)?;
+        let description = format!("{} record {}", description, records.len());
+        records.push(deserialize_from(
+            bytes.as_slice(),
+            &description,
+            Some(length),
+            options,
+        ));
+    }
+}
+
+pub fn proof_size<E: Pairing>() -> usize {
+    Proof::<E>::default().uncompressed_size()
+}
+
+pub fn verifying_key_size<E: Pairing>(n_inputs: usize) -> usize {
+    VerifyingKey::<E> {
+        gamma_abc_g1: vec![E::G1Affine::default(); n_inputs + 1],
+        ..Default::default()
+    }
+    .uncompressed_size()
+}
+
+/// A lower bound on the size of the proving key for a circuit. The exact size depends on the
+/// evaluation domain chosen during setup, which is at least as large as the circuit.
+pub fn min_proving_key_size<E: Pairing>(r1cs: &R1CS<E>) -> usize {
+    min_proving_key_size_for::<E>(
+        r1cs.input_variables.len(),
+        r1cs.witness_variables.len(),
+        r1cs.constraints.len(),
+    )
+}
+
+/// `min_proving_key_size` from the circuit dimensions alone.
+pub fn min_proving_key_size_for<E: Pairing>(
+    n_inputs: usize,
+    n_witness: usize,
+    n_constraints: usize,
+) -> usize {
+    let g1 = E::G1Affine::default().uncompressed_size();
+    let g2 = E::G2Affine::default().uncompressed_size();
+
+    let n_variables = 1 + n_inputs + n_witness;
+
+    // beta_g1 and delta_g1, then the a, b_g1, b_g2, h and l queries
+    verifying_key_size::<E>(n_inputs)
+        + 2 * g1
+        + 5 * LENGTH_PREFIX_SIZE
+        + n_variables * (2 * g1 + g2)
+        + (n_constraints + n_inputs) * g1
+        + n_witness * g1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+    use ark_groth16::{prepare_verifying_key, Groth16, ProvingKey};
+
+    #[test]
+    fn test_truncated_verifying_key() {
+        let vk = VerifyingKey::<Bn254> {
+            gamma_abc_g1: vec![Default::default(); 2],
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        serialize_to(&vk, &mut bytes, "verifying key", Options::default()).unwrap();
+        assert_eq!(bytes.len(), verifying_key_size::<Bn254>(1));
+
+        bytes.truncate(bytes.len() - 10);
+        let err = deserialize_from::<VerifyingKey<Bn254>, _>(
+            bytes.as_slice(),
+            "verifying key",
+            Some(verifying_key_size::<Bn254>(1)),
+            Options::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(err.to_string().contains(&format!(
+            "truncated or corrupt: expected at least {} bytes",
+            verifying_key_size::<Bn254>(1)
+        )));
+    }
+
+    #[test]
+    fn test_reads_artifacts_from_v0_2_0() {
+        // Written by v0.2.0, which had no format tag. These must stay readable.
+        let pk: ProvingKey<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/pk").as_slice(),
+            "proving key",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+        let vk: VerifyingKey<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/vk").as_slice(),
+            "verifying key",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+        let proof: Proof<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/proof").as_slice(),
+            "proof",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+
+        assert_eq!(pk.vk, vk);
+        let inputs = [Fr::from(1u64)];
+        let pvk = prepare_verifying_key(&vk);
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &inputs).unwrap());
+    }
+
+    #[test]
+    fn test_tagged_artifacts() {
+        let vk: VerifyingKey<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/vk").as_slice(),
+            "verifying key",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+
+        // No version before 3 was ever written
+        for version in [1u8, 2] {
+            let mut bytes = FORMAT_TAG.to_vec();
+            bytes.push(version);
+            vk.serialize_uncompressed(&mut bytes).unwrap();
+            let err = deserialize_from::<VerifyingKey<Bn254>, _>(
+                bytes.as_slice(),
+                "vk",
+                None,
+                Options::default(),
+            )
+            .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut bytes = Vec::new();
+        let tagged = Options {
+            tagged: true,
+            ..Default::default()
+        };
+        serialize_to(&vk, &mut bytes, "verifying key", tagged).unwrap();
+        assert_eq!(bytes[..7], [b'A', b'R', b'K', b'B', 3, 0, 4]);
+        let tagged: VerifyingKey<Bn254> =
+            deserialize_from(bytes.as_slice(), "verifying key", None, Options::default()).unwrap();
+        assert_eq!(tagged, vk);
+
+        // Another ark-serialize version is only a warning unless strict
+        bytes[5] = 9;
+        let tagged: VerifyingKey<Bn254> =
+            deserialize_from(bytes.as_slice(), "verifying key", None, Options::default()).unwrap();
+        assert_eq!(tagged, vk);
+        let strict = Options {
+            strict: true,
+            ..Default::default()
+        };
+        let err = deserialize_from::<VerifyingKey<Bn254>, _>(bytes.as_slice(), "vk", None, strict)
+            .unwrap_err();
+        assert!(err.to_string().contains("ark-serialize 9.4"));
+        assert!(deserialize_from::<VerifyingKey<Bn254>, _>(
+            &bytes[..6],
+            "vk",
+            None,
+            Options::default()
+        )
+        .is_err());
+
+        let mut bytes = FORMAT_TAG.to_vec();
+        bytes.push(0xff);
+        let err = deserialize_from::<VerifyingKey<Bn254>, _>(
+            bytes.as_slice(),
+            "vk",
+            None,
+            Options::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported artifact format version 255"));
+    }
+
+    #[test]
+    fn test_records_roundtrip() {
+        let mut log = Vec::new();
+        for _ in 0..3 {
+            append_record(
+                &Proof::<Bn254>::default(),
+                &mut log,
+                "proof",
+                Options::default(),
+            )
+            .unwrap();
+        }
+        assert_eq!(log.len(), 3 * (LENGTH_PREFIX_SIZE + proof_size::<Bn254>()));
+
+        let proofs: Vec<Proof<Bn254>> =
+            read_records(log.as_slice(), "proof", Options::default()).unwrap();
+        assert_eq!(proofs, vec![Proof::default(); 3]);
+
+        // A record cut short is reported rather than silently dropped
+        log.truncate(log.len() - 1);
+        assert!(
+            read_records::<Proof<Bn254>, _>(log.as_slice(), "proof", Options::default()).is_err()
+        );
+        log.truncate(2 * (LENGTH_PREFIX_SIZE + proof_size::<Bn254>()) + 3);
+        assert!(
+            read_records::<Proof<Bn254>, _>(log.as_slice(), "proof", Options::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_read_each_record() {
+        let mut log = Vec::new();
+        append_record(
+            &Proof::<Bn254>::default(),
+            &mut log,
+            "proof",
+            Options::default(),
+        )
+        .unwrap();
+        // A record of the right length that is not a proof
+        log.extend((proof_size::<Bn254>() as u64).to_le_bytes());
+        log.extend(vec![0xff; proof_size::<Bn254>()]);
+        append_record(
+            &Proof::<Bn254>::default(),
+            &mut log,
+            "proof",
+            Options::default(),
+        )
+        .unwrap();
+
+        let records: Vec<io::Result<Proof<Bn254>>> =
+            read_each_record(log.as_slice(), "proof", Options::default()).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].is_ok() && records[2].is_ok());
+        let err = records[1].as_ref().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("proof record 1"));
+    }
+}
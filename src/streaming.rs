@@ -0,0 +1,140 @@
+use crate::serialization::{detect_layout, Options};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_groth16::Proof;
+use ark_serialize::{CanonicalDeserialize, Compress, SerializationError, Validate};
+use std::io::{self, Read};
+
+fn read_field<T: CanonicalDeserialize, R: Read>(
+    reader: R,
+    layout: Compress,
+    field: &str,
+) -> io::Result<T> {
+    T::deserialize_with_mode(reader, layout, Validate::Yes).map_err(|e| match e {
+        SerializationError::IoError(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Failed to deserialize verifying key: file appears truncated or corrupt at {}",
+                    field
+                ),
+            )
+        }
+        _ => io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to deserialize verifying key {}: {}", field, e),
+        ),
+    })
+}
+
+/// Verify a Groth16 proof while reading the verifying key, in any layout `deserialize_from`
+/// accepts. Each IC point is folded into the prepared inputs as it is read, so only a fixed
+/// number of points is held at once however many public inputs there are.
+pub fn verify<E: Pairing, R: Read>(
+    verifying_key: R,
+    proof: &Proof<E>,
+    inputs: &[E::ScalarField],
+    options: Options,
+) -> io::Result<bool> {
+    let (layout, mut reader) = detect_layout(verifying_key, options)?;
+
+    let alpha_g1: E::G1Affine = read_field(&mut reader, layout, "alpha_g1")?;
+    let beta_g2: E::G2Affine = read_field(&mut reader, layout, "beta_g2")?;
+    let gamma_g2: E::G2Affine = read_field(&mut reader, layout, "gamma_g2")?;
+    let delta_g2: E::G2Affine = read_field(&mut reader, layout, "delta_g2")?;
+
+    let n_points: u64 = read_field(&mut reader, layout, "gamma_abc_g1 length")?;
+    if n_points != inputs.len() as u64 + 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Failed to verify proof: verifying key has {} IC points, but {} public inputs need {}",
+                n_points,
+                inputs.len(),
+                inputs.len() + 1
+            ),
+        ));
+    }
+
+    let first: E::G1Affine = read_field(&mut reader, layout, "gamma_abc_g1 point 0")?;
+    let mut prepared_inputs = first.into_group();
+    for (i, input) in inputs.iter().enumerate() {
+        let point: E::G1Affine = read_field(
+            &mut reader,
+            layout,
+            &format!("gamma_abc_g1 point {}", i + 1),
+        )?;
+        prepared_inputs += point * input;
+    }
+
+    let qap = E::multi_miller_loop(
+        [proof.a, prepared_inputs.into_affine(), proof.c],
+        [
+            proof.b,
+            (-gamma_g2.into_group()).into_affine(),
+            (-delta_g2.into_group()).into_affine(),
+        ],
+    );
+    let test = E::final_exponentiation(qap).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "Failed to verify proof: pairing check hit the identity",
+        )
+    })?;
+
+    Ok(test == E::pairing(alpha_g1, beta_g2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::{deserialize_from, serialize_to};
+    use ark_bn254::{Bn254, Fr};
+    use ark_groth16::{prepare_verifying_key, Groth16, VerifyingKey};
+
+    const VK: &[u8] = include_bytes!("../test/resources/v0.2.0/vk");
+
+    #[test]
+    fn test_streaming_matches_processed_vk() {
+        let vk: VerifyingKey<Bn254> =
+            deserialize_from(VK, "verifying key", None, Options::default()).unwrap();
+        let proof: Proof<Bn254> = deserialize_from(
+            include_bytes!("../test/resources/v0.2.0/proof").as_slice(),
+            "proof",
+            None,
+            Options::default(),
+        )
+        .unwrap();
+        let pvk = prepare_verifying_key(&vk);
+
+        for input in [1u64, 2] {
+            let inputs = [Fr::from(input)];
+            assert_eq!(
+                verify(VK, &proof, &inputs, Options::default()).unwrap(),
+                Groth16::<Bn254>::verify_proof(&pvk, &proof, &inputs).unwrap()
+            );
+        }
+        assert!(verify(VK, &proof, &[Fr::from(1u64)], Options::default()).unwrap());
+
+        // Tagged keys are read too
+        let mut tagged = Vec::new();
+        let options = Options {
+            tagged: true,
+            ..Default::default()
+        };
+        serialize_to(&vk, &mut tagged, "verifying key", options).unwrap();
+        assert!(verify(
+            tagged.as_slice(),
+            &proof,
+            &[Fr::from(1u64)],
+            Options::default()
+        )
+        .unwrap());
+
+        let err = verify(VK, &proof, &[], Options::default()).unwrap_err();
+        assert!(err.to_string().contains("has 2 IC points"));
+        let truncated = &VK[..VK.len() - 10];
+        let err = verify(truncated, &proof, &[Fr::from(1u64)], Options::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,28 @@
+use log::warn;
+use std::io;
+
+/// Log `message` as a warning, or fail with it if `strict`, as the CLI's `--strict` flag asks.
+pub fn warn(strict: bool, message: &str) -> io::Result<()> {
+    if strict {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} (--strict)", message),
+        ));
+    }
+    warn!("{}", message);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warn() {
+        assert!(warn(false, "no inputs").is_ok());
+
+        let err = warn(true, "no inputs").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "no inputs (--strict)");
+    }
+}
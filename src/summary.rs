@@ -0,0 +1,75 @@
+use ark_ec::pairing::Pairing;
+use ark_groth16::ProvingKey;
+use std::fmt;
+use std::path::PathBuf;
+
+/// What a trusted setup produced, for checking it against the expected circuit dimensions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SetupSummary {
+    pub g1_elements: usize,
+    pub g2_elements: usize,
+    pub ic_elements: usize,
+    pub public_inputs: usize,
+    /// Every file written by the setup with its size in bytes
+    pub files: Vec<(PathBuf, u64)>,
+}
+
+impl SetupSummary {
+    /// Count the elements of a proving key, including the verifying key it embeds.
+    pub fn of<E: Pairing>(pk: &ProvingKey<E>, public_inputs: usize) -> Self {
+        let vk = &pk.vk;
+        SetupSummary {
+            g1_elements: 3
+                + vk.gamma_abc_g1.len()
+                + pk.a_query.len()
+                + pk.b_g1_query.len()
+                + pk.h_query.len()
+                + pk.l_query.len(),
+            g2_elements: 3 + pk.b_g2_query.len(),
+            ic_elements: vk.gamma_abc_g1.len(),
+            public_inputs,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for SetupSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "proving key: {} G1 elements, {} G2 elements",
+            self.g1_elements, self.g2_elements
+        )?;
+        writeln!(f, "verifying key ic: {} elements", self.ic_elements)?;
+        write!(f, "public inputs: {}", self.public_inputs)?;
+        for (path, size) in &self.files {
+            write!(f, "\n{}: {} bytes", path.display(), size)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Bn254;
+    use arkworks_bridge::r1cs::{parse_r1cs_file, R1CS};
+    use arkworks_bridge::setup_with;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_summary_of_fixture() {
+        let r1cs: R1CS<Bn254> =
+            parse_r1cs_file(include_str!("../test/resources/prog-r1cs.jsonl").as_bytes())
+                .unwrap()
+                .into();
+        let (pk, _) = setup_with(r1cs, &mut StdRng::seed_from_u64(0)).unwrap();
+        let summary = SetupSummary::of(&pk, 1);
+
+        // One public input plus the constant one
+        assert_eq!(summary.ic_elements, 2);
+        assert_eq!(summary.g2_elements, 3 + pk.b_g2_query.len());
+        assert!(summary.to_string().ends_with("public inputs: 1"));
+    }
+}
@@ -1,7 +1,14 @@
+use crate::eip2537;
+use crate::eth::G2Order;
+use ark_bls12_381::Bls12_381;
 use ark_circom::ethereum::VerifyingKey;
 use ethers_core::abi::AbiEncode;
-use serde_json::json;
-use std::collections::HashMap;
+use ethers_core::utils::hex;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::str::FromStr;
 use tera::{Context, Tera};
 
 /*
@@ -16,13 +23,27 @@ https://github.com/yi-sun/circom-pairing/blob/master/docs/README.md#fp2-element
 However, in ethereum the convention is the opposite (of course):
 https://github.com/ethereum/EIPs/blob/master/EIPS/eip-197.md#encoding
 
+The order is applied to the verifying key before it reaches the template, which writes G2
+coefficients as given.
+
 */
 
 fn prepare_data_for_template(
     vk: &VerifyingKey,
     n_inputs: usize,
-) -> HashMap<String, serde_json::Value> {
-    let mut context = HashMap::new();
+    g2_order: G2Order,
+) -> BTreeMap<String, serde_json::Value> {
+    let mut context = vk_data(vk, g2_order);
+    context.insert("n_public".to_string(), json!(n_inputs));
+    context
+}
+
+// The points of one verifying key, as the templates refer to them
+fn vk_data(vk: &VerifyingKey, g2_order: G2Order) -> BTreeMap<String, serde_json::Value> {
+    let vk = g2_order.apply_to_vk(vk);
+
+    // Ordered so the context is built the same way on every run
+    let mut context = BTreeMap::new();
 
     // Convert G1 and G2 types to Tera-compatible format
     let alpha1 = vk.alpha1.as_tuple();
@@ -67,23 +88,310 @@ fn prepare_data_for_template(
 
     context.insert("IC_length".to_string(), json!(ic.len()));
 
-    context.insert("n_public".to_string(), json!(n_inputs));
-
     context
 }
 
+/// Language of a generated verifier. Solidity is the only one with curve operations built in, the
+/// others are generic over a BN254 implementation supplied by the integrator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    Solidity,
+    /// Cairo 1 for Starknet
+    Cairo,
+    Noir,
+}
+
+impl Language {
+    pub const VARIANTS: &'static [&'static str] = &["solidity", "cairo", "noir"];
+
+    fn template(self) -> &'static str {
+        match self {
+            Language::Solidity => "verifier_groth16",
+            Language::Cairo => "verifier_groth16_cairo",
+            Language::Noir => "verifier_groth16_noir",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solidity" => Ok(Language::Solidity),
+            "cairo" => Ok(Language::Cairo),
+            "noir" => Ok(Language::Noir),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown language {}, expected solidity, cairo or noir", s),
+            )),
+        }
+    }
+}
+
+// Split a 0x-prefixed 256-bit hex word into its high and low 128-bit halves, `0xhi, 0xlo`
+fn u128_limbs(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let hex = value
+        .as_str()
+        .and_then(|word| word.strip_prefix("0x"))
+        .filter(|digits| digits.len() == 64)
+        .ok_or_else(|| {
+            tera::Error::msg(format!("u128_limbs expects a 256-bit word, got {}", value))
+        })?;
+    let (high, low) = hex.split_at(32);
+    Ok(json!(format!("0x{}, 0x{}", high, low)))
+}
+
+const PAIRING_TEMPLATE: &str = include_str!("./pairing.sol.tera");
 const TEMPLATE: &str = include_str!("./verifier_groth16.sol.tera");
+const MULTI_TEMPLATE: &str = include_str!("./multi_verifier_groth16.sol.tera");
+const CAIRO_TEMPLATE: &str = include_str!("./verifier_groth16.cairo.tera");
+const NOIR_TEMPLATE: &str = include_str!("./verifier_groth16.nr.tera");
+const EIP2537_TEMPLATE: &str = include_str!("./verifier_groth16_eip2537.sol.tera");
 
-pub fn render_contract(vk: &VerifyingKey, n_inputs: usize) -> tera::Result<String> {
+fn templates() -> tera::Result<Tera> {
     let mut tera = Tera::default();
-    tera.add_raw_template("verifier_groth16", TEMPLATE)?;
+    tera.register_filter("u128_limbs", u128_limbs);
+    tera.add_raw_templates(vec![
+        ("pairing", PAIRING_TEMPLATE),
+        ("verifier_groth16", TEMPLATE),
+        ("multi_verifier_groth16", MULTI_TEMPLATE),
+        ("verifier_groth16_cairo", CAIRO_TEMPLATE),
+        ("verifier_groth16_noir", NOIR_TEMPLATE),
+        ("verifier_groth16_eip2537", EIP2537_TEMPLATE),
+    ])?;
+    Ok(tera)
+}
 
-    let data = prepare_data_for_template(vk, n_inputs);
+pub fn render_contract(
+    vk: &VerifyingKey,
+    n_inputs: usize,
+    g2_order: G2Order,
+    language: Language,
+) -> tera::Result<String> {
+    let tera = templates()?;
+
+    let data = prepare_data_for_template(vk, n_inputs, g2_order);
 
     let mut context = Context::new();
     for (key, value) in data {
         context.insert(key, &value);
     }
 
-    tera.render("verifier_groth16", &context)
+    tera.render(language.template(), &context)
+}
+
+/// One contract verifying proofs for any of `vks`, selected by their index as the circuit id.
+/// The keys may have different numbers of public inputs.
+pub fn render_multi_contract(vks: &[VerifyingKey], g2_order: G2Order) -> tera::Result<String> {
+    let tera = templates()?;
+
+    let vks: Vec<_> = vks.iter().map(|vk| vk_data(vk, g2_order)).collect();
+    let mut context = Context::new();
+    context.insert("vks", &vks);
+
+    tera.render("multi_verifier_groth16", &context)
+}
+
+/// A Solidity verifier for a BLS12-381 key, calling the EIP-2537 precompiles. Its `verifyProof`
+/// takes the proof points as `eip2537::EthProof` encodes them.
+pub fn render_eip2537_contract(
+    vk: &ark_groth16::VerifyingKey<Bls12_381>,
+    n_inputs: usize,
+) -> tera::Result<String> {
+    let tera = templates()?;
+
+    let g1 = |point| hex::encode(eip2537::g1_bytes(point));
+    let neg_g2 = |point: &ark_bls12_381::G2Affine| hex::encode(eip2537::g2_bytes(&-*point));
+    let ic: Vec<_> = vk.gamma_abc_g1.iter().map(g1).collect();
+
+    let mut context = Context::new();
+    context.insert("vk_alpha_1", &g1(&vk.alpha_g1));
+    context.insert("vk_neg_beta_2", &neg_g2(&vk.beta_g2));
+    context.insert("vk_neg_gamma_2", &neg_g2(&vk.gamma_g2));
+    context.insert("vk_neg_delta_2", &neg_g2(&vk.delta_g2));
+    context.insert("IC_length", &ic.len());
+    context.insert("IC", &ic);
+    context.insert("n_public", &n_inputs);
+
+    tera.render("verifier_groth16_eip2537", &context)
+}
+
+/// Hex-encoded SHA-256 of a rendered contract, for pinning the reviewed output.
+pub fn contract_hash(contract: &str) -> String {
+    hex::encode(Sha256::digest(contract.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contract_hash() {
+        assert_eq!(
+            contract_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_g2_order_in_contract() {
+        let beta2 = ark_circom::ethereum::G2 {
+            x: [1.into(), 2.into()],
+            y: [3.into(), 4.into()],
+        };
+        let vk = VerifyingKey {
+            beta2,
+            ic: vec![Default::default(); 2],
+            ..Default::default()
+        };
+
+        let beta = |order| {
+            let data = prepare_data_for_template(&vk, 1, order);
+            data["vk_beta_2"].clone()
+        };
+        let hex = |n: u64| ethers_core::types::U256::from(n).encode_hex();
+
+        assert_eq!(
+            beta(G2Order::Eth),
+            json!([[hex(2), hex(1)], [hex(4), hex(3)]])
+        );
+        assert_eq!(
+            beta(G2Order::Arkworks),
+            json!([[hex(1), hex(2)], [hex(3), hex(4)]])
+        );
+    }
+
+    #[test]
+    fn test_render_is_byte_stable() {
+        let vk: VerifyingKey = ark_groth16::VerifyingKey::<ark_bn254::Bn254> {
+            gamma_abc_g1: vec![Default::default(); 2],
+            ..Default::default()
+        }
+        .into();
+
+        let first = render_contract(&vk, 1, G2Order::Eth, Language::Solidity).unwrap();
+        for _ in 0..4 {
+            assert_eq!(
+                render_contract(&vk, 1, G2Order::Eth, Language::Solidity).unwrap(),
+                first
+            );
+        }
+    }
+
+    #[test]
+    fn test_n_public_matches_ic() {
+        let vk: VerifyingKey = ark_groth16::VerifyingKey::<ark_bn254::Bn254> {
+            gamma_abc_g1: vec![Default::default(); 4],
+            ..Default::default()
+        }
+        .into();
+
+        let contract =
+            render_contract(&vk, vk.ic.len() - 1, G2Order::Eth, Language::Solidity).unwrap();
+        assert!(contract.contains("Pairing.G1Point[4] IC;"));
+        assert!(contract.contains("uint[3] memory input"));
+        assert!(contract.contains("vk.IC[3] = "));
+        assert!(!contract.contains("vk.IC[4] = "));
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let vk: VerifyingKey = ark_groth16::VerifyingKey::<ark_bn254::Bn254> {
+            gamma_abc_g1: vec![Default::default(); 3],
+            ..Default::default()
+        }
+        .into();
+
+        let cairo = render_contract(&vk, 2, G2Order::Eth, Language::Cairo).unwrap();
+        assert!(cairo.contains("pub const N_PUBLIC: usize = 2;"));
+        assert!(cairo.contains("pub fn verify<impl C: Bn254>"));
+        assert_eq!(cairo.matches("G1Point {\n            x:").count(), 3);
+
+        let noir = render_contract(&vk, 2, G2Order::Eth, Language::Noir).unwrap();
+        assert!(noir.contains("pub fn ic() -> [G1Point; 3]"));
+        assert!(noir.contains("input: [Field; 2]"));
+        assert!(!noir.contains("{{"));
+
+        let word = ethers_core::types::U256::from(1).encode_hex();
+        assert_eq!(
+            u128_limbs(&json!(word), &HashMap::new()).unwrap(),
+            json!(format!("0x{}, 0x{}1", "0".repeat(32), "0".repeat(31)))
+        );
+        assert!(u128_limbs(&json!("0x1"), &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_eip2537_contract() {
+        let vk = ark_groth16::VerifyingKey::<Bls12_381> {
+            gamma_abc_g1: vec![Default::default(); 3],
+            ..Default::default()
+        };
+
+        let contract = render_eip2537_contract(&vk, 2).unwrap();
+        assert!(contract.contains("uint256[2] calldata input"));
+        assert!(contract.contains("bytes constant IC2 = hex\""));
+        assert!(!contract.contains("IC3"));
+        assert!(contract.contains("IC2, input[1]"));
+        assert!(!contract.contains("{{"));
+    }
+
+    // The contract's pairing check, with the negated G2 points of the key, holds for a real proof
+    #[test]
+    fn test_eip2537_pairing_check() {
+        use crate::r1cs::{parse_r1cs_file, R1CS};
+        use crate::witness::{parse_witness_file, Witness};
+        use crate::{prove_with, setup_with};
+        use ark_ec::pairing::Pairing;
+        use ark_ec::{AffineRepr, CurveGroup};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        // The fixture circuit with its -1 coefficient over the BLS12-381 scalar field
+        let r1cs = include_str!("../../test/resources/prog-r1cs.jsonl").replace(
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616",
+            "52435875175126190479447740508185965837690552500527637822603658699938581184512",
+        );
+        let r1cs: R1CS<Bls12_381> = parse_r1cs_file(r1cs.as_bytes()).unwrap().into();
+        let witness: Witness<Bls12_381> =
+            parse_witness_file(include_str!("../../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+        let mut rng = StdRng::seed_from_u64(0);
+        let (pk, vk) = setup_with(r1cs.clone(), &mut rng).unwrap();
+        let proof = prove_with(&pk, r1cs, witness, false, &mut rng).unwrap();
+
+        let input = ark_bls12_381::Fr::from(1u64);
+        let vk_x = (vk.gamma_abc_g1[0] + vk.gamma_abc_g1[1] * input).into_affine();
+        let check = |a: ark_bls12_381::G1Affine| {
+            Bls12_381::multi_pairing(
+                [a, vk.alpha_g1, vk_x, proof.c],
+                [proof.b, -vk.beta_g2, -vk.gamma_g2, -vk.delta_g2],
+            )
+            .0 == <Bls12_381 as Pairing>::TargetField::from(1u64)
+        };
+        assert!(check(proof.a));
+        assert!(!check(
+            (proof.a + ark_bls12_381::G1Affine::generator()).into_affine()
+        ));
+    }
+
+    #[test]
+    fn test_multi_contract_has_every_key() {
+        let vk = |n_inputs: usize| -> VerifyingKey {
+            ark_groth16::VerifyingKey::<ark_bn254::Bn254> {
+                gamma_abc_g1: vec![Default::default(); n_inputs + 1],
+                ..Default::default()
+            }
+            .into()
+        };
+
+        let contract = render_multi_contract(&[vk(1), vk(3)], G2Order::Eth).unwrap();
+        assert!(contract.contains("contract MultiVerifier"));
+        assert!(contract.contains("library Pairing"));
+        assert!(contract.contains("return 2;"));
+        assert!(contract.contains("if (id == 1)"));
+        assert!(contract.contains("vk.IC = new Pairing.G1Point[](2);"));
+        assert!(contract.contains("vk.IC = new Pairing.G1Point[](4);"));
+    }
 }
@@ -0,0 +1,54 @@
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Run `job` on a worker thread, failing with `TimedOut` if it has not finished within `limit`.
+///
+/// arkworks can't be interrupted mid-computation, so a timed out job keeps running until the
+/// process exits. Callers are expected to return the error and let the process end.
+pub fn run_with_limit<T, F>(limit: Option<Duration>, job: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+{
+    let limit = match limit {
+        Some(limit) => limit,
+        None => return job(),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if we already timed out
+        let _ = sender.send(job());
+    });
+
+    match receiver.recv_timeout(limit) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("Exceeded the time limit of {}s", limit.as_secs_f64()),
+        )),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Worker thread panicked",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_with_limit() {
+        let quick = run_with_limit(Some(Duration::from_secs(10)), || Ok(1));
+        assert_eq!(quick.unwrap(), 1);
+
+        let slow = run_with_limit(Some(Duration::from_millis(10)), || {
+            thread::sleep(Duration::from_secs(1));
+            Ok(())
+        });
+        assert_eq!(slow.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    }
+}
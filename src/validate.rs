@@ -0,0 +1,610 @@
+use crate::error;
+use crate::field::{is_reduced, JsonCoefficient};
+use crate::header::Header;
+use crate::r1cs::{R1CSFile, R1C, R1CS};
+use crate::witness::{binary_element_size, Witness};
+use ark_ec::pairing::Pairing;
+use ark_ff::{One, PrimeField, Zero};
+use num_bigint::BigUint;
+use rand::seq::index;
+use rand::Rng;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Check the header describes a circuit over the scalar field of `E`.
+pub fn check_field<E: Pairing>(header: &Header) -> io::Result<()> {
+    if header.extension_degree != 1 {
+        return Err(invalid(format!(
+            "extension_degree is {}, only prime fields (1) are supported",
+            header.extension_degree
+        )));
+    }
+
+    let modulus: BigUint = E::ScalarField::MODULUS.into();
+    if header.field_characteristic != modulus {
+        if let Some(curve) = &header.curve {
+            return Err(invalid(format!(
+                "curve {} has scalar field modulus {}, not {}",
+                curve, header.field_characteristic, modulus
+            )));
+        }
+        return Err(invalid(format!(
+            "field_characteristic {} does not match the scalar field modulus {}",
+            header.field_characteristic, modulus
+        )));
+    }
+    Ok(())
+}
+
+/// Check the header describes a circuit over the scalar field of `E` with sensible variables.
+pub fn check_header<E: Pairing>(header: &Header) -> io::Result<()> {
+    check_field::<E>(header)?;
+
+    let constant_index = header.constant_index();
+    if constant_index >= header.n_variables {
+        return Err(invalid(format!(
+            "constant_index {} is out of range 0..{}",
+            constant_index, header.n_variables
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    for &v in &header.input_variables {
+        if v >= header.n_variables {
+            return Err(invalid(format!(
+                "input variable {} is out of range 0..{}",
+                v, header.n_variables
+            )));
+        }
+        if v == constant_index {
+            return Err(invalid(format!(
+                "input variable {} is the constant one variable",
+                v
+            )));
+        }
+        if !seen.insert(v) {
+            return Err(invalid(format!("input variable {} is listed twice", v)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the constraint count matches the header and every term refers to a declared variable.
+pub fn check_indices<E: Pairing>(file: &R1CSFile<E>) -> io::Result<()> {
+    if file.header.n_constraints != file.constraints.len() {
+        return Err(invalid(format!(
+            "header declares {} constraints but the file contains {}",
+            file.header.n_constraints,
+            file.constraints.len()
+        )));
+    }
+
+    for (i, constraint) in file.constraints.iter().enumerate() {
+        for (name, terms) in [
+            ("A", &constraint.a),
+            ("B", &constraint.b),
+            ("C", &constraint.c),
+        ] {
+            if let Some((_, v)) = terms.iter().find(|(_, v)| *v >= file.header.n_variables) {
+                return Err(invalid(format!(
+                    "constraint {} references variable {} in {}, but there are only {} variables",
+                    i, v, name, file.header.n_variables
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the circuit declares at least one public input. Without any, a proof only shows some
+/// satisfying witness exists, not that it matches a particular statement.
+pub fn check_public_inputs<E: Pairing>(r1cs: &R1CS<E>) -> io::Result<()> {
+    if r1cs.input_variables.is_empty() {
+        return Err(invalid(
+            "header declares no input_variables, so proofs will not be bound to any public inputs"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Public inputs that no constraint uses with a non-zero coefficient, in index order. The circuit
+/// puts no condition on them, so a prover can prove a statement for any value of them.
+pub fn unconstrained_inputs<E: Pairing>(r1cs: &R1CS<E>) -> Vec<usize> {
+    let mut unused: HashSet<usize> = r1cs.input_variables.iter().copied().collect();
+    for constraint in &r1cs.constraints {
+        for (coeff, v) in constraint
+            .a
+            .iter()
+            .chain(&constraint.b)
+            .chain(&constraint.c)
+        {
+            if !coeff.is_zero() {
+                unused.remove(v);
+            }
+        }
+    }
+    let mut unused: Vec<usize> = unused.into_iter().collect();
+    unused.sort_unstable();
+    unused
+}
+
+/// Check the witness assigns every public input and witness variable of the circuit.
+pub fn check_witness_complete<E: Pairing>(r1cs: &R1CS<E>, witness: &Witness<E>) -> io::Result<()> {
+    if let Some(v) = r1cs
+        .input_variables
+        .iter()
+        .find(|v| !witness.input_variables.contains_key(v))
+    {
+        return Err(invalid(format!(
+            "witness has no value for public input {}",
+            v
+        )));
+    }
+
+    if let Some(v) = r1cs
+        .witness_variables
+        .iter()
+        .find(|v| !witness.witness_variables.contains_key(v))
+    {
+        return Err(invalid(format!(
+            "witness has no value for witness variable {}",
+            v
+        )));
+    }
+
+    Ok(())
+}
+
+// Every variable the circuit expects the witness to assign
+fn circuit_variables(input_variables: &[usize], witness_variables: &[usize]) -> HashSet<usize> {
+    input_variables
+        .iter()
+        .chain(witness_variables)
+        .copied()
+        .collect()
+}
+
+/// Variables the witness assigns that are neither public inputs nor witness variables of the
+/// circuit, including any at or beyond `n_variables`, in index order.
+pub fn extra_witness_variables<E: Pairing>(
+    input_variables: &[usize],
+    witness_variables: &[usize],
+    witness: &Witness<E>,
+) -> Vec<usize> {
+    let expected = circuit_variables(input_variables, witness_variables);
+    let mut extra: Vec<usize> = witness
+        .input_variables
+        .keys()
+        .chain(witness.witness_variables.keys())
+        .filter(|v| !expected.contains(v))
+        .copied()
+        .collect();
+    extra.sort_unstable();
+    extra
+}
+
+/// Check the witness assigns every variable of the circuit. Variables it assigns beyond them are
+/// reported by [`extra_witness_variables`].
+pub fn check_witness_covers<E: Pairing>(
+    input_variables: &[usize],
+    witness_variables: &[usize],
+    witness: &Witness<E>,
+) -> io::Result<()> {
+    let missing = circuit_variables(input_variables, witness_variables)
+        .into_iter()
+        .filter(|v| {
+            !witness.input_variables.contains_key(v) && !witness.witness_variables.contains_key(v)
+        })
+        .min();
+    match missing {
+        None => Ok(()),
+        Some(v) => Err(invalid(format!("witness has no value for variable {}", v))),
+    }
+}
+
+/// Check a witness was generated for the same constant one variable as the circuit.
+pub fn check_constant_index(r1cs_constant: usize, witness_constant: usize) -> io::Result<()> {
+    if r1cs_constant != witness_constant {
+        return Err(invalid(format!(
+            "R1CS uses variable {} as the constant one but the witness uses variable {}",
+            r1cs_constant, witness_constant
+        )));
+    }
+
+    Ok(())
+}
+
+fn value_of<E: Pairing>(witness: &Witness<E>, var: usize) -> Option<E::ScalarField> {
+    if var == witness.constant_index {
+        return Some(E::ScalarField::one());
+    }
+    witness
+        .input_variables
+        .get(&var)
+        .or_else(|| witness.witness_variables.get(&var))
+        .copied()
+}
+
+// Evaluate a linear combination, or name the first of its variables the witness lacks
+fn eval_terms<E: Pairing>(
+    terms: &[(E::ScalarField, usize)],
+    witness: &Witness<E>,
+) -> Result<E::ScalarField, usize> {
+    terms
+        .iter()
+        .try_fold(E::ScalarField::zero(), |acc, (coeff, var)| {
+            Ok(acc + *coeff * value_of(witness, *var).ok_or(*var)?)
+        })
+}
+
+/// Evaluate a linear combination, or `None` if the witness lacks one of its variables.
+pub fn eval_lc<E: Pairing>(
+    terms: &[(E::ScalarField, usize)],
+    witness: &Witness<E>,
+) -> Option<E::ScalarField> {
+    eval_terms(terms, witness).ok()
+}
+
+/// A constraint a witness does not satisfy, with where it came from if the R1CS says.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintViolation<E: Pairing> {
+    /// The constraint uses a variable the witness has no value for
+    Unassigned {
+        index: usize,
+        src: Option<String>,
+        variable: usize,
+    },
+    /// `A·z * B·z`, the left-hand side, differs from `C·z`, the right-hand side
+    Unsatisfied {
+        index: usize,
+        src: Option<String>,
+        lhs: E::ScalarField,
+        rhs: E::ScalarField,
+    },
+}
+
+impl<E: Pairing> fmt::Display for ConstraintViolation<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (index, src) = match self {
+            ConstraintViolation::Unassigned { index, src, .. }
+            | ConstraintViolation::Unsatisfied { index, src, .. } => (index, src),
+        };
+        write!(f, "constraint {}", index)?;
+        if let Some(src) = src {
+            write!(f, " from {}", src)?;
+        }
+        match self {
+            ConstraintViolation::Unassigned { variable, .. } => write!(
+                f,
+                " uses variable {}, which the witness does not assign",
+                variable
+            ),
+            ConstraintViolation::Unsatisfied { lhs, rhs, .. } => {
+                write!(f, " is not satisfied: A·B = {} but C = {}", lhs, rhs)
+            }
+        }
+    }
+}
+
+impl<E: Pairing> Error for ConstraintViolation<E> {}
+
+// An unsatisfied constraint has its own exit code, an unassigned variable is a bad input
+impl<E: Pairing> From<ConstraintViolation<E>> for io::Error {
+    fn from(violation: ConstraintViolation<E>) -> Self {
+        match violation {
+            ConstraintViolation::Unassigned { .. } => invalid(violation.to_string()),
+            ConstraintViolation::Unsatisfied { .. } => error::unsatisfied(violation.to_string()),
+        }
+    }
+}
+
+fn check_constraint<E: Pairing>(
+    index: usize,
+    constraint: &R1C<E>,
+    witness: &Witness<E>,
+) -> Result<(), ConstraintViolation<E>> {
+    let eval = |terms: &[(E::ScalarField, usize)]| {
+        eval_terms(terms, witness).map_err(|variable| ConstraintViolation::Unassigned {
+            index,
+            src: constraint.src.clone(),
+            variable,
+        })
+    };
+    let lhs = eval(&constraint.a)? * eval(&constraint.b)?;
+    let rhs = eval(&constraint.c)?;
+    if lhs != rhs {
+        return Err(ConstraintViolation::Unsatisfied {
+            index,
+            src: constraint.src.clone(),
+            lhs,
+            rhs,
+        });
+    }
+    Ok(())
+}
+
+/// Evaluate every constraint `A·z * B·z == C·z` over the witness, and return the first that
+/// does not hold.
+pub fn first_violation<E: Pairing>(
+    r1cs: &R1CS<E>,
+    witness: &Witness<E>,
+) -> Result<(), ConstraintViolation<E>> {
+    for (i, constraint) in r1cs.constraints.iter().enumerate() {
+        check_constraint(i, constraint, witness)?;
+    }
+
+    Ok(())
+}
+
+/// Check every constraint `A·z * B·z == C·z` holds, reporting the first that does not.
+pub fn check_satisfied<E: Pairing>(r1cs: &R1CS<E>, witness: &Witness<E>) -> io::Result<()> {
+    Ok(first_violation(r1cs, witness)?)
+}
+
+/// Check `samples` constraints chosen uniformly at random without replacement, returning the
+/// failure of each that does not hold, in constraint order. Passing is evidence, not proof, that
+/// the witness satisfies the circuit.
+pub fn spot_check<E: Pairing, R: Rng>(
+    r1cs: &R1CS<E>,
+    witness: &Witness<E>,
+    samples: usize,
+    rng: &mut R,
+) -> Vec<io::Error> {
+    let n_constraints = r1cs.constraints.len();
+    let mut indices = index::sample(rng, n_constraints, samples.min(n_constraints)).into_vec();
+    indices.sort_unstable();
+
+    indices
+        .into_iter()
+        .filter_map(|i| check_constraint(i, &r1cs.constraints[i], witness).err())
+        .map(io::Error::from)
+        .collect()
+}
+
+/// Values in a witness or inputs file that parsing has to reduce into the field, with their
+/// variable. Generators should only emit reduced values, so these point at a generator bug.
+pub fn find_unreduced<E: Pairing, R: BufRead>(reader: R) -> io::Result<Vec<(usize, String)>> {
+    let mut unreduced = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let json: Value =
+            serde_json::from_str(&line).map_err(|e| invalid(format!("line {}: {}", i + 1, e)))?;
+        // Witness files, and optionally inputs files, start with a header object
+        if json.is_object() {
+            continue;
+        }
+
+        let (var, value): (usize, JsonCoefficient) =
+            serde_json::from_value(json).map_err(|e| invalid(format!("line {}: {}", i + 1, e)))?;
+        let value = value
+            .into_string()
+            .map_err(|e| invalid(format!("line {}: {}", i + 1, e)))?;
+        if !is_reduced::<E::ScalarField>(&value) {
+            unreduced.push((var, value));
+        }
+    }
+    Ok(unreduced)
+}
+
+/// `find_unreduced` for a binary witness, where element `i` is variable `i`.
+pub fn find_unreduced_binary<E: Pairing, R: Read>(
+    mut reader: R,
+) -> io::Result<Vec<(usize, String)>> {
+    let modulus: BigUint = E::ScalarField::MODULUS.into();
+    let element_size = binary_element_size::<E::ScalarField>();
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % element_size != 0 {
+        return Err(invalid(format!(
+            "binary witness is {} bytes, which is not a multiple of {}",
+            bytes.len(),
+            element_size
+        )));
+    }
+
+    Ok(bytes
+        .chunks(element_size)
+        .map(BigUint::from_bytes_le)
+        .enumerate()
+        .filter(|(_, value)| *value >= modulus)
+        .map(|(var, value)| (var, value.to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::parse_r1cs_file;
+    use crate::witness::parse_witness_file;
+    use ark_bn254::Bn254;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const R1CS_JSONL: &str = include_str!("../test/resources/prog-r1cs.jsonl");
+    const WITNESS_JSONL: &str = include_str!("../test/resources/prog-witness.jsonl");
+
+    #[test]
+    fn test_fixture_passes_every_check() {
+        let file = parse_r1cs_file::<Bn254, _>(R1CS_JSONL.as_bytes()).unwrap();
+        check_header::<Bn254>(&file.header).unwrap();
+        check_indices(&file).unwrap();
+
+        let r1cs: R1CS<Bn254> = file.into();
+        check_public_inputs(&r1cs).unwrap();
+        let witness: Witness<Bn254> = parse_witness_file(WITNESS_JSONL.as_bytes()).unwrap().into();
+        check_witness_complete(&r1cs, &witness).unwrap();
+        check_satisfied(&r1cs, &witness).unwrap();
+    }
+
+    #[test]
+    fn test_witness_must_cover_the_circuit() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_JSONL.as_bytes()).unwrap().into();
+        let check = |witness: &str| {
+            let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+            check_witness_covers(&r1cs.input_variables, &r1cs.witness_variables, &witness)
+        };
+
+        check(WITNESS_JSONL).unwrap();
+        let missing = WITNESS_JSONL.replace("[2,\"66\"]\n", "");
+        let err = check(&missing).unwrap_err();
+        assert!(err.to_string().contains("variable 2"));
+        // Extra variables are left to `extra_witness_variables`
+        check(&format!("{}\n[3,\"1\"]", WITNESS_JSONL.trim_end())).unwrap();
+    }
+
+    #[test]
+    fn test_extra_witness_variables() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_JSONL.as_bytes()).unwrap().into();
+        let extra = |witness: &str| {
+            let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+            extra_witness_variables(&r1cs.input_variables, &r1cs.witness_variables, &witness)
+        };
+
+        assert!(extra(WITNESS_JSONL).is_empty());
+        // The header declares 3 variables, so 7 and 4 are out of range
+        let stale = format!("{}\n[7,\"1\"]\n[4,\"2\"]", WITNESS_JSONL.trim_end());
+        assert_eq!(extra(&stale), vec![4, 7]);
+    }
+
+    #[test]
+    fn test_constant_index_in_header() {
+        let header = |fields: &str| {
+            let line = R1CS_JSONL.lines().next().unwrap();
+            let header: Header =
+                serde_json::from_str(&line.replace("}", &format!(",{}}}", fields))).unwrap();
+            check_header::<Bn254>(&header)
+        };
+
+        header(r#""constant_index":2"#).unwrap();
+        let err = header(r#""constant_index":1"#).unwrap_err();
+        assert!(err.to_string().contains("is the constant one variable"));
+        assert!(header(r#""constant_index":3"#).is_err());
+    }
+
+    #[test]
+    fn test_named_curve_must_be_bn254() {
+        let line = R1CS_JSONL.lines().next().unwrap();
+        let named = |curve: &str| {
+            let mut header: serde_json::Map<String, Value> = serde_json::from_str(line).unwrap();
+            header.remove("field_characteristic");
+            header.insert("curve".to_string(), curve.into());
+            let header: Header = serde_json::from_value(header.into()).unwrap();
+            check_header::<Bn254>(&header)
+        };
+
+        named("bn254").unwrap();
+        let err = named("bls12-381").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("curve bls12-381 has scalar field modulus"));
+    }
+
+    #[test]
+    fn test_unsatisfied_constraint_is_reported() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_JSONL.as_bytes()).unwrap().into();
+        let witness = WITNESS_JSONL.replace(r#"[2,"66"]"#, r#"[2,"67"]"#);
+        let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+
+        let err = check_satisfied(&r1cs, &witness).unwrap_err();
+        assert!(err.to_string().starts_with("constraint 0 is not satisfied"));
+        assert_eq!(error::classify(&err).exit_code(), 3);
+    }
+
+    #[test]
+    fn test_unsatisfied_constraint_names_its_source() {
+        let witness = WITNESS_JSONL.replace(r#"[2,"66"]"#, r#"[2,"67"]"#);
+        let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+
+        for field in ["src", "label"] {
+            let (header, constraint) = R1CS_JSONL.trim_end().split_once('\n').unwrap();
+            let r1cs = format!(
+                "{}\n{},\"{}\":\"prog.circom:42\"}}",
+                header,
+                constraint.trim_end_matches('}'),
+                field
+            );
+            let r1cs: R1CS<Bn254> = parse_r1cs_file(r1cs.as_bytes()).unwrap().into();
+
+            let err = check_satisfied(&r1cs, &witness).unwrap_err();
+            assert!(err
+                .to_string()
+                .starts_with("constraint 0 from prog.circom:42 is not satisfied"));
+        }
+    }
+
+    #[test]
+    fn test_spot_check() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_JSONL.as_bytes()).unwrap().into();
+        let witness: Witness<Bn254> = parse_witness_file(WITNESS_JSONL.as_bytes()).unwrap().into();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(spot_check(&r1cs, &witness, 100, &mut rng).is_empty());
+
+        let witness = WITNESS_JSONL.replace(r#"[2,"66"]"#, r#"[2,"67"]"#);
+        let witness: Witness<Bn254> = parse_witness_file(witness.as_bytes()).unwrap().into();
+        let failures = spot_check(&r1cs, &witness, r1cs.constraints.len(), &mut rng);
+        assert!(!failures.is_empty());
+        assert!(failures[0]
+            .to_string()
+            .starts_with("constraint 0 is not satisfied"));
+        assert!(spot_check(&r1cs, &witness, 0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn test_unreduced_values_are_found() {
+        assert!(find_unreduced::<Bn254, _>(WITNESS_JSONL.as_bytes())
+            .unwrap()
+            .is_empty());
+
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        let witness = WITNESS_JSONL.replace(r#"[2,"66"]"#, &format!(r#"[2,"{}"]"#, modulus));
+        assert_eq!(
+            find_unreduced::<Bn254, _>(witness.as_bytes()).unwrap(),
+            vec![(2, modulus.to_string())]
+        );
+        assert_eq!(
+            find_unreduced::<Bn254, _>(r#"[1,"-1"]"#.as_bytes()).unwrap(),
+            vec![(1, "-1".to_string())]
+        );
+
+        let mut bytes = vec![0u8; 64];
+        bytes[32..].fill(0xff);
+        let unreduced = find_unreduced_binary::<Bn254, _>(bytes.as_slice()).unwrap();
+        assert_eq!(unreduced.len(), 1);
+        assert_eq!(unreduced[0].0, 1);
+    }
+
+    #[test]
+    fn test_no_public_inputs_is_reported() {
+        let r1cs_jsonl = R1CS_JSONL.replace(r#""input_variables":[1]"#, r#""input_variables":[]"#);
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(r1cs_jsonl.as_bytes()).unwrap().into();
+
+        assert!(r1cs.input_variables.is_empty());
+        assert!(check_public_inputs(&r1cs).is_err());
+    }
+
+    #[test]
+    fn test_unconstrained_inputs() {
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(R1CS_JSONL.as_bytes()).unwrap().into();
+        assert!(unconstrained_inputs(&r1cs).is_empty());
+
+        // Variable 1 only appears with a zero coefficient, and 2 is a second unused input
+        let r1cs_jsonl = R1CS_JSONL
+            .replace(r#""input_variables":[1]"#, r#""input_variables":[2,1]"#)
+            .replace(r#"["11",1]"#, r#"["0",1]"#)
+            .replace(r#"["21888242871839275222246405745257275088548364400416034343698204186575808495616",2]"#, r#"["1",0]"#);
+        let r1cs: R1CS<Bn254> = parse_r1cs_file(r1cs_jsonl.as_bytes()).unwrap().into();
+        assert_eq!(unconstrained_inputs(&r1cs), vec![1, 2]);
+    }
+}
@@ -1,13 +1,17 @@
+use crate::curve::SupportedCurve;
+use crate::field::{to_decimal_string, JsonCoefficient};
 use crate::header::Header;
-use ark_bn254::Bn254;
+use crate::inputs::Inputs;
 use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, One, PrimeField};
+use ark_serialize::CanonicalDeserialize;
 use serde::de::IntoDeserializer;
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
 use std::str::FromStr; // Import IntoDeserializer trait
 
 pub fn deserialize_coeff_var_tuple<'de, D, E>(
@@ -18,10 +22,11 @@ where
     E: Pairing,
     E::ScalarField: FromStr,
 {
-    let (var, coeff): (usize, String) = Deserialize::deserialize(deserializer)?;
-    E::ScalarField::from_str(&coeff)
+    let (var, coeff): (usize, JsonCoefficient) = Deserialize::deserialize(deserializer)?;
+    coeff
+        .parse()
         .map(|field_element| (var, field_element))
-        .map_err(|_| serde::de::Error::custom("Error in ScalarField parser"))
+        .map_err(serde::de::Error::custom)
     // Use Debug formatting
 }
 
@@ -33,6 +38,8 @@ pub struct WitnessFile<E: Pairing> {
 
 #[derive(Debug, Clone)]
 pub struct Witness<E: Pairing> {
+    /// The constant one variable, which is not part of the assignment
+    pub constant_index: usize,
     pub input_variables: HashMap<usize, E::ScalarField>,
     pub witness_variables: HashMap<usize, E::ScalarField>,
 }
@@ -41,23 +48,120 @@ impl<E: Pairing> From<WitnessFile<E>> for Witness<E> {
     fn from(file: WitnessFile<E>) -> Self {
         let mut input_variables: HashMap<usize, E::ScalarField> = HashMap::new();
         let mut witness_variables: HashMap<usize, E::ScalarField> = HashMap::new();
+        let constant_index = file.header.constant_index();
 
         file.witness.into_iter().for_each(|(index, value)| {
             if file.header.input_variables.contains(&index) {
                 input_variables.insert(index, value);
-            } else if index != 0 {
+            } else if index != constant_index {
                 witness_variables.insert(index, value);
             }
         });
 
         Witness {
+            constant_index,
             input_variables,
             witness_variables,
         }
     }
 }
 
-pub fn parse_witness_file(reader: BufReader<File>) -> io::Result<WitnessFile<Bn254>> {
+impl<E: Pairing> Witness<E> {
+    /// Values of the public inputs, ordered by variable index as they are allocated in the circuit
+    pub fn public_inputs(&self) -> Vec<E::ScalarField> {
+        let mut inputs: Vec<(&usize, &E::ScalarField)> = self.input_variables.iter().collect();
+        inputs.sort_by_key(|(index, _)| **index);
+        inputs.into_iter().map(|(_, value)| *value).collect()
+    }
+}
+
+/// Combine witness shards that each assign a disjoint set of variables, e.g. index ranges
+/// generated in parallel.
+pub fn merge_witnesses<E: Pairing>(shards: Vec<Witness<E>>) -> io::Result<Witness<E>> {
+    let mut merged = Witness {
+        constant_index: shards.first().map_or(0, |shard| shard.constant_index),
+        input_variables: HashMap::new(),
+        witness_variables: HashMap::new(),
+    };
+    for shard in shards {
+        if shard.constant_index != merged.constant_index {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Witness shards use different constant one variables, {} and {}",
+                    merged.constant_index, shard.constant_index
+                ),
+            ));
+        }
+        for (values, merged) in [
+            (shard.input_variables, &mut merged.input_variables),
+            (shard.witness_variables, &mut merged.witness_variables),
+        ] {
+            for (index, value) in values {
+                if merged.insert(index, value).is_some() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Variable {} is assigned by more than one witness shard",
+                            index
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Split a witness file into `shards` files of contiguous variable index ranges, as even in size
+/// as possible. Every shard keeps the original header, so `merge_witnesses` reads them back as one.
+pub fn split_witness_file<E: Pairing>(
+    file: &WitnessFile<E>,
+    shards: usize,
+) -> io::Result<Vec<WitnessFile<E>>> {
+    if shards == 0 || shards > file.witness.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Can't split a witness of {} values into {} shards",
+                file.witness.len(),
+                shards
+            ),
+        ));
+    }
+
+    let mut entries = file.witness.clone();
+    entries.sort_by_key(|(index, _)| *index);
+
+    let (size, larger) = (entries.len() / shards, entries.len() % shards);
+    let mut rest = entries.as_slice();
+    let split = (0..shards)
+        .map(|i| {
+            let (shard, tail) = rest.split_at(size + usize::from(i < larger));
+            rest = tail;
+            WitnessFile {
+                header: file.header.clone(),
+                witness: shard.to_vec(),
+            }
+        })
+        .collect();
+    Ok(split)
+}
+
+pub fn write_witness_file<E: Pairing, W: Write>(
+    file: &WitnessFile<E>,
+    mut writer: W,
+) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, &file.header)?;
+    writeln!(writer)?;
+    for (index, value) in &file.witness {
+        serde_json::to_writer(&mut writer, &(index, to_decimal_string(value)))?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+pub fn parse_witness_file<E: Pairing, R: BufRead>(reader: R) -> io::Result<WitnessFile<E>> {
     let mut lines = reader.lines();
 
     // Read and parse witness header line
@@ -73,7 +177,7 @@ pub fn parse_witness_file(reader: BufReader<File>) -> io::Result<WitnessFile<Bn2
         let line = line.expect("Error reading line from witness file");
         let json = serde_json::from_str::<Value>(&line).expect("Error parsing JSON to Value");
         let deserializer = json.into_deserializer();
-        let parsed_data = deserialize_coeff_var_tuple::<_, Bn254>(deserializer)
+        let parsed_data = deserialize_coeff_var_tuple::<_, E>(deserializer)
             .expect("Error in custom deserialization");
         witness_data.push(parsed_data);
     }
@@ -83,3 +187,367 @@ pub fn parse_witness_file(reader: BufReader<File>) -> io::Result<WitnessFile<Bn2
         witness: witness_data,
     })
 }
+
+/// Size of one element in a binary witness file over the field `F`: its limbs, as circom writes.
+pub fn binary_element_size<F: PrimeField>() -> usize {
+    <F::BigInt as BigInteger>::NUM_LIMBS * 8
+}
+
+/// Encoding of a witness file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessFormat {
+    /// JSONL with a header line, as produced for the R1CS file
+    Json,
+    /// Packed little-endian field elements, element `i` being variable `i`. Elements are 32 bytes
+    /// over BN254 and BLS12-381, 48 over BW6-761.
+    Bin,
+    /// Like `Bin`, but each element is the raw Montgomery form arkworks holds in memory. Only
+    /// for generators built on the same arkworks field implementation, and never inferred.
+    Montgomery,
+}
+
+impl WitnessFormat {
+    pub const VARIANTS: &'static [&'static str] = &["json", "bin", "montgomery"];
+
+    /// Use the explicit format if given, otherwise `bin` for `.bin` files and `json` for the rest.
+    pub fn resolve(format: Option<WitnessFormat>, path: &Path) -> WitnessFormat {
+        format.unwrap_or_else(|| match path.extension() {
+            Some(extension) if extension == "bin" => WitnessFormat::Bin,
+            _ => WitnessFormat::Json,
+        })
+    }
+}
+
+impl FromStr for WitnessFormat {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(WitnessFormat::Json),
+            "bin" => Ok(WitnessFormat::Bin),
+            "montgomery" => Ok(WitnessFormat::Montgomery),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown witness format {}, expected json, bin or montgomery",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Read a binary witness. It has no header, so the public inputs are taken from the R1CS. The
+/// element at `constant_index` must be 1, as circom writes it.
+pub fn parse_binary_witness<E: Pairing, R: Read>(
+    reader: R,
+    constant_index: usize,
+    input_variables: &[usize],
+) -> io::Result<Witness<E>> {
+    parse_packed_witness(reader, constant_index, input_variables, |chunk| {
+        Ok(E::ScalarField::from_le_bytes_mod_order(chunk))
+    })
+}
+
+/// Read a binary witness whose elements are in arkworks' internal Montgomery form, skipping
+/// the conversion from canonical form. Only a field element written by the same arkworks
+/// implementation of the curve is meaningful here, so elements of at least the modulus are
+/// rejected rather than reduced.
+pub fn parse_montgomery_witness<E: SupportedCurve, R: Read>(
+    reader: R,
+    constant_index: usize,
+    input_variables: &[usize],
+) -> io::Result<Witness<E>> {
+    parse_packed_witness(reader, constant_index, input_variables, |chunk| {
+        let limbs = <E::ScalarField as PrimeField>::BigInt::deserialize_uncompressed(chunk)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        if limbs >= E::ScalarField::MODULUS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Montgomery witness element is not below the modulus, check the generator \
+                     uses arkworks {} Fr",
+                    E::CURVE.name()
+                ),
+            ));
+        }
+        Ok(E::from_montgomery(limbs))
+    })
+}
+
+// `{"inputs": {...}, "witness": {...}}`, each keyed by variable index
+#[derive(Deserialize)]
+struct Assignment {
+    inputs: BTreeMap<usize, JsonCoefficient>,
+    witness: BTreeMap<usize, JsonCoefficient>,
+}
+
+fn parse_values<F: PrimeField>(
+    values: BTreeMap<usize, JsonCoefficient>,
+    kind: &str,
+) -> io::Result<Vec<(usize, F)>> {
+    values
+        .into_iter()
+        .map(|(index, value)| {
+            value.parse().map(|value| (index, value)).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} variable {}: {}", kind, index, e),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Read the public inputs and the full witness from one JSON object holding both, each keyed by
+/// variable index, and check the inputs are exactly the public variables of the witness. The
+/// object has no header, so the public inputs are taken from the R1CS.
+pub fn parse_assignment<E: Pairing, R: Read>(
+    reader: R,
+    constant_index: usize,
+    input_variables: &[usize],
+) -> io::Result<(Inputs<E>, Witness<E>)> {
+    let assignment: Assignment = serde_json::from_reader(reader).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Failed to parse inputs and witness object: {}", e),
+        )
+    })?;
+
+    let inputs = Inputs {
+        inputs: parse_values(assignment.inputs, "Input")?,
+    };
+
+    let mut witness = Witness {
+        constant_index,
+        input_variables: HashMap::new(),
+        witness_variables: HashMap::new(),
+    };
+    for (index, value) in parse_values(assignment.witness, "Witness")? {
+        if input_variables.contains(&index) {
+            witness.input_variables.insert(index, value);
+        } else if index != constant_index {
+            witness.witness_variables.insert(index, value);
+        }
+    }
+
+    inputs.check_against(&witness)?;
+    Ok((inputs, witness))
+}
+
+// Element `i` of a packed witness is variable `i`
+fn parse_packed_witness<E: Pairing, R: Read, F>(
+    mut reader: R,
+    constant_index: usize,
+    input_variables: &[usize],
+    decode: F,
+) -> io::Result<Witness<E>>
+where
+    F: Fn(&[u8]) -> io::Result<E::ScalarField>,
+{
+    let element_size = binary_element_size::<E::ScalarField>();
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() % element_size != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Binary witness is {} bytes, which is not a multiple of {}",
+                bytes.len(),
+                element_size
+            ),
+        ));
+    }
+
+    let mut input_variables_map = HashMap::new();
+    let mut witness_variables = HashMap::new();
+
+    if bytes.len() / element_size <= constant_index {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Binary witness has {} elements, so none for the constant at index {}",
+                bytes.len() / element_size,
+                constant_index
+            ),
+        ));
+    }
+
+    for (index, chunk) in bytes.chunks(element_size).enumerate() {
+        let value = decode(chunk)
+            .map_err(|e| io::Error::new(e.kind(), format!("Witness element {}: {}", index, e)))?;
+        if index == constant_index {
+            // Otherwise the file is likely in another encoding, or offset
+            if !value.is_one() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Witness element {} is the constant and must be 1, not {}",
+                        index,
+                        to_decimal_string(&value)
+                    ),
+                ));
+            }
+            continue;
+        }
+        if input_variables.contains(&index) {
+            input_variables_map.insert(index, value);
+        } else {
+            witness_variables.insert(index, value);
+        }
+    }
+
+    Ok(Witness {
+        constant_index,
+        input_variables: input_variables_map,
+        witness_variables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::{Bn254, Fr};
+
+    #[test]
+    fn test_binary_witness_matches_json() {
+        let json: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        let mut bytes = Vec::new();
+        for value in [1u64, 1, 66] {
+            let value = <Bn254 as Pairing>::ScalarField::from(value);
+            bytes.extend(value.into_bigint().to_bytes_le());
+        }
+        let bin = parse_binary_witness::<Bn254, _>(bytes.as_slice(), 0, &[1]).unwrap();
+
+        assert_eq!(bin.input_variables, json.input_variables);
+        assert_eq!(bin.witness_variables, json.witness_variables);
+
+        assert!(parse_binary_witness::<Bn254, _>(&bytes[1..], 0, &[1]).is_err());
+        assert!(parse_binary_witness::<Bn254, _>(&bytes[..0], 0, &[1]).is_err());
+        // A witness whose first element is not the constant 1
+        let mut shifted = bytes.clone();
+        shifted[0] = 2;
+        let err = parse_binary_witness::<Bn254, _>(shifted.as_slice(), 0, &[1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().ends_with("must be 1, not 2"));
+        assert_eq!(
+            WitnessFormat::resolve(None, Path::new("witness.bin")),
+            WitnessFormat::Bin
+        );
+    }
+
+    #[test]
+    fn test_montgomery_witness_matches_json() {
+        let json: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        // The limbs as arkworks holds them, not the canonical value
+        let mut bytes = Vec::new();
+        for value in [1u64, 1, 66] {
+            bytes.extend(Fr::from(value).0.to_bytes_le());
+        }
+        let montgomery = parse_montgomery_witness::<Bn254, _>(bytes.as_slice(), 0, &[1]).unwrap();
+        assert_eq!(montgomery.input_variables, json.input_variables);
+        assert_eq!(montgomery.witness_variables, json.witness_variables);
+
+        // Read as canonical, the constant is not 1
+        let err = parse_binary_witness::<Bn254, _>(bytes.as_slice(), 0, &[1]).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Witness element 0 is the constant and must be 1"));
+
+        // Elements of at least the modulus can't come from arkworks, so they are refused
+        bytes[64..].fill(0xff);
+        let err = parse_montgomery_witness::<Bn254, _>(bytes.as_slice(), 0, &[1]).unwrap_err();
+        assert!(err.to_string().starts_with("Witness element 2"));
+    }
+
+    #[test]
+    fn test_assignment_object() {
+        let json: Witness<Bn254> =
+            parse_witness_file(include_str!("../test/resources/prog-witness.jsonl").as_bytes())
+                .unwrap()
+                .into();
+
+        let object = r#"{"inputs": {"1": "1"}, "witness": {"0": 1, "1": "1", "2": "66"}}"#;
+        let (inputs, witness) = parse_assignment::<Bn254, _>(object.as_bytes(), 0, &[1]).unwrap();
+        assert_eq!(inputs.inputs, vec![(1, Fr::from(1u64))]);
+        assert_eq!(witness.input_variables, json.input_variables);
+        assert_eq!(witness.witness_variables, json.witness_variables);
+
+        let mismatch = r#"{"inputs": {"1": "2"}, "witness": {"1": "1", "2": "66"}}"#;
+        let err = parse_assignment::<Bn254, _>(mismatch.as_bytes(), 0, &[1]).unwrap_err();
+        assert!(err.to_string().contains("is 2 in the inputs file but 1"));
+
+        // An input must be a public variable, not just any witness variable
+        let private = r#"{"inputs": {"2": "66"}, "witness": {"1": "1", "2": "66"}}"#;
+        assert!(parse_assignment::<Bn254, _>(private.as_bytes(), 0, &[1]).is_err());
+    }
+
+    #[test]
+    fn test_merge_witnesses() {
+        let header = include_str!("../test/resources/prog-witness.jsonl")
+            .lines()
+            .next()
+            .unwrap();
+        let shard = |lines: &str| -> Witness<Bn254> {
+            parse_witness_file(format!("{}\n{}", header, lines).as_bytes())
+                .unwrap()
+                .into()
+        };
+
+        let merged = merge_witnesses(vec![shard(r#"[1,"1"]"#), shard(r#"[2,"66"]"#)]).unwrap();
+        let whole: Witness<Bn254> = shard("[1,\"1\"]\n[2,\"66\"]");
+        assert_eq!(merged.input_variables, whole.input_variables);
+        assert_eq!(merged.witness_variables, whole.witness_variables);
+
+        let err = merge_witnesses(vec![shard(r#"[2,"66"]"#), shard(r#"[2,"66"]"#)]).unwrap_err();
+        assert!(err.to_string().starts_with("Variable 2"));
+    }
+
+    #[test]
+    fn test_split_witness_round_trips() {
+        let header = include_str!("../test/resources/prog-witness.jsonl")
+            .lines()
+            .next()
+            .unwrap();
+        let file = parse_witness_file::<Bn254, _>(
+            format!("{}\n[2,\"66\"]\n[0,\"1\"]\n[1,\"1\"]", header).as_bytes(),
+        )
+        .unwrap();
+
+        let shards = split_witness_file(&file, 2).unwrap();
+        let indices: Vec<Vec<usize>> = shards
+            .iter()
+            .map(|shard| shard.witness.iter().map(|(index, _)| *index).collect())
+            .collect();
+        assert_eq!(indices, vec![vec![0, 1], vec![2]]);
+
+        // Written and read back, the shards merge into the original witness
+        let read_back = shards
+            .iter()
+            .map(|shard| {
+                let mut bytes = Vec::new();
+                write_witness_file(shard, &mut bytes).unwrap();
+                parse_witness_file::<Bn254, _>(bytes.as_slice())
+                    .unwrap()
+                    .into()
+            })
+            .collect();
+        let merged = merge_witnesses(read_back).unwrap();
+        let whole: Witness<Bn254> = file.into();
+        assert_eq!(merged.input_variables, whole.input_variables);
+        assert_eq!(merged.witness_variables, whole.witness_variables);
+
+        let single =
+            parse_witness_file::<Bn254, _>(format!("{}\n[1,\"1\"]", header).as_bytes()).unwrap();
+        assert!(split_witness_file(&single, 2).is_err());
+    }
+}
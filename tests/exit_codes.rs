@@ -0,0 +1,158 @@
+//! The exit code of each error category, as a script calling the CLI sees it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn resource(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("test/resources")
+        .join(name)
+}
+
+fn bridge(subcommand: &str) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_arkworks-bridge"));
+    command.args(["--log-level", "error", subcommand]);
+    command
+}
+
+fn exit_code(command: &mut Command) -> i32 {
+    command.output().unwrap().status.code().unwrap()
+}
+
+fn setup(dir: &Path, name: &str) -> (PathBuf, PathBuf) {
+    let pk = dir.join(format!("{}-pk", name));
+    let vk = dir.join(format!("{}-vk", name));
+    let mut command = bridge("create-trusted-setup");
+    command
+        .arg("-r")
+        .arg(resource("prog-r1cs.jsonl"))
+        .arg("-p")
+        .arg(&pk)
+        .arg("-v")
+        .arg(&vk);
+    assert_eq!(exit_code(&mut command), 0);
+    (pk, vk)
+}
+
+#[test]
+fn test_parse_and_io_exit_codes() {
+    let not_a_proof = resource("prog-r1cs.jsonl");
+    assert_eq!(
+        exit_code(bridge("inspect-proof").arg("-p").arg(not_a_proof)),
+        2
+    );
+    let missing = resource("missing-proof");
+    assert_eq!(exit_code(bridge("inspect-proof").arg("-p").arg(missing)), 5);
+}
+
+#[test]
+fn test_empty_records_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_, vk) = setup(dir.path(), "records");
+    let proofs = dir.path().join("proofs");
+    fs::write(&proofs, b"").unwrap();
+
+    let mut verify_proof = bridge("verify-proof");
+    verify_proof
+        .arg("-v")
+        .arg(&vk)
+        .arg("-p")
+        .arg(&proofs)
+        .arg("-i")
+        .arg(resource("prog-inputs.jsonl"))
+        .arg("--records");
+    assert_eq!(exit_code(&mut verify_proof), 2);
+}
+
+#[test]
+fn test_continue_on_error_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_, vk) = setup(dir.path(), "continue");
+    let proofs = dir.path().join("proofs");
+    let mut record = 16u64.to_le_bytes().to_vec();
+    record.extend([0xff; 16]);
+    fs::write(&proofs, [record.clone(), record].concat()).unwrap();
+
+    // Every record is tried, and the summary names each that failed
+    let output = bridge("verify-proof")
+        .arg("-v")
+        .arg(&vk)
+        .arg("-p")
+        .arg(&proofs)
+        .arg("-i")
+        .arg(resource("prog-inputs.jsonl"))
+        .args(["--records", "--continue-on-error"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("2 of 2 proofs failed"));
+    assert!(stderr.contains("proof 0: ") && stderr.contains("proof 1: "));
+}
+
+#[test]
+fn test_unsatisfied_and_prove_exit_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    let witness = resource("prog-witness.jsonl");
+
+    // The output no longer matches the input
+    let wrong = dir.path().join("wrong-witness.jsonl");
+    let contents = fs::read_to_string(&witness).unwrap();
+    fs::write(&wrong, contents.replace(r#"[2,"66"]"#, r#"[2,"67"]"#)).unwrap();
+
+    let run_r1cs = |witness: &Path| {
+        let mut command = bridge("run-r1cs");
+        command
+            .arg("-r")
+            .arg(resource("prog-r1cs.jsonl"))
+            .arg("-w")
+            .arg(witness)
+            .arg("-i")
+            .arg(resource("prog-inputs.jsonl"));
+        command
+    };
+    assert_eq!(exit_code(run_r1cs(&wrong).arg("--check-witness")), 3);
+    // Proving can't finish within no time at all
+    assert_eq!(exit_code(run_r1cs(&witness).args(["--time-limit", "0"])), 6);
+}
+
+#[test]
+fn test_verification_failed_exit_codes() {
+    let dir = tempfile::tempdir().unwrap();
+    let (pk, vk) = setup(dir.path(), "a");
+    let (_, other_vk) = setup(dir.path(), "b");
+
+    let mut check_setup = bridge("check-setup");
+    check_setup.arg("-p").arg(&pk).arg("-v").arg(&other_vk);
+    assert_eq!(exit_code(&mut check_setup), 4);
+
+    let mut diff_vk = bridge("diff-vk");
+    diff_vk.arg("-a").arg(&vk).arg("-b").arg(&other_vk);
+    assert_eq!(exit_code(&mut diff_vk), 4);
+
+    let proof = dir.path().join("proof");
+    let mut create_proof = bridge("create-proof");
+    create_proof
+        .arg("-p")
+        .arg(&pk)
+        .arg("-w")
+        .arg(resource("prog-witness.jsonl"))
+        .arg("-r")
+        .arg(resource("prog-r1cs.jsonl"))
+        .arg("--proof")
+        .arg(&proof);
+    assert_eq!(exit_code(&mut create_proof), 0);
+
+    let mut verify_proof = bridge("verify-proof");
+    verify_proof
+        .arg("-v")
+        .arg(&vk)
+        .arg("-p")
+        .arg(&proof)
+        .arg("-i")
+        .arg(resource("prog-inputs.jsonl"))
+        .arg("--commitment")
+        .arg(format!("0x{}", "00".repeat(32)));
+    assert_eq!(exit_code(&mut verify_proof), 4);
+}